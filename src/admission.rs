@@ -0,0 +1,480 @@
+use crate::client::Client;
+use crate::error::Error;
+use crate::subsystem::mac::areq::{AssociateInd, CommStatusInd};
+use crate::subsystem::mac::pib::check_status;
+use crate::subsystem::mac::sreq::AssociateRsp;
+use crate::types::{
+    Address, AssociationStatus, CapabilityInformation, CommEventReason, ExtendedAddress,
+    ShortAddress, Status,
+};
+use std::collections::{HashMap, HashSet};
+use std::io::{Read, Write};
+use std::ops::RangeInclusive;
+
+// Short address of a refused AssociateRsp.
+pub const NO_SHORT_ADDRESS: u16 = 0xffff;
+// Assigned to devices which didn't ask for a short address.
+pub const USE_EXTENDED_ADDRESS: u16 = 0xfffe;
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum JoinOutcome {
+    Joined(ExtendedAddress, ShortAddress),
+    // The AssociateRsp wasn't delivered, e.g. TransactionExpired.
+    Failed(ExtendedAddress, Status),
+}
+
+pub trait AdmissionHandler {
+    fn associate(&mut self, indication: &AssociateInd) -> AssociateRsp;
+
+    // Some outcome if the indication completes a pending association.
+    fn comm_status(&mut self, indication: &CommStatusInd) -> Option<JoinOutcome>;
+
+    // Reverts associate for a response the CoP didn't accept.
+    fn cancel(&mut self, response: &AssociateRsp);
+
+    // Replies to the AssociateInd, the outcome is reported by comm_status
+    // once the CoP delivered the AssociateRsp.
+    fn respond<T: Read + Write>(
+        &mut self,
+        client: &mut Client<T>,
+        indication: &AssociateInd,
+    ) -> Result<AssociateRsp, Error> {
+        let response = self.associate(indication);
+        match client
+            .request(&response)
+            .and_then(|x| check_status(x.status))
+        {
+            Ok(()) => Ok(response),
+            Err(e) => {
+                self.cancel(&response);
+                Err(e)
+            }
+        }
+    }
+}
+
+// The device of the AssociateRsp a CommStatusInd reports on.
+pub fn associate_rsp_device(indication: &CommStatusInd) -> Option<ExtendedAddress> {
+    match (indication.reason, indication.dst_addr) {
+        (CommEventReason::AssociateRsp, Address::Addr64Bit(address)) => Some(address),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AdmissionConfig {
+    // None admits any device which isn't denied.
+    pub allowed: Option<HashSet<ExtendedAddress>>,
+    pub denied: HashSet<ExtendedAddress>,
+    pub max_devices: usize,
+    // Devices with RxOnWhenIdle=false, their frames are held by the CoP until
+    // they poll.
+    pub max_sleepy_devices: usize,
+    pub address_pool: RangeInclusive<u16>,
+}
+
+impl AdmissionConfig {
+    pub fn new() -> AdmissionConfig {
+        AdmissionConfig {
+            allowed: None,
+            denied: HashSet::new(),
+            max_devices: usize::MAX,
+            max_sleepy_devices: usize::MAX,
+            address_pool: 0x0001..=0xfffd,
+        }
+    }
+}
+
+impl Default for AdmissionConfig {
+    fn default() -> Self {
+        AdmissionConfig::new()
+    }
+}
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct Member {
+    pub short_address: ShortAddress,
+    pub capabilities: CapabilityInformation,
+    // Set once an AssociateRsp was delivered to the device.
+    pub joined: bool,
+}
+
+impl Member {
+    pub fn is_sleepy(&self) -> bool {
+        !self
+            .capabilities
+            .contains(CapabilityInformation::RX_ON_IDLE)
+    }
+}
+
+// Admits devices from an allow and deny list up to the capacity limits and
+// allocates their short addresses from a pool.
+#[derive(Debug, Clone)]
+pub struct Admission {
+    config: AdmissionConfig,
+    members: HashMap<ExtendedAddress, Member>,
+    // The member before the pending association, restored if it fails.
+    pending: HashMap<ExtendedAddress, Option<Member>>,
+}
+
+impl Admission {
+    pub fn new(config: AdmissionConfig) -> Admission {
+        Admission {
+            config,
+            members: HashMap::new(),
+            pending: HashMap::new(),
+        }
+    }
+
+    pub fn config(&self) -> &AdmissionConfig {
+        &self.config
+    }
+
+    pub fn config_mut(&mut self) -> &mut AdmissionConfig {
+        &mut self.config
+    }
+
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
+    pub fn get(&self, extended_address: &ExtendedAddress) -> Option<&Member> {
+        self.members.get(extended_address)
+    }
+
+    pub fn members(&self) -> impl Iterator<Item = (&ExtendedAddress, &Member)> {
+        self.members.iter()
+    }
+
+    // Restores a known device, e.g. from the membership of a previous run.
+    pub fn insert(&mut self, extended_address: ExtendedAddress, member: Member) {
+        self.members.insert(extended_address, member);
+    }
+
+    pub fn remove(&mut self, extended_address: &ExtendedAddress) -> Option<Member> {
+        self.pending.remove(extended_address);
+        self.members.remove(extended_address)
+    }
+
+    fn is_allowed(&self, extended_address: &ExtendedAddress) -> bool {
+        !self.config.denied.contains(extended_address)
+            && self
+                .config
+                .allowed
+                .as_ref()
+                .is_none_or(|x| x.contains(extended_address))
+    }
+
+    fn allocate(&self) -> Option<u16> {
+        let used: HashSet<u16> = self
+            .members
+            .values()
+            .map(|x| x.short_address.address)
+            .collect();
+        self.config.address_pool.clone().find(|x| !used.contains(x))
+    }
+
+    fn admit(&mut self, indication: &AssociateInd) -> Result<ShortAddress, AssociationStatus> {
+        let extended_address = indication.extended_address;
+        if !self.is_allowed(&extended_address) {
+            return Err(AssociationStatus::PANAccessDenied);
+        }
+
+        // A known device is checked again only for what its capabilities
+        // change, e.g. when it rejoins after a reset.
+        let previous = self.members.get(&extended_address).copied();
+        let sleepy = !indication
+            .capabilities
+            .contains(CapabilityInformation::RX_ON_IDLE);
+        let sleepy_devices = self.members.values().filter(|x| x.is_sleepy()).count();
+        if (previous.is_none() && self.members.len() >= self.config.max_devices)
+            || (sleepy
+                && !previous.is_some_and(|x| x.is_sleepy())
+                && sleepy_devices >= self.config.max_sleepy_devices)
+        {
+            return Err(AssociationStatus::PANAtCapacity);
+        }
+
+        // A known device keeps its short address.
+        let address = match (
+            indication
+                .capabilities
+                .contains(CapabilityInformation::ALLOC_ADDR),
+            previous.map(|x| x.short_address.address),
+        ) {
+            (true, Some(address)) if address != USE_EXTENDED_ADDRESS => address,
+            (true, _) => self.allocate().ok_or(AssociationStatus::PANAtCapacity)?,
+            (false, _) => USE_EXTENDED_ADDRESS,
+        };
+
+        let short_address = ShortAddress { address };
+        self.members.insert(
+            extended_address,
+            Member {
+                short_address,
+                capabilities: indication.capabilities,
+                joined: previous.is_some_and(|x| x.joined),
+            },
+        );
+        self.pending.insert(extended_address, previous);
+        Ok(short_address)
+    }
+
+    fn restore(&mut self, extended_address: ExtendedAddress, previous: Option<Member>) {
+        match previous {
+            Some(member) => self.members.insert(extended_address, member),
+            None => self.members.remove(&extended_address),
+        };
+    }
+}
+
+impl AdmissionHandler for Admission {
+    fn associate(&mut self, indication: &AssociateInd) -> AssociateRsp {
+        let (assoc_status, address) = match self.admit(indication) {
+            Ok(short_address) => (AssociationStatus::Successful, short_address.address),
+            Err(status) => (status, NO_SHORT_ADDRESS),
+        };
+
+        // Secured like the request of the device.
+        AssociateRsp {
+            extended_address: indication.extended_address,
+            assoc_short_address: ShortAddress { address },
+            assoc_status,
+            key_source: indication.key_source,
+            security_level: indication.security_level,
+            key_id_mode: indication.key_id_mode,
+            key_index: indication.key_index,
+        }
+    }
+
+    fn comm_status(&mut self, indication: &CommStatusInd) -> Option<JoinOutcome> {
+        let extended_address = associate_rsp_device(indication)?;
+        let previous = self.pending.remove(&extended_address)?;
+
+        let member = self.members.get_mut(&extended_address)?;
+        if indication.status == Status::Success {
+            member.joined = true;
+            return Some(JoinOutcome::Joined(extended_address, member.short_address));
+        }
+
+        // Gives the address back, a device which joined before keeps its
+        // previous membership.
+        self.restore(extended_address, previous);
+        Some(JoinOutcome::Failed(extended_address, indication.status))
+    }
+
+    fn cancel(&mut self, response: &AssociateRsp) {
+        if response.assoc_status != AssociationStatus::Successful {
+            return;
+        }
+        if let Some(previous) = self.pending.remove(&response.extended_address) {
+            self.restore(response.extended_address, previous);
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::{Admission, AdmissionConfig, AdmissionHandler, JoinOutcome, USE_EXTENDED_ADDRESS};
+    use crate::client::Client;
+    use crate::client::tests::MockTransport;
+    use crate::subsystem::mac::areq::{AssociateInd, CommStatusInd};
+    use crate::types::{
+        Address, AssociationStatus, CapabilityInformation, CommEventReason, ExtendedAddress,
+        KeyIdMode, KeySource, SecurityLevel, ShortAddress, Status,
+    };
+
+    pub fn extended_address(x: u8) -> ExtendedAddress {
+        ExtendedAddress {
+            address: [0x0, 0x12, 0x4b, 0x0, 0x0, 0x0, 0x0, x],
+        }
+    }
+
+    pub fn associate_ind(x: u8, capabilities: CapabilityInformation) -> AssociateInd {
+        AssociateInd {
+            extended_address: extended_address(x),
+            capabilities,
+            key_source: KeySource { key: [0; 8] },
+            security_level: SecurityLevel::NoSecurity,
+            key_id_mode: KeyIdMode::NotUsed,
+            key_index: 0,
+        }
+    }
+
+    pub fn comm_status_ind(x: u8, status: Status) -> CommStatusInd {
+        CommStatusInd {
+            status,
+            src_addr: Address::Addr16Bit(ShortAddress { address: 0x0 }),
+            dst_addr: Address::Addr64Bit(extended_address(x)),
+            device_pan_id: 0x1234,
+            reason: CommEventReason::AssociateRsp,
+            key_source: KeySource { key: [0; 8] },
+            security_level: SecurityLevel::NoSecurity,
+            key_id_mode: KeyIdMode::NotUsed,
+            key_index: 0,
+        }
+    }
+
+    pub const ROUTER: CapabilityInformation = CapabilityInformation::FFD
+        .union(CapabilityInformation::RX_ON_IDLE)
+        .union(CapabilityInformation::ALLOC_ADDR);
+
+    #[test]
+    fn admit_and_allocate() {
+        let mut config = AdmissionConfig::new();
+        config.denied.insert(extended_address(9));
+        config.max_devices = 3;
+        config.max_sleepy_devices = 1;
+        config.address_pool = 0x10..=0x11;
+        let mut admission = Admission::new(config);
+
+        let response = admission.associate(&associate_ind(1, ROUTER));
+        assert_eq!(response.assoc_status, AssociationStatus::Successful);
+        assert_eq!(response.assoc_short_address.address, 0x10);
+        assert_eq!(
+            admission.comm_status(&comm_status_ind(1, Status::Success)),
+            Some(JoinOutcome::Joined(
+                extended_address(1),
+                ShortAddress { address: 0x10 }
+            ))
+        );
+        assert!(admission.get(&extended_address(1)).unwrap().joined);
+
+        let response = admission.associate(&associate_ind(9, ROUTER));
+        assert_eq!(response.assoc_status, AssociationStatus::PANAccessDenied);
+        assert_eq!(response.assoc_short_address.address, 0xffff);
+
+        // A sleepy device without a short address.
+        let response = admission.associate(&associate_ind(2, CapabilityInformation::empty()));
+        assert_eq!(response.assoc_status, AssociationStatus::Successful);
+        assert_eq!(response.assoc_short_address.address, USE_EXTENDED_ADDRESS);
+        let response = admission.associate(&associate_ind(3, CapabilityInformation::ALLOC_ADDR));
+        assert_eq!(response.assoc_status, AssociationStatus::PANAtCapacity);
+
+        // An undelivered response gives the address back.
+        let response = admission.associate(&associate_ind(4, ROUTER));
+        assert_eq!(response.assoc_short_address.address, 0x11);
+        assert_eq!(
+            admission.comm_status(&comm_status_ind(4, Status::TransactionExpired)),
+            Some(JoinOutcome::Failed(
+                extended_address(4),
+                Status::TransactionExpired
+            ))
+        );
+        assert!(admission.get(&extended_address(4)).is_none());
+        assert_eq!(
+            admission.comm_status(&comm_status_ind(4, Status::Success)),
+            None
+        );
+
+        // The pool is exhausted, known devices still rejoin.
+        admission.config_mut().max_devices = 4;
+        let response = admission.associate(&associate_ind(5, ROUTER));
+        assert_eq!(response.assoc_short_address.address, 0x11);
+        let response = admission.associate(&associate_ind(6, ROUTER));
+        assert_eq!(response.assoc_status, AssociationStatus::PANAtCapacity);
+        let response = admission.associate(&associate_ind(1, ROUTER));
+        assert_eq!(response.assoc_status, AssociationStatus::Successful);
+        assert_eq!(response.assoc_short_address.address, 0x10);
+        assert_eq!(admission.len(), 3);
+    }
+
+    #[test]
+    fn rejoin_with_new_capabilities() {
+        let mut config = AdmissionConfig::new();
+        config.max_sleepy_devices = 1;
+        config.address_pool = 0x10..=0x11;
+        let mut admission = Admission::new(config);
+
+        let response = admission.associate(&associate_ind(1, CapabilityInformation::empty()));
+        assert_eq!(response.assoc_short_address.address, USE_EXTENDED_ADDRESS);
+        admission.comm_status(&comm_status_ind(1, Status::Success));
+        let response = admission.associate(&associate_ind(2, ROUTER));
+        assert_eq!(response.assoc_short_address.address, 0x10);
+        admission.comm_status(&comm_status_ind(2, Status::Success));
+
+        // Becoming sleepy is limited like a new sleepy device.
+        let response = admission.associate(&associate_ind(2, CapabilityInformation::ALLOC_ADDR));
+        assert_eq!(response.assoc_status, AssociationStatus::PANAtCapacity);
+        assert_eq!(
+            admission.get(&extended_address(2)).unwrap().capabilities,
+            ROUTER
+        );
+
+        // Asking for a short address allocates one, the previous membership
+        // is restored if the response isn't delivered.
+        let response = admission.associate(&associate_ind(1, ROUTER));
+        assert_eq!(response.assoc_status, AssociationStatus::Successful);
+        assert_eq!(response.assoc_short_address.address, 0x11);
+        assert_eq!(
+            admission.comm_status(&comm_status_ind(1, Status::TransactionExpired)),
+            Some(JoinOutcome::Failed(
+                extended_address(1),
+                Status::TransactionExpired
+            ))
+        );
+        let member = admission.get(&extended_address(1)).unwrap();
+        assert_eq!(member.short_address.address, USE_EXTENDED_ADDRESS);
+        assert!(member.is_sleepy() && member.joined);
+
+        // Dropping ALLOC_ADDR gives the short address back.
+        let capabilities = CapabilityInformation::FFD | CapabilityInformation::RX_ON_IDLE;
+        let response = admission.associate(&associate_ind(2, capabilities));
+        assert_eq!(response.assoc_short_address.address, USE_EXTENDED_ADDRESS);
+        let response = admission.associate(&associate_ind(1, ROUTER));
+        assert_eq!(response.assoc_short_address.address, 0x10);
+    }
+
+    #[test]
+    fn respond_rolls_back() {
+        let mut admission = Admission::new(AdmissionConfig::new());
+        let mut client = Client::new(MockTransport::new(&[&[0x1, 0x62, 0x50, 0x19]]));
+
+        match admission.respond(&mut client, &associate_ind(1, ROUTER)) {
+            Err(error) => assert_eq!(error.status(), Some(Status::BadState)),
+            x => panic!("Unexpected result {:?}", x),
+        }
+        assert!(admission.is_empty());
+        assert_eq!(
+            admission.comm_status(&comm_status_ind(1, Status::Success)),
+            None
+        );
+
+        let response = admission.associate(&associate_ind(1, ROUTER));
+        assert_eq!(response.assoc_short_address.address, 0x1);
+    }
+
+    #[test]
+    fn allow_list_and_respond() {
+        let mut config = AdmissionConfig::new();
+        config.allowed = Some([extended_address(1)].into_iter().collect());
+        let mut admission = Admission::new(config);
+        let mut client = Client::new(MockTransport::new(&[
+            &[0x1, 0x62, 0x50, 0x0],
+            &[0x1, 0x62, 0x50, 0x0],
+        ]));
+
+        let response = admission
+            .respond(&mut client, &associate_ind(2, ROUTER))
+            .unwrap();
+        assert_eq!(response.assoc_status, AssociationStatus::PANAccessDenied);
+
+        let response = admission
+            .respond(&mut client, &associate_ind(1, ROUTER))
+            .unwrap();
+        assert_eq!(response.assoc_short_address.address, 0x1);
+
+        let output = &client.get_ref().output;
+        assert_eq!(output[..4], [0xfe, 0x16, 0x22, 0x50]);
+        assert_eq!(
+            output[27..41],
+            [
+                0xfe, 0x16, 0x22, 0x50, 0x1, 0x0, 0x0, 0x0, 0x0, 0x4b, 0x12, 0x0, 0x1, 0x0
+            ]
+        );
+    }
+}
@@ -0,0 +1,339 @@
+use crate::client::{is_command_error, is_extended_status};
+use crate::codec::MTCodec;
+use crate::command::{Command, Request};
+use crate::error::Error;
+use crate::fragmentation::{Reassembler, fragment, fragment_status};
+use crate::frame::{CommandCode, MAX_DATA_LENGTH, MTFrame};
+use crate::subsystem::mac::pib::{Attribute, BeaconPayload, BeaconPayloadLength};
+use crate::subsystem::mac::security_pib::{SecurityTables, SecurityTablesReader, TableEntry};
+use crate::subsystem::rpc::MTCommandError;
+use crate::types::{CommandType, MTExtendedHeaderStatus};
+use futures_util::{SinkExt, Stream, StreamExt};
+use std::io::ErrorKind;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite, WriteHalf};
+use tokio::sync::{Mutex, mpsc};
+use tokio::task::JoinHandle;
+use tokio_util::codec::{FramedRead, FramedWrite};
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(1);
+
+type Writer<T> = Arc<Mutex<FramedWrite<WriteHalf<T>, MTCodec>>>;
+
+pub struct AsyncClient<T> {
+    writer: Writer<T>,
+    responses: mpsc::UnboundedReceiver<MTFrame>,
+    reader: JoinHandle<()>,
+    timeout: Duration,
+}
+
+pub struct Indications {
+    receiver: mpsc::UnboundedReceiver<MTFrame>,
+}
+
+impl<T> AsyncClient<T>
+where
+    T: AsyncRead + AsyncWrite + Send + 'static,
+{
+    // Must be called from within a tokio runtime, as the transport is read by
+    // a spawned task that routes SRSPs to the client and AREQs to the stream.
+    pub fn new(transport: T) -> (AsyncClient<T>, Indications) {
+        let (read_half, write_half) = tokio::io::split(transport);
+        let (response_sender, responses) = mpsc::unbounded_channel();
+        let (indication_sender, receiver) = mpsc::unbounded_channel();
+        let writer = Arc::new(Mutex::new(FramedWrite::new(write_half, MTCodec::new())));
+        let ack_writer = writer.clone();
+
+        let reader = tokio::spawn(async move {
+            let mut frames = FramedRead::new(read_half, MTCodec::new());
+            let mut reassembler = Reassembler::new();
+
+            while let Some(Ok(frame)) = frames.next().await {
+                // Broken fragment sequences are dropped like corrupted frames.
+                let frame = reassembler.push(frame);
+
+                while let Some(ack) = reassembler.pop_ack() {
+                    if ack_writer.lock().await.send(ack).await.is_err() {
+                        return;
+                    }
+                }
+
+                let Ok(Some(frame)) = frame else {
+                    continue;
+                };
+
+                // Fragmentation acks are awaited by the client, whatever their type.
+                let sender = match frame.header.command.cmd_type {
+                    CommandType::AREQ if fragment_status(&frame).is_none() => &indication_sender,
+                    _ => &response_sender,
+                };
+                // A closed receiver only means nobody is listening anymore.
+                let _ = sender.send(frame);
+            }
+        });
+
+        let client = AsyncClient {
+            writer,
+            responses,
+            reader,
+            timeout: DEFAULT_TIMEOUT,
+        };
+
+        (client, Indications { receiver })
+    }
+
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    // Payloads too long for a single MT frame are sent as V2 fragments, in
+    // which case the ack of each block is waited for.
+    pub async fn send(&mut self, frame: &MTFrame) -> Result<(), Error> {
+        if frame.payload.len() > MAX_DATA_LENGTH {
+            return self
+                .send_fragmented(&frame.header.command, &frame.payload)
+                .await;
+        }
+
+        self.writer.lock().await.send(frame).await
+    }
+
+    pub async fn request_frame(&mut self, frame: MTFrame) -> Result<MTFrame, Error> {
+        self.discard_responses();
+        self.send(&frame).await?;
+        self.wait_response(&frame.header.command).await
+    }
+
+    // Same as request_frame, for payloads that may need to be fragmented,
+    // e.g. a MAC_DATA_REQ carrying a full-size 802.15.4g frame.
+    pub async fn request_payload(
+        &mut self,
+        command: CommandCode,
+        payload: Vec<u8>,
+    ) -> Result<MTFrame, Error> {
+        if payload.len() > MAX_DATA_LENGTH {
+            self.discard_responses();
+            self.send_fragmented(&command, &payload).await?;
+            return self.wait_response(&command).await;
+        }
+
+        self.request_frame(MTFrame::from_payload(command, payload)?)
+            .await
+    }
+
+    pub async fn request<R: Request>(&mut self, request: &R) -> Result<R::Response, Error> {
+        let response = self
+            .request_payload(R::command_code(), request.encode())
+            .await?;
+        R::Response::try_decode(&response.payload)
+    }
+
+    pub async fn get<A: Attribute>(&mut self) -> Result<A, Error> {
+        let response = self.request(&A::get_req()).await?;
+        A::from_get_rsp(response)
+    }
+
+    pub async fn set<A: Attribute>(&mut self, attribute: &A) -> Result<(), Error> {
+        let response = self.request(&attribute.set_req()?).await?;
+        A::check_set_rsp(response)
+    }
+
+    pub async fn get_beacon_payload(&mut self) -> Result<BeaconPayload, Error> {
+        let length = self.get::<BeaconPayloadLength>().await?;
+        Ok(self.get::<BeaconPayload>().await?.truncate(&length))
+    }
+
+    pub async fn get_entry<E: TableEntry>(&mut self, index1: u16, index2: u16) -> Result<E, Error> {
+        let response = self.request(&E::get_req(index1, index2)).await?;
+        E::from_get_rsp(response)
+    }
+
+    pub async fn set_entry<E: TableEntry>(
+        &mut self,
+        entry: &E,
+        index1: u16,
+        index2: u16,
+    ) -> Result<(), Error> {
+        let response = self.request(&entry.set_req(index1, index2)?).await?;
+        E::check_set_rsp(response)
+    }
+
+    pub async fn read_security_tables(&mut self) -> Result<SecurityTables, Error> {
+        let mut reader = SecurityTablesReader::new();
+        while let Some(request) = reader.next_request() {
+            let response = self.request(&request).await?;
+            reader.handle_response(response)?;
+        }
+        Ok(reader.into_tables())
+    }
+
+    // Responses that arrived after an earlier request timed out are stale.
+    fn discard_responses(&mut self) {
+        while self.responses.try_recv().is_ok() {}
+    }
+
+    async fn receive_response(&mut self) -> Result<MTFrame, Error> {
+        self.responses
+            .recv()
+            .await
+            .ok_or(Error::Io(ErrorKind::UnexpectedEof.into()))
+    }
+
+    async fn wait_response(&mut self, request: &CommandCode) -> Result<MTFrame, Error> {
+        let timeout = self.timeout;
+        let wait_response = async {
+            loop {
+                let frame = self.receive_response().await?;
+                let command = &frame.header.command;
+
+                if is_command_error(&frame) {
+                    let error = MTCommandError::try_decode(frame.payload.as_slice())?;
+                    return Err(Error::MTCommandError(error));
+                }
+
+                if command.cmd_type == CommandType::SRSP
+                    && command.subsystem == request.subsystem
+                    && command.id == request.id
+                    && fragment_status(&frame).is_none()
+                {
+                    return Ok(frame);
+                }
+            }
+        };
+
+        tokio::time::timeout(timeout, wait_response)
+            .await
+            .map_err(|_| Error::Timeout)?
+    }
+
+    async fn send_fragmented(
+        &mut self,
+        request: &CommandCode,
+        payload: &[u8],
+    ) -> Result<(), Error> {
+        let timeout = self.timeout;
+
+        for (block, fragment) in fragment(request, payload)?.iter().enumerate() {
+            self.writer.lock().await.send(fragment).await?;
+
+            let wait_ack = async {
+                loop {
+                    let frame = self.receive_response().await?;
+                    let command = &frame.header.command;
+
+                    let status = match fragment_status(&frame) {
+                        Some((acked_block, status))
+                            if command.subsystem == request.subsystem
+                                && command.id == request.id
+                                && (acked_block as usize == block
+                                    || is_extended_status(&frame)) =>
+                        {
+                            status
+                        }
+                        _ => continue,
+                    };
+
+                    match status {
+                        MTExtendedHeaderStatus::Success
+                        | MTExtendedHeaderStatus::FragmentationSequenceCompleted => return Ok(()),
+                        MTExtendedHeaderStatus::ResendLastFrame => {
+                            self.writer.lock().await.send(fragment).await?
+                        }
+                        status => return Err(Error::Fragmentation(status)),
+                    }
+                }
+            };
+
+            tokio::time::timeout(timeout, wait_ack)
+                .await
+                .map_err(|_| Error::Timeout)??;
+        }
+
+        Ok(())
+    }
+}
+
+impl<T> Drop for AsyncClient<T> {
+    fn drop(&mut self) {
+        self.reader.abort();
+    }
+}
+
+impl Indications {
+    pub async fn recv(&mut self) -> Option<MTFrame> {
+        self.receiver.recv().await
+    }
+
+    pub fn try_recv(&mut self) -> Option<MTFrame> {
+        self.receiver.try_recv().ok()
+    }
+}
+
+impl Stream for Indications {
+    type Item = MTFrame;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<MTFrame>> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AsyncClient;
+    use crate::command::Command;
+    use crate::error::Error;
+    use crate::frame::MTFrame;
+    use crate::subsystem::sys;
+    use crate::types::ResetReason;
+    use futures_util::StreamExt;
+    use std::io::Cursor;
+    use std::time::Duration;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    fn uart_frame(data: &[u8]) -> Vec<u8> {
+        let mut cursor = Cursor::new(data);
+        MTFrame::try_decode(&mut cursor)
+            .unwrap()
+            .encode_to_uart_transport_frame()
+    }
+
+    #[tokio::test]
+    async fn request_over_duplex() {
+        let (local, mut remote) = tokio::io::duplex(256);
+        let (mut client, mut indications) = AsyncClient::new(local);
+
+        let coprocessor = tokio::spawn(async move {
+            let mut request = [0u8; 5];
+            remote.read_exact(&mut request).await.unwrap();
+            assert_eq!(request, [0xfe, 0x0, 0x21, 0x1, 0x20]);
+
+            let reset_ind = uart_frame(&[0x6, 0x41, 0x80, 0x0, 0x3, 0x1, 0x2, 0x2, 0x0]);
+            let ping_srsp = uart_frame(&[0x2, 0x61, 0x1, 0x59, 0x0]);
+            remote.write_all(&reset_ind).await.unwrap();
+            remote.write_all(&ping_srsp).await.unwrap();
+            remote
+        });
+
+        let response = client.request(&sys::sreq::PingReq {}).await.unwrap();
+        assert_eq!(response.capabilities, 0x0059);
+
+        let indication = indications.next().await.unwrap();
+        let reset_ind = sys::areq::ResetInd::try_decode(&indication.payload).unwrap();
+        assert_eq!(reset_ind.reason, ResetReason::Hardware);
+
+        let _remote = coprocessor.await.unwrap();
+        client.set_timeout(Duration::from_millis(10));
+        let request = sys::sreq::PingReq {};
+        assert!(matches!(
+            client.request(&request).await,
+            Err(Error::Timeout)
+        ));
+    }
+}
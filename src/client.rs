@@ -0,0 +1,574 @@
+use crate::command::{Command, Request};
+use crate::error::Error;
+use crate::fragmentation::{Reassembler, fragment, fragment_status};
+use crate::frame::{CommandCode, MAX_DATA_LENGTH, MTExtendedHeader, MTFrame};
+use crate::parser::Parser;
+use crate::subsystem::mac::pib::{Attribute, BeaconPayload, BeaconPayloadLength};
+use crate::subsystem::mac::security_pib::{SecurityTables, SecurityTablesReader, TableEntry};
+use crate::subsystem::rpc::MTCommandError;
+use crate::types::{CommandType, MTExtendedHeaderStatus, MTSubsystem, RPCCommandId};
+use std::collections::VecDeque;
+use std::io::{ErrorKind, Read, Write};
+use std::time::{Duration, Instant};
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(1);
+const READ_BUFFER_SIZE: usize = 256;
+const POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+// The transport must be non-blocking or have a read timeout, e.g. a serial
+// port opened with one: the request timeout is only checked between reads, so
+// a read that blocks forever hangs the client.
+pub struct Client<T: Read + Write> {
+    transport: T,
+    parser: Parser,
+    reassembler: Reassembler,
+    timeout: Duration,
+    received: VecDeque<MTFrame>,
+    indications: VecDeque<MTFrame>,
+}
+
+impl<T: Read + Write> Client<T> {
+    pub fn new(transport: T) -> Client<T> {
+        Client {
+            transport,
+            parser: Parser::new(),
+            reassembler: Reassembler::new(),
+            timeout: DEFAULT_TIMEOUT,
+            received: VecDeque::new(),
+            indications: VecDeque::new(),
+        }
+    }
+
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    pub fn get_ref(&self) -> &T {
+        &self.transport
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.transport
+    }
+
+    pub fn into_inner(self) -> T {
+        self.transport
+    }
+
+    // Writes a frame without waiting for any response, e.g. SYS_RESET_REQ.
+    // Payloads too long for a single MT frame are sent as V2 fragments, in
+    // which case the ack of each block is waited for.
+    pub fn send(&mut self, frame: &MTFrame) -> Result<(), Error> {
+        if frame.payload.len() > MAX_DATA_LENGTH {
+            return self.send_fragmented(&frame.header.command, &frame.payload);
+        }
+
+        self.write(frame)
+    }
+
+    // Sends a SREQ and waits for the SRSP carrying the same subsystem and
+    // command id. AREQs received in the meantime are queued as indications.
+    pub fn request_frame(&mut self, frame: MTFrame) -> Result<MTFrame, Error> {
+        self.send(&frame)?;
+        self.wait_response(&frame.header.command)
+    }
+
+    // Same as request_frame, for payloads that may need to be fragmented,
+    // e.g. a MAC_DATA_REQ carrying a full-size 802.15.4g frame.
+    pub fn request_payload(
+        &mut self,
+        command: CommandCode,
+        payload: Vec<u8>,
+    ) -> Result<MTFrame, Error> {
+        if payload.len() > MAX_DATA_LENGTH {
+            self.send_fragmented(&command, &payload)?;
+            return self.wait_response(&command);
+        }
+
+        self.request_frame(MTFrame::from_payload(command, payload)?)
+    }
+
+    pub fn request<R: Request>(&mut self, request: &R) -> Result<R::Response, Error> {
+        let response = self.request_payload(R::command_code(), request.encode())?;
+        R::Response::try_decode(&response.payload)
+    }
+
+    pub fn get<A: Attribute>(&mut self) -> Result<A, Error> {
+        let response = self.request(&A::get_req())?;
+        A::from_get_rsp(response)
+    }
+
+    pub fn set<A: Attribute>(&mut self, attribute: &A) -> Result<(), Error> {
+        let response = self.request(&attribute.set_req()?)?;
+        A::check_set_rsp(response)
+    }
+
+    pub fn get_beacon_payload(&mut self) -> Result<BeaconPayload, Error> {
+        let length = self.get::<BeaconPayloadLength>()?;
+        Ok(self.get::<BeaconPayload>()?.truncate(&length))
+    }
+
+    pub fn get_entry<E: TableEntry>(&mut self, index1: u16, index2: u16) -> Result<E, Error> {
+        let response = self.request(&E::get_req(index1, index2))?;
+        E::from_get_rsp(response)
+    }
+
+    pub fn set_entry<E: TableEntry>(
+        &mut self,
+        entry: &E,
+        index1: u16,
+        index2: u16,
+    ) -> Result<(), Error> {
+        let response = self.request(&entry.set_req(index1, index2)?)?;
+        E::check_set_rsp(response)
+    }
+
+    pub fn read_security_tables(&mut self) -> Result<SecurityTables, Error> {
+        let mut reader = SecurityTablesReader::new();
+        while let Some(request) = reader.next_request() {
+            let response = self.request(&request)?;
+            reader.handle_response(response)?;
+        }
+        Ok(reader.into_tables())
+    }
+
+    pub fn pop_indication(&mut self) -> Option<MTFrame> {
+        self.indications.pop_front()
+    }
+
+    pub fn indications(&mut self) -> impl Iterator<Item = MTFrame> + '_ {
+        self.indications.drain(..)
+    }
+
+    // Returns the next queued AREQ or waits up to the client timeout for one.
+    pub fn wait_indication(&mut self) -> Result<MTFrame, Error> {
+        if let Some(frame) = self.indications.pop_front() {
+            return Ok(frame);
+        }
+
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            let frame = self.receive(deadline)?;
+            if frame.header.command.cmd_type == CommandType::AREQ {
+                return Ok(frame);
+            }
+        }
+    }
+
+    // Waits up to the client timeout for the AREQ C, e.g. the confirm of a
+    // request. Other indications stay queued.
+    pub fn wait_for<C: Command>(&mut self) -> Result<C, Error> {
+        if let Some(index) = self.indications.iter().position(is_indication::<C>) {
+            let frame = self.indications.remove(index).unwrap();
+            return C::try_decode(&frame.payload);
+        }
+
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            let frame = self.receive(deadline)?;
+            if is_indication::<C>(&frame) {
+                return C::try_decode(&frame.payload);
+            }
+            if frame.header.command.cmd_type == CommandType::AREQ {
+                self.indications.push_back(frame);
+            }
+        }
+    }
+
+    // Removes the queued AREQs C, e.g. the beacons received during a scan.
+    pub fn take_indications<C: Command>(&mut self) -> Result<Vec<C>, Error> {
+        let (taken, kept): (VecDeque<_>, _) =
+            self.indications.drain(..).partition(is_indication::<C>);
+        self.indications = kept;
+        taken.iter().map(|x| C::try_decode(&x.payload)).collect()
+    }
+
+    fn wait_response(&mut self, request: &CommandCode) -> Result<MTFrame, Error> {
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            let frame = self.receive(deadline)?;
+            let command = &frame.header.command;
+
+            match command.cmd_type {
+                CommandType::AREQ => self.indications.push_back(frame),
+                CommandType::SRSP if is_command_error(&frame) => {
+                    let error = MTCommandError::try_decode(frame.payload.as_slice())?;
+                    return Err(Error::MTCommandError(error));
+                }
+                CommandType::SRSP
+                    if command.subsystem == request.subsystem
+                        && command.id == request.id
+                        && fragment_status(&frame).is_none() =>
+                {
+                    return Ok(frame);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn send_fragmented(&mut self, request: &CommandCode, payload: &[u8]) -> Result<(), Error> {
+        for (block, fragment) in fragment(request, payload)?.iter().enumerate() {
+            let deadline = Instant::now() + self.timeout;
+            self.write(fragment)?;
+
+            loop {
+                let frame = self.receive(deadline)?;
+                let command = &frame.header.command;
+
+                let status = match fragment_status(&frame) {
+                    Some((acked_block, status))
+                        if command.subsystem == request.subsystem
+                            && command.id == request.id
+                            && (acked_block as usize == block || is_extended_status(&frame)) =>
+                    {
+                        status
+                    }
+                    _ => {
+                        if command.cmd_type == CommandType::AREQ {
+                            self.indications.push_back(frame);
+                        }
+                        continue;
+                    }
+                };
+
+                match status {
+                    MTExtendedHeaderStatus::Success
+                    | MTExtendedHeaderStatus::FragmentationSequenceCompleted => break,
+                    MTExtendedHeaderStatus::ResendLastFrame => self.write(fragment)?,
+                    status => return Err(Error::Fragmentation(status)),
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn write(&mut self, frame: &MTFrame) -> Result<(), Error> {
+        self.transport
+            .write_all(&frame.encode_to_uart_transport_frame())
+            .map_err(Error::Io)?;
+        self.transport.flush().map_err(Error::Io)
+    }
+
+    fn receive(&mut self, deadline: Instant) -> Result<MTFrame, Error> {
+        let mut buffer = [0u8; READ_BUFFER_SIZE];
+
+        loop {
+            if let Some(frame) = self.received.pop_front() {
+                return Ok(frame);
+            }
+
+            if Instant::now() >= deadline {
+                return Err(Error::Timeout);
+            }
+
+            let count = match self.transport.read(&mut buffer) {
+                Ok(0) => return Err(Error::Io(ErrorKind::UnexpectedEof.into())),
+                Ok(count) => count,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) if is_transient(&e) => {
+                    // Back off instead of spinning on a transport without data.
+                    let remaining = deadline.saturating_duration_since(Instant::now());
+                    std::thread::sleep(POLL_INTERVAL.min(remaining));
+                    continue;
+                }
+                Err(e) => return Err(Error::Io(e)),
+            };
+
+            // Stray bytes, corrupted frames and broken fragment sequences are
+            // dropped; the request will time out if its response was among them.
+            let frames: Vec<MTFrame> = self.parser.feed_slice(&buffer[..count]).flatten().collect();
+            for frame in frames {
+                if let Ok(Some(frame)) = self.reassembler.push(frame) {
+                    self.received.push_back(frame);
+                }
+                while let Some(ack) = self.reassembler.pop_ack() {
+                    self.write(&ack)?;
+                }
+            }
+        }
+    }
+}
+
+// Extended status frames (V4) report on the whole sequence, e.g. when the
+// CoP ran out of memory, so their block doesn't refer to the last fragment.
+pub(crate) fn is_extended_status(frame: &MTFrame) -> bool {
+    matches!(frame.extended_header, Some(MTExtendedHeader::V4 { .. }))
+}
+
+fn is_indication<C: Command>(frame: &MTFrame) -> bool {
+    let command = &frame.header.command;
+    command.cmd_type == CommandType::AREQ
+        && command.subsystem == C::SUBSYSTEM
+        && command.id == C::ID
+}
+
+pub(crate) fn is_command_error(frame: &MTFrame) -> bool {
+    frame.header.command.subsystem == MTSubsystem::RPC
+        && frame.header.command.id == RPCCommandId::MTCommandError as u8
+}
+
+fn is_transient(error: &std::io::Error) -> bool {
+    matches!(
+        error.kind(),
+        ErrorKind::WouldBlock | ErrorKind::TimedOut | ErrorKind::Interrupted
+    )
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::Client;
+    use crate::command::Command;
+    use crate::error::Error;
+    use crate::frame::{CommandCode, MTFrame};
+    use crate::subsystem::mac::pib::{PANId, ShortAddress};
+    use crate::subsystem::mac::security_pib::{KeyIdDeviceEntry, KeyIdLookupEntry};
+    use crate::subsystem::sys;
+    use crate::types::{CommandType, ErrorCode, MTSubsystem, ResetReason, Status};
+    use std::io::{Cursor, ErrorKind, Read, Write};
+    use std::time::Duration;
+
+    pub(crate) struct MockTransport {
+        input: Cursor<Vec<u8>>,
+        pub(crate) output: Vec<u8>,
+    }
+
+    impl MockTransport {
+        pub(crate) fn new(frames: &[&[u8]]) -> MockTransport {
+            let mut input = Vec::new();
+            for data in frames {
+                let mut cursor = Cursor::new(*data);
+                let frame = MTFrame::try_decode(&mut cursor).unwrap();
+                input.extend(frame.encode_to_uart_transport_frame());
+            }
+            MockTransport {
+                input: Cursor::new(input),
+                output: Vec::new(),
+            }
+        }
+    }
+
+    impl Read for MockTransport {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            match self.input.read(buf)? {
+                0 => Err(ErrorKind::TimedOut.into()),
+                count => Ok(count),
+            }
+        }
+    }
+
+    impl Write for MockTransport {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.output.write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn request_queues_areq_and_returns_srsp() {
+        let reset_ind: &[u8] = &[0x6, 0x41, 0x80, 0x0, 0x3, 0x1, 0x2, 0x2, 0x0];
+        let ping_srsp: &[u8] = &[0x2, 0x61, 0x1, 0x59, 0x0];
+        let mut client = Client::new(MockTransport::new(&[reset_ind, ping_srsp]));
+
+        let response = client.request(&sys::sreq::PingReq {}).unwrap();
+        assert_eq!(response.capabilities, 0x0059);
+
+        assert_eq!(client.get_ref().output, [0xfe, 0x0, 0x21, 0x1, 0x20]);
+
+        let indication = client.pop_indication().unwrap();
+        let reset_ind = sys::areq::ResetInd::try_decode(&indication.payload).unwrap();
+        assert_eq!(reset_ind.reason, ResetReason::Hardware);
+        assert!(client.pop_indication().is_none());
+    }
+
+    #[test]
+    fn request_returns_mt_command_error() {
+        let command_error: &[u8] = &[0x3, 0x60, 0x0, 0x2, 0x21, 0x1];
+        let mut client = Client::new(MockTransport::new(&[command_error]));
+
+        let request = sys::sreq::PingReq {}.into_mt_frame().unwrap();
+        match client.request_frame(request) {
+            Err(Error::MTCommandError(error)) => {
+                assert!(matches!(error.error_code, ErrorCode::InvalidCommandId));
+            }
+            x => panic!("Unexpected result {:?}", x),
+        }
+    }
+
+    #[test]
+    fn wait_indication_reassembles_fragments() {
+        let block0: &[u8] = &[0x6, 0xc2, 0x85, 0x10, 0x0, 0x3, 0x0, 0xaa, 0xbb];
+        let block1: &[u8] = &[0x5, 0xc2, 0x85, 0x10, 0x1, 0x3, 0x0, 0xcc];
+        let mut client = Client::new(MockTransport::new(&[block0, block1]));
+
+        let indication = client.wait_indication().unwrap();
+        assert_eq!(indication.payload, [0xaa, 0xbb, 0xcc]);
+        assert_eq!(
+            client.get_ref().output,
+            [
+                0xfe, 0x3, 0xc2, 0x85, 0x18, 0x0, 0x0, 0x5c, 0xfe, 0x3, 0xc2, 0x85, 0x18, 0x1, 0x6,
+                0x5b
+            ]
+        );
+    }
+
+    #[test]
+    fn request_fragments_long_payload() {
+        let resend_ack: &[u8] = &[0x3, 0xe2, 0x5, 0x18, 0x0, 0x1];
+        let block0_ack: &[u8] = &[0x3, 0xe2, 0x5, 0x18, 0x0, 0x0];
+        let block1_ack: &[u8] = &[0x3, 0xe2, 0x5, 0x18, 0x1, 0x6];
+        let data_srsp: &[u8] = &[0x1, 0x62, 0x5, 0x0];
+        let mut client = Client::new(MockTransport::new(&[
+            resend_ack, block0_ack, block1_ack, data_srsp,
+        ]));
+
+        let command = CommandCode {
+            is_extended: false,
+            cmd_type: CommandType::SREQ,
+            subsystem: MTSubsystem::MAC,
+            id: 0x5,
+        };
+
+        let response = client.request_payload(command, vec![0xaa; 300]).unwrap();
+        assert_eq!(response.payload, [0x0]);
+
+        // Block 0 was sent twice, as the first ack asked for a resend.
+        let output = &client.get_ref().output;
+        assert_eq!(
+            output.len(),
+            2 * (1 + 3 + 4 + 246 + 1) + (1 + 3 + 4 + 54 + 1)
+        );
+        assert_eq!(output[..8], [0xfe, 0xfa, 0xa2, 0x5, 0x10, 0x0, 0x2c, 0x1]);
+    }
+
+    #[test]
+    fn get_and_set_mac_pib_attributes() {
+        let mut get_srsp = vec![0x11, 0x62, 0x8, 0x0, 0x34, 0x12];
+        get_srsp.extend([0x0; 14]);
+        let set_srsp: &[u8] = &[0x1, 0x62, 0x9, 0x0];
+        let set_failed_srsp: &[u8] = &[0x1, 0x62, 0x9, 0xe8];
+        let mut client = Client::new(MockTransport::new(&[&get_srsp, set_srsp, set_failed_srsp]));
+
+        assert_eq!(client.get::<PANId>().unwrap(), PANId(0x1234));
+        assert_eq!(client.get_ref().output, [0xfe, 0x1, 0x22, 0x8, 0x50, 0x7b]);
+
+        client.set(&ShortAddress(0xabcd)).unwrap();
+        assert_eq!(
+            client.get_ref().output[6..11],
+            [0xfe, 0x11, 0x22, 0x9, 0x53]
+        );
+        assert_eq!(client.get_ref().output[11..13], [0xcd, 0xab]);
+
+        assert!(matches!(
+            client.set(&ShortAddress(0xabcd)),
+            Err(Error::Status(Status::InvalidParameter))
+        ));
+    }
+
+    #[test]
+    fn read_security_tables() {
+        let key_count: &[u8] = &[0x6, 0x62, 0x30, 0x0, 0x0, 0x0, 0x0, 0x0, 0x1];
+        let device_count: &[u8] = &[0x6, 0x62, 0x30, 0x0, 0x0, 0x0, 0x0, 0x0, 0x1];
+        let level_count: &[u8] = &[0x6, 0x62, 0x30, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0];
+        let mut key = vec![0x19, 0x62, 0x30, 0x0, 0x0, 0x0, 0x0, 0x0];
+        key.extend([0x11; 16]);
+        key.extend([0x5, 0x0, 0x0, 0x0]);
+        let lookup: &[u8] = &[
+            0xf, 0x62, 0x30, 0x0, 0x0, 0x0, 0x0, 0x0, 0x1, 0x2, 0x3, 0x4, 0x5, 0x0, 0x0, 0x0, 0x0,
+            0x0,
+        ];
+        let invalid_index: &[u8] = &[0x5, 0x62, 0x30, 0xf9, 0x0, 0x0, 0x1, 0x0];
+        let key_device: &[u8] = &[0x9, 0x62, 0x30, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x1, 0x0];
+        let mut device = vec![
+            0x12, 0x62, 0x30, 0x0, 0x0, 0x0, 0x0, 0x0, 0x34, 0x12, 0x1, 0x0,
+        ];
+        device.extend([0x8, 0x7, 0x6, 0x5, 0x4, 0x3, 0x2, 0x1, 0x0]);
+
+        let mut client = Client::new(MockTransport::new(&[
+            key_count,
+            device_count,
+            level_count,
+            &key,
+            lookup,
+            invalid_index,
+            key_device,
+            invalid_index,
+            &device,
+        ]));
+
+        let tables = client.read_security_tables().unwrap();
+        assert_eq!(tables.keys.len(), 1);
+        assert_eq!(tables.keys[0].key.key, [0x11; 16]);
+        assert_eq!(tables.keys[0].key.frame_counter, 5);
+        assert_eq!(
+            tables.keys[0].lookup_list,
+            [KeyIdLookupEntry {
+                lookup_data: vec![1, 2, 3, 4, 5]
+            }]
+        );
+        assert_eq!(
+            tables.keys[0].device_list,
+            [KeyIdDeviceEntry {
+                device_handle: 0,
+                unique_device: true,
+                blacklisted: false,
+            }]
+        );
+        assert!(tables.keys[0].usage_list.is_empty());
+        assert_eq!(tables.devices.len(), 1);
+        assert_eq!(tables.devices[0].pan_id, 0x1234);
+        assert_eq!(
+            tables.devices[0].ext_address.address,
+            [1, 2, 3, 4, 5, 6, 7, 8]
+        );
+        assert!(tables.security_levels.is_empty());
+    }
+
+    #[test]
+    fn request_times_out() {
+        let mut client = Client::new(MockTransport::new(&[]));
+        client.set_timeout(Duration::from_millis(10));
+
+        let request = sys::sreq::PingReq {}.into_mt_frame().unwrap();
+        assert!(matches!(client.request_frame(request), Err(Error::Timeout)));
+    }
+
+    struct WouldBlockTransport {
+        reads: usize,
+    }
+
+    impl Read for WouldBlockTransport {
+        fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+            self.reads += 1;
+            Err(ErrorKind::WouldBlock.into())
+        }
+    }
+
+    impl Write for WouldBlockTransport {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn request_backs_off_on_would_block() {
+        let mut client = Client::new(WouldBlockTransport { reads: 0 });
+        client.set_timeout(Duration::from_millis(10));
+
+        let request = sys::sreq::PingReq {}.into_mt_frame().unwrap();
+        assert!(matches!(client.request_frame(request), Err(Error::Timeout)));
+        assert!(client.get_ref().reads <= 11);
+    }
+}
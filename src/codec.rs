@@ -0,0 +1,83 @@
+use crate::error::Error;
+use crate::frame::MTFrame;
+use crate::parser::Parser;
+use bytes::BytesMut;
+use tokio_util::codec::{Decoder, Encoder};
+
+pub struct MTCodec {
+    parser: Parser,
+}
+
+impl MTCodec {
+    pub fn new() -> MTCodec {
+        MTCodec {
+            parser: Parser::new(),
+        }
+    }
+}
+
+impl Default for MTCodec {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Decoder for MTCodec {
+    type Item = MTFrame;
+    type Error = Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<MTFrame>, Error> {
+        // Returning an error would terminate a FramedRead, so garbage and
+        // corrupted frames are dropped here and decoding carries on. Bytes
+        // after the returned frame stay buffered in the parser.
+        let data = src.split();
+        Ok(self.parser.feed_slice(&data).flatten().next())
+    }
+}
+
+impl Encoder<MTFrame> for MTCodec {
+    type Error = Error;
+
+    fn encode(&mut self, frame: MTFrame, dst: &mut BytesMut) -> Result<(), Error> {
+        self.encode(&frame, dst)
+    }
+}
+
+impl Encoder<&MTFrame> for MTCodec {
+    type Error = Error;
+
+    fn encode(&mut self, frame: &MTFrame, dst: &mut BytesMut) -> Result<(), Error> {
+        dst.extend_from_slice(&frame.encode_to_uart_transport_frame());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MTCodec;
+    use crate::command::Command;
+    use crate::subsystem::sys;
+    use bytes::BytesMut;
+    use tokio_util::codec::{Decoder, Encoder};
+
+    #[test]
+    fn decode_skips_garbage_and_keeps_partial_frames() {
+        let mut codec = MTCodec::new();
+        let mut buffer = BytesMut::new();
+
+        codec
+            .encode(sys::sreq::PingReq {}.into_mt_frame().unwrap(), &mut buffer)
+            .unwrap();
+        let frame = buffer.split();
+
+        buffer.extend_from_slice(&[0x00, 0x13]);
+        buffer.extend_from_slice(&frame[..3]);
+        assert!(codec.decode(&mut buffer).unwrap().is_none());
+        assert!(buffer.is_empty());
+
+        buffer.extend_from_slice(&frame[3..]);
+        let decoded = codec.decode(&mut buffer).unwrap().unwrap();
+        assert_eq!(decoded.header.command.id, 0x01);
+        assert_eq!(decoded.payload.len(), 0);
+    }
+}
@@ -0,0 +1,44 @@
+use crate::error::Error;
+use crate::frame::{CommandCode, MTFrame};
+use crate::types::{CommandType, MTSubsystem};
+use std::io::Cursor;
+
+pub trait Command: Sized {
+    const SUBSYSTEM: MTSubsystem;
+    const CMD_TYPE: CommandType;
+    const ID: u8;
+
+    // Leaves the cursor after the decoded payload, see
+    // MTFramePayload::try_decode_strict.
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error>;
+
+    fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
+        Self::try_decode_from(&mut Cursor::new(buffer))
+    }
+
+    fn encode_into(&self, buffer: &mut Vec<u8>);
+
+    fn encode(&self) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        self.encode_into(&mut buffer);
+        buffer
+    }
+
+    fn command_code() -> CommandCode {
+        CommandCode {
+            is_extended: false,
+            cmd_type: Self::CMD_TYPE,
+            subsystem: Self::SUBSYSTEM,
+            id: Self::ID,
+        }
+    }
+
+    fn into_mt_frame(self) -> Result<MTFrame, Error> {
+        MTFrame::from_payload(Self::command_code(), self.encode())
+    }
+}
+
+// Implemented by the SREQs, answered by the SRSP of the same name.
+pub trait Request: Command {
+    type Response: Command;
+}
@@ -0,0 +1,282 @@
+use crate::client::Client;
+use crate::command::Command;
+use crate::error::Error;
+pub use crate::error::StartStep;
+use crate::phy::NON_BEACON_ORDER;
+use crate::subsystem::mac::fh_pib::FHPIBValue;
+use crate::subsystem::mac::pib::{
+    AssociationPermit, MACPIBValue, PANId, RxOnWhenIdle, ShortAddress, check_status,
+};
+use crate::subsystem::mac::{areq, sreq};
+use crate::subsystem::sys;
+use crate::types::{KeyIdMode, KeySource, PhyId, ResetType, SecurityLevel};
+use std::io::{Read, Write};
+
+// Channel page of the 802.15.4g SUN PHYs.
+pub const SUN_CHANNEL_PAGE: u8 = 9;
+
+// No enhanced beacons in a non beacon-enabled network.
+const NO_ENHANCED_BEACON_ORDER: u16 = 16383;
+
+#[derive(Debug, Clone)]
+pub struct CoordinatorConfig {
+    pub pan_id: u16,
+    pub short_address: u16,
+    pub phy_id: PhyId,
+    pub channel_page: u8,
+    pub logical_channel: u8,
+    pub beacon_order: u8,
+    pub superframe_order: u8,
+    pub association_permit: bool,
+    // None skips the SYS reset, e.g. right after powering up the CoP.
+    pub reset_type: Option<ResetType>,
+    // Written after the PAN id and addresses, e.g. the beacon payload.
+    pub attributes: Vec<MACPIBValue>,
+    // Starts a frequency hopping network once the FH PIB is written.
+    pub frequency_hopping: Option<Vec<FHPIBValue>>,
+}
+
+impl CoordinatorConfig {
+    // A non beacon-enabled PAN without frequency hopping.
+    pub fn new(pan_id: u16, phy_id: PhyId, logical_channel: u8) -> CoordinatorConfig {
+        CoordinatorConfig {
+            pan_id,
+            short_address: 0x0000,
+            phy_id,
+            channel_page: SUN_CHANNEL_PAGE,
+            logical_channel,
+            beacon_order: NON_BEACON_ORDER,
+            superframe_order: NON_BEACON_ORDER,
+            association_permit: true,
+            reset_type: Some(ResetType::Hard),
+            attributes: Vec::new(),
+            frequency_hopping: None,
+        }
+    }
+
+    pub fn start_req(&self) -> sreq::StartReq {
+        let no_key_source = KeySource { key: [0; 8] };
+        sreq::StartReq {
+            start_time: 0,
+            pan_id: self.pan_id,
+            logical_channel: self.logical_channel,
+            channel_page: self.channel_page,
+            phy_id: self.phy_id,
+            beacon_order: self.beacon_order,
+            super_frame_order: self.superframe_order,
+            pan_coordinator: true,
+            battery_life_ext: false,
+            coord_realignment: false,
+            realign_key_source: no_key_source,
+            realign_security_level: SecurityLevel::NoSecurity,
+            realign_key_id_mode: KeyIdMode::NotUsed,
+            realign_key_index: 0,
+            beacon_key_source: no_key_source,
+            beacon_security_level: SecurityLevel::NoSecurity,
+            beacon_key_id_mode: KeyIdMode::NotUsed,
+            beacon_key_index: 0,
+            start_fh: self.frequency_hopping.is_some(),
+            enh_beacon_order: NON_BEACON_ORDER,
+            ofs_time_slot: 0,
+            non_beacon_order: NO_ENHANCED_BEACON_ORDER,
+            ie_id_list: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Coordinator {
+    config: CoordinatorConfig,
+}
+
+impl Coordinator {
+    // Resets the CoP, writes the PIB and starts the PAN. A failure names the
+    // step, with the Status reported by the CoP if it refused the request.
+    pub fn start<T: Read + Write>(
+        client: &mut Client<T>,
+        config: CoordinatorConfig,
+    ) -> Result<Coordinator, Error> {
+        let start_req = config.start_req();
+        start_step(
+            StartStep::Start,
+            start_req
+                .validate_channel()
+                .and_then(|_| start_req.beacon_timing()),
+        )?;
+
+        if let Some(reset_type) = config.reset_type {
+            let result = sys::areq::ResetReq { reset_type }
+                .into_mt_frame()
+                .and_then(|frame| client.send(&frame))
+                .and_then(|_| client.wait_for::<sys::areq::ResetInd>());
+            start_step(StartStep::SysReset, result)?;
+        }
+
+        let result = client.request(&sreq::ResetReq { set_default: true });
+        start_step(
+            StartStep::MACReset,
+            result.and_then(|x| check_status(x.status)),
+        )?;
+
+        start_step(StartStep::SetPANId, client.set(&PANId(config.pan_id)))?;
+        start_step(
+            StartStep::SetShortAddress,
+            client.set(&ShortAddress(config.short_address)),
+        )?;
+        start_step(StartStep::SetRxOnWhenIdle, client.set(&RxOnWhenIdle(true)))?;
+        start_step(
+            StartStep::SetAssociationPermit,
+            client.set(&AssociationPermit(config.association_permit)),
+        )?;
+
+        for value in config.attributes.iter() {
+            let result = value
+                .clone()
+                .into_set_req()
+                .and_then(|request| client.request(&request))
+                .and_then(|response| check_status(response.status));
+            start_step(StartStep::SetAttribute(value.attribute_id()), result)?;
+        }
+
+        if let Some(ref values) = config.frequency_hopping {
+            let result = client.request(&sreq::FHEnableReq {});
+            start_step(
+                StartStep::EnableFrequencyHopping,
+                result.and_then(|x| check_status(x.status)),
+            )?;
+
+            for value in values.iter() {
+                let result = value
+                    .clone()
+                    .into_set_req()
+                    .and_then(|request| client.request(&request))
+                    .and_then(|response| check_status(response.status));
+                start_step(StartStep::SetFHAttribute(value.attribute_id()), result)?;
+            }
+        }
+
+        let result = client.request(&start_req);
+        start_step(
+            StartStep::Start,
+            result.and_then(|x| check_status(x.status)),
+        )?;
+
+        let result = client.wait_for::<areq::StartCnf>();
+        start_step(
+            StartStep::StartCnf,
+            result.and_then(|x| check_status(x.status)),
+        )?;
+
+        Ok(Coordinator { config })
+    }
+
+    pub fn config(&self) -> &CoordinatorConfig {
+        &self.config
+    }
+}
+
+fn start_step<R>(step: StartStep, result: Result<R, Error>) -> Result<R, Error> {
+    result.map_err(|e| Error::StartFailed(step, Box::new(e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Coordinator, CoordinatorConfig, StartStep};
+    use crate::client::Client;
+    use crate::client::tests::MockTransport;
+    use crate::error::Error;
+    use crate::subsystem::mac::fh_pib::FHPIBValue;
+    use crate::subsystem::mac::pib::MACPIBValue;
+    use crate::types::{PhyId, Status};
+
+    const RESET_IND: &[u8] = &[0x6, 0x41, 0x80, 0x0, 0x3, 0x1, 0x2, 0x2, 0x0];
+    const RESET_SRSP: &[u8] = &[0x1, 0x62, 0x1, 0x0];
+    const SET_SRSP: &[u8] = &[0x1, 0x62, 0x9, 0x0];
+    const START_SRSP: &[u8] = &[0x1, 0x62, 0x3, 0x0];
+    const START_CNF: &[u8] = &[0x1, 0x42, 0x8e, 0x0];
+
+    #[test]
+    fn start_coordinator() {
+        let fh_enable_srsp: &[u8] = &[0x1, 0x62, 0x40, 0x0];
+        let fh_set_srsp: &[u8] = &[0x1, 0x62, 0x43, 0x0];
+        let data_cnf: &[u8] = &[
+            0x10, 0x42, 0x84, 0x0, 0x1, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+            0x0, 0x0,
+        ];
+        let mut client = Client::new(MockTransport::new(&[
+            RESET_IND,
+            RESET_SRSP,
+            SET_SRSP,
+            SET_SRSP,
+            SET_SRSP,
+            SET_SRSP,
+            SET_SRSP,
+            fh_enable_srsp,
+            fh_set_srsp,
+            START_SRSP,
+            data_cnf,
+            START_CNF,
+        ]));
+
+        let mut config = CoordinatorConfig::new(0x1234, PhyId::STD_ETSI_863_PHY_3, 0);
+        config.attributes = vec![MACPIBValue::BeaconPayload(vec![0x1, 0x2])];
+        config.frequency_hopping = Some(vec![FHPIBValue::PANVersion(1)]);
+        let coordinator = Coordinator::start(&mut client, config).unwrap();
+        assert_eq!(coordinator.config().pan_id, 0x1234);
+        assert!(coordinator.config().start_req().start_fh);
+
+        // The DataCnf received before the StartCnf stays queued.
+        let indication = client.pop_indication().unwrap();
+        assert_eq!(indication.header.command.id, 0x84);
+
+        // SYS reset, MAC reset and the PAN id write.
+        let output = &client.get_ref().output;
+        assert_eq!(output[..6], [0xfe, 0x1, 0x41, 0x0, 0x0, 0x40]);
+        assert_eq!(output[6..12], [0xfe, 0x1, 0x22, 0x1, 0x1, 0x23]);
+        assert_eq!(output[12..17], [0xfe, 0x11, 0x22, 0x9, 0x50]);
+    }
+
+    #[test]
+    fn start_reports_failed_step() {
+        let denied_srsp: &[u8] = &[0x1, 0x62, 0x9, 0xf4];
+        let mut client = Client::new(MockTransport::new(&[
+            RESET_SRSP,
+            SET_SRSP,
+            SET_SRSP,
+            SET_SRSP,
+            denied_srsp,
+        ]));
+
+        let mut config = CoordinatorConfig::new(0x1234, PhyId::STD_ETSI_863_PHY_3, 0);
+        config.reset_type = None;
+        match Coordinator::start(&mut client, config) {
+            Err(Error::StartFailed(StartStep::SetAssociationPermit, error)) => {
+                assert!(matches!(
+                    *error,
+                    Error::Status(Status::UnsupportedAttribute)
+                ));
+            }
+            x => panic!("Unexpected result {:?}", x),
+        }
+
+        let mut config = CoordinatorConfig::new(0x1234, PhyId::STD_ETSI_863_PHY_3, 34);
+        config.frequency_hopping = Some(vec![FHPIBValue::PANVersion(1)]);
+        assert!(matches!(
+            Coordinator::start(&mut client, config),
+            Err(Error::StartFailed(StartStep::Start, _))
+        ));
+
+        let start_cnf: &[u8] = &[0x1, 0x42, 0x8e, 0xee];
+        let mut client = Client::new(MockTransport::new(&[
+            RESET_IND, RESET_SRSP, SET_SRSP, SET_SRSP, SET_SRSP, SET_SRSP, START_SRSP, start_cnf,
+        ]));
+        let config = CoordinatorConfig::new(0x1234, PhyId::STD_ETSI_863_PHY_3, 0);
+        match Coordinator::start(&mut client, config) {
+            Err(error) => {
+                assert_eq!(error.status(), Some(Status::PANIdConflict));
+                assert!(matches!(error, Error::StartFailed(StartStep::StartCnf, _)));
+            }
+            x => panic!("Unexpected result {:?}", x),
+        }
+    }
+}
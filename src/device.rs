@@ -0,0 +1,398 @@
+use crate::client::Client;
+use crate::coordinator::SUN_CHANNEL_PAGE;
+use crate::error::Error;
+pub use crate::error::JoinStep;
+use crate::phy::{BASE_SUPERFRAME_DURATION, PhyDescriptor};
+use crate::scan::PanCandidates;
+use crate::subsystem::mac::areq::{AssociateCnf, BeaconNotifyInd, PanDescriptor, ScanCnf};
+use crate::subsystem::mac::pib::check_status;
+use crate::subsystem::mac::sreq::{AssociateReq, ScanReq};
+use crate::types::{
+    AssociateCnfStatus, CapabilityInformation, ChannelsBitMap, KeyIdMode, KeySource, MPMScan,
+    MPMType, PermitJoin, PhyId, ScanType, SecurityLevel, ShortAddress, Status,
+};
+use std::io::{Read, Write};
+use std::time::Duration;
+
+const DEFAULT_SCAN_DURATION: u8 = 5;
+
+#[derive(Debug, Clone)]
+pub struct JoinPolicy {
+    pub phy_id: PhyId,
+    pub channel_page: u8,
+    // Scanned one at a time, in channel order.
+    pub channels: ChannelsBitMap,
+    pub scan_type: ScanType,
+    pub scan_duration: u8,
+    // Only join this PAN.
+    pub pan_id: Option<u16>,
+    pub min_link_quality: u8,
+    pub capability_info: CapabilityInformation,
+}
+
+impl JoinPolicy {
+    // Active scan of all the channels of the PHY, asking for a short address.
+    pub fn new(phy_id: PhyId) -> Result<JoinPolicy, Error> {
+        Ok(JoinPolicy {
+            phy_id,
+            channel_page: SUN_CHANNEL_PAGE,
+            channels: PhyDescriptor::try_get(phy_id)?.channels(),
+            scan_type: ScanType::Active,
+            scan_duration: DEFAULT_SCAN_DURATION,
+            pan_id: None,
+            min_link_quality: 0,
+            capability_info: CapabilityInformation::FFD
+                | CapabilityInformation::MAINS_POWER
+                | CapabilityInformation::RX_ON_IDLE
+                | CapabilityInformation::ALLOC_ADDR,
+        })
+    }
+
+    pub fn scan_req(&self, channel: u8) -> ScanReq {
+        ScanReq {
+            scan_type: self.scan_type,
+            scan_duration: self.scan_duration,
+            channel_page: self.channel_page,
+            phy_id: self.phy_id,
+            max_results: 0,
+            permit_join: PermitJoin::OnlyIfPermitJoinIsEnabled,
+            link_quality: self.min_link_quality,
+            rsp_filter: 0,
+            mpm_scan: MPMScan::Disabled,
+            mpm_type: MPMType::NBPAN,
+            mpm_duration: 0,
+            key_source: KeySource { key: [0; 8] },
+            security_level: SecurityLevel::NoSecurity,
+            key_id_mode: KeyIdMode::NotUsed,
+            key_index: 0,
+            channels: [channel].into_iter().collect(),
+        }
+    }
+
+    // The scan of one channel lasts (2^scan_duration + 1) superframes.
+    pub fn scan_time(&self) -> Result<Duration, Error> {
+        let phy = PhyDescriptor::try_get(self.phy_id)?;
+        let superframes = (1u32 << self.scan_duration.min(14)) + 1;
+        Ok(phy.symbol_duration() * BASE_SUPERFRAME_DURATION * superframes)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Device {
+    short_address: ShortAddress,
+    coordinator: PanDescriptor,
+}
+
+impl Device {
+    // Scans the channels of the policy one by one and associates with the
+    // best coordinator heard. Channels without beacons and coordinators at
+    // capacity or denying access are skipped, the last of these errors is
+    // returned if no coordinator accepts the device.
+    pub fn join<T: Read + Write>(
+        client: &mut Client<T>,
+        policy: &JoinPolicy,
+    ) -> Result<Device, Error> {
+        let scan_time = join_step(JoinStep::Scan, policy.scan_time())?;
+        let mut last_error = join_step_error(JoinStep::ScanCnf, Error::Status(Status::NoBeacon));
+
+        for channel in policy.channels.iter() {
+            let candidates = match scan(client, policy, channel, scan_time)? {
+                Some(candidates) => candidates,
+                None => {
+                    last_error =
+                        join_step_error(JoinStep::ScanCnf, Error::Status(Status::NoBeacon));
+                    continue;
+                }
+            };
+
+            for candidate in candidates.ranked() {
+                match associate(client, policy, candidate) {
+                    Ok(short_address) => {
+                        return Ok(Device {
+                            short_address,
+                            coordinator: candidate.clone(),
+                        });
+                    }
+                    Err(Error::JoinFailed(step, error))
+                        if matches!(*error, Error::AssociationRefused(_)) =>
+                    {
+                        last_error = Error::JoinFailed(step, error)
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+        }
+
+        Err(last_error)
+    }
+
+    pub fn short_address(&self) -> ShortAddress {
+        self.short_address
+    }
+
+    pub fn coordinator(&self) -> &PanDescriptor {
+        &self.coordinator
+    }
+}
+
+// None when no acceptable coordinator answered on the channel, coordinators
+// not permitting association aren't acceptable.
+fn scan<T: Read + Write>(
+    client: &mut Client<T>,
+    policy: &JoinPolicy,
+    channel: u8,
+    scan_time: Duration,
+) -> Result<Option<PanCandidates>, Error> {
+    let response = client.request(&policy.scan_req(channel));
+    join_step(
+        JoinStep::Scan,
+        response.and_then(|x| check_status(x.status)),
+    )?;
+
+    let timeout = client.timeout();
+    client.set_timeout(timeout + scan_time);
+    let scan_cnf = client.wait_for::<ScanCnf>();
+    client.set_timeout(timeout);
+
+    let scan_cnf = join_step(JoinStep::ScanCnf, scan_cnf)?;
+    let beacons = join_step(
+        JoinStep::ScanCnf,
+        client.take_indications::<BeaconNotifyInd>(),
+    )?;
+    match scan_cnf.status {
+        Status::Success => {}
+        Status::NoBeacon => return Ok(None),
+        status => return Err(join_step_error(JoinStep::ScanCnf, Error::Status(status))),
+    }
+
+    let mut candidates = PanCandidates::new();
+    for beacon in beacons.iter() {
+        candidates.insert_beacon(beacon);
+    }
+    candidates.insert_scan_cnf(&scan_cnf);
+    candidates.retain(|x| {
+        x.superframe_spec.association_permit
+            && policy.pan_id.is_none_or(|pan_id| x.pan_id == pan_id)
+            && x.link_quality >= policy.min_link_quality
+    });
+
+    match candidates.ranked().is_empty() {
+        true => Ok(None),
+        false => Ok(Some(candidates)),
+    }
+}
+
+fn associate<T: Read + Write>(
+    client: &mut Client<T>,
+    policy: &JoinPolicy,
+    candidate: &PanDescriptor,
+) -> Result<ShortAddress, Error> {
+    let request =
+        AssociateReq::from_pan_descriptor(candidate, policy.phy_id, policy.capability_info);
+    let response = client.request(&request);
+    join_step(
+        JoinStep::Associate,
+        response.and_then(|x| check_status(x.status)),
+    )?;
+
+    let associate_cnf = client
+        .wait_for::<AssociateCnf>()
+        .and_then(|x| match x.status {
+            AssociateCnfStatus::Status(status) => check_status(status).map(|_| x.short_address),
+            AssociateCnfStatus::Refused(status) => Err(Error::AssociationRefused(status)),
+        });
+    join_step(JoinStep::AssociateCnf, associate_cnf)
+}
+
+fn join_step<R>(step: JoinStep, result: Result<R, Error>) -> Result<R, Error> {
+    result.map_err(|e| join_step_error(step, e))
+}
+
+fn join_step_error(step: JoinStep, error: Error) -> Error {
+    Error::JoinFailed(step, Box::new(error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Device, JoinPolicy, JoinStep};
+    use crate::client::Client;
+    use crate::client::tests::MockTransport;
+    use crate::error::Error;
+    use crate::types::{Address, AssociationStatus, PhyId, ShortAddress, Status};
+
+    const SCAN_SRSP: &[u8] = &[0x1, 0x62, 0xc, 0x0];
+    const ASSOCIATE_SRSP: &[u8] = &[0x1, 0x62, 0x6, 0x0];
+
+    fn scan_cnf(status: u8) -> Vec<u8> {
+        let mut frame = vec![0x16, 0x42, 0x8c, status, 0x1, 0x9, 0x3];
+        frame.extend_from_slice(&[0x0; 18]);
+        frame
+    }
+
+    fn beacon(address: u16, link_quality: u8) -> Vec<u8> {
+        let [lo, hi] = address.to_le_bytes();
+        vec![
+            0x26,
+            0x42,
+            0x83,
+            0x0,
+            0x7,
+            0x0,
+            0x0,
+            0x0,
+            0x0,
+            0x2,
+            lo,
+            hi,
+            0x0,
+            0x0,
+            0x0,
+            0x0,
+            0x0,
+            0x0,
+            0x34,
+            0x12,
+            0xff,
+            0xcf,
+            0x1,
+            0x9,
+            0x0,
+            link_quality,
+            0x0,
+            0x0,
+            0x0,
+            0x0,
+            0x0,
+            0x0,
+            0x0,
+            0x0,
+            0x0,
+            0x0,
+            0x0,
+            0x0,
+            0x0,
+            0x0,
+            0x0,
+        ]
+    }
+
+    fn associate_cnf(status: u8, short_address: u16) -> Vec<u8> {
+        let [lo, hi] = short_address.to_le_bytes();
+        let mut frame = vec![0xe, 0x42, 0x82, status, lo, hi];
+        frame.extend_from_slice(&[0x0; 11]);
+        frame
+    }
+
+    fn policy() -> JoinPolicy {
+        let mut policy = JoinPolicy::new(PhyId::STD_ETSI_863_PHY_3).unwrap();
+        policy.channels = [0, 1].into_iter().collect();
+        policy.scan_duration = 0;
+        policy
+    }
+
+    #[test]
+    fn join_next_channel_and_coordinator() {
+        let no_beacon = scan_cnf(0xea);
+        let success = scan_cnf(0x0);
+        let beacon1 = beacon(0xabcd, 200);
+        let beacon2 = beacon(0xabce, 100);
+        let at_capacity = associate_cnf(0x1, 0xffff);
+        let associated = associate_cnf(0x0, 0x0005);
+        let mut client = Client::new(MockTransport::new(&[
+            SCAN_SRSP,
+            &no_beacon,
+            SCAN_SRSP,
+            &beacon1,
+            &beacon2,
+            &success,
+            ASSOCIATE_SRSP,
+            &at_capacity,
+            ASSOCIATE_SRSP,
+            &associated,
+        ]));
+
+        let device = Device::join(&mut client, &policy()).unwrap();
+        assert_eq!(device.short_address(), ShortAddress { address: 0x0005 });
+        assert_eq!(
+            device.coordinator().coord_address,
+            Address::Addr16Bit(ShortAddress { address: 0xabce })
+        );
+        assert_eq!(device.coordinator().logical_channel, 1);
+        assert!(client.pop_indication().is_none());
+    }
+
+    #[test]
+    fn join_skips_denying_coordinators() {
+        let success = scan_cnf(0x0);
+        let mut not_permitting = beacon(0xabcc, 250);
+        not_permitting[21] = 0x4f;
+        let beacon1 = beacon(0xabcd, 200);
+        let beacon2 = beacon(0xabce, 100);
+        let denied = associate_cnf(0x2, 0xffff);
+        let associated = associate_cnf(0x0, 0x0006);
+        let mut client = Client::new(MockTransport::new(&[
+            SCAN_SRSP,
+            &not_permitting,
+            &beacon1,
+            &beacon2,
+            &success,
+            ASSOCIATE_SRSP,
+            &denied,
+            ASSOCIATE_SRSP,
+            &associated,
+        ]));
+
+        let device = Device::join(&mut client, &policy()).unwrap();
+        assert_eq!(device.short_address(), ShortAddress { address: 0x0006 });
+        assert_eq!(
+            device.coordinator().coord_address,
+            Address::Addr16Bit(ShortAddress { address: 0xabce })
+        );
+    }
+
+    #[test]
+    fn join_reports_failed_step() {
+        let no_beacon = scan_cnf(0xea);
+        let mut client = Client::new(MockTransport::new(&[
+            SCAN_SRSP, &no_beacon, SCAN_SRSP, &no_beacon,
+        ]));
+        match Device::join(&mut client, &policy()) {
+            Err(error) => {
+                assert_eq!(error.status(), Some(Status::NoBeacon));
+                assert!(matches!(error, Error::JoinFailed(JoinStep::ScanCnf, _)));
+            }
+            x => panic!("Unexpected result {:?}", x),
+        }
+
+        let success = scan_cnf(0x0);
+        let beacon1 = beacon(0xabcd, 200);
+        let denied = associate_cnf(0x2, 0xffff);
+        let mut client = Client::new(MockTransport::new(&[
+            SCAN_SRSP,
+            &success,
+            SCAN_SRSP,
+            &beacon1,
+            &success,
+            ASSOCIATE_SRSP,
+            &denied,
+        ]));
+        match Device::join(&mut client, &policy()) {
+            Err(Error::JoinFailed(JoinStep::AssociateCnf, error)) => assert!(matches!(
+                *error,
+                Error::AssociationRefused(AssociationStatus::PANAccessDenied)
+            )),
+            x => panic!("Unexpected result {:?}", x),
+        }
+
+        // Beacons below the link quality of the policy are ignored.
+        let mut policy = policy();
+        policy.min_link_quality = 201;
+        let mut client = Client::new(MockTransport::new(&[
+            SCAN_SRSP, &beacon1, &success, SCAN_SRSP, &no_beacon,
+        ]));
+        assert!(matches!(
+            Device::join(&mut client, &policy),
+            Err(Error::JoinFailed(JoinStep::ScanCnf, _))
+        ));
+    }
+}
@@ -0,0 +1,284 @@
+use crate::error::Error;
+use crate::phy::PhyDescriptor;
+use crate::subsystem::mac::areq::DataCnf;
+use crate::subsystem::mac::sreq::DataReq;
+use crate::types::{Address, AddressMode, KeyIdMode, SecurityLevel};
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+// SUN FSK PPDU: preamble, 2 bytes SFD and 2 bytes PHR ahead of the PSDU.
+const SFD_LENGTH: usize = 2;
+const PHR_LENGTH: usize = 2;
+const FCS_LENGTH: usize = 4;
+
+// Frame control, sequence number and destination PAN id, the source PAN id
+// is compressed.
+const MAC_HEADER_LENGTH: usize = 5;
+
+pub fn frame_airtime(phy: &PhyDescriptor, preamble_length: u8, psdu_length: usize) -> Duration {
+    // The preamble and SFD are sent uncoded, e.g. LRM only codes the PHR and
+    // PSDU.
+    let sync_bits = 8 * (preamble_length as u64 + SFD_LENGTH as u64);
+    let data_bits = 8 * (PHR_LENGTH + psdu_length) as u64;
+
+    let nanos = sync_bits * 1_000_000_000 / phy.symbol_rate as u64
+        + data_bits * 1_000_000_000 / phy.data_rate as u64;
+    Duration::from_nanos(nanos)
+}
+
+// MAC frame length of a DataReq, the FH IEs added by the CoP for
+// include_fh_ies are not known to the host and aren't counted.
+pub fn data_req_psdu_length(data_req: &DataReq) -> usize {
+    let address_length = |mode: AddressMode| match mode {
+        AddressMode::Addr16Bit => 2,
+        AddressMode::Addr64Bit => 8,
+    };
+    let dest_address_mode = match data_req.dest_address {
+        Address::Addr16Bit(_) => AddressMode::Addr16Bit,
+        Address::Addr64Bit(_) => AddressMode::Addr64Bit,
+    };
+
+    MAC_HEADER_LENGTH
+        + address_length(dest_address_mode)
+        + address_length(data_req.src_address_mode)
+        + security_overhead(data_req.security_level, data_req.key_id_mode)
+        + data_req.ie_payload.len()
+        + data_req.data_payload.len()
+        + FCS_LENGTH
+}
+
+pub fn data_req_airtime(phy: &PhyDescriptor, preamble_length: u8, data_req: &DataReq) -> Duration {
+    frame_airtime(phy, preamble_length, data_req_psdu_length(data_req))
+}
+
+// Auxiliary security header and MIC.
+fn security_overhead(security_level: SecurityLevel, key_id_mode: KeyIdMode) -> usize {
+    let mic_length = match security_level {
+        SecurityLevel::NoSecurity => return 0,
+        SecurityLevel::MIC32Auth | SecurityLevel::AESEncryptionMIC32 => 4,
+        SecurityLevel::MIC64Auth | SecurityLevel::AESEncryptionMIC64 => 8,
+        SecurityLevel::MIC128Auth | SecurityLevel::AESEncryptionMIC128 => 16,
+        SecurityLevel::AESEncryption => 0,
+    };
+
+    let key_id_length = match key_id_mode {
+        KeyIdMode::NotUsed => 0,
+        KeyIdMode::Key1ByteIndex => 1,
+        KeyIdMode::Key4ByteIndex => 5,
+        KeyIdMode::Key8ByteIndex => 9,
+    };
+
+    // Security control and frame counter.
+    5 + key_id_length + mic_length
+}
+
+// Sliding window airtime budget, e.g. 1% over one hour for the ETSI
+// 863-870 MHz sub-bands.
+pub struct DutyCycle {
+    window: Duration,
+    limit: Duration,
+    transmissions: VecDeque<(Instant, Duration)>,
+    pending: HashMap<u8, Duration>,
+}
+
+impl DutyCycle {
+    // The ratio is the share of the window which may be spent transmitting.
+    pub fn new(window: Duration, ratio: f64) -> Result<DutyCycle, Error> {
+        match (0.0..=1.0).contains(&ratio) {
+            true => Ok(DutyCycle::with_limit(window, window.mul_f64(ratio))),
+            false => Err(Error::InvalidDutyCycleRatio(ratio)),
+        }
+    }
+
+    pub fn etsi_1_percent() -> DutyCycle {
+        DutyCycle::with_limit(Duration::from_secs(3600), Duration::from_secs(36))
+    }
+
+    pub fn etsi_10_percent() -> DutyCycle {
+        DutyCycle::with_limit(Duration::from_secs(3600), Duration::from_secs(360))
+    }
+
+    fn with_limit(window: Duration, limit: Duration) -> DutyCycle {
+        DutyCycle {
+            window,
+            limit,
+            transmissions: VecDeque::new(),
+            pending: HashMap::new(),
+        }
+    }
+
+    pub fn limit(&self) -> Duration {
+        self.limit
+    }
+
+    pub fn used(&mut self, now: Instant) -> Duration {
+        self.expire(now);
+        self.transmissions.iter().map(|(_, airtime)| *airtime).sum()
+    }
+
+    pub fn available(&mut self, now: Instant) -> Duration {
+        self.limit.saturating_sub(self.used(now))
+    }
+
+    // How long to wait before the airtime fits into the budget, None if it
+    // never does.
+    pub fn delay(&mut self, now: Instant, airtime: Duration) -> Option<Duration> {
+        if airtime > self.limit {
+            return None;
+        }
+
+        let mut used = self.used(now);
+        if used + airtime <= self.limit {
+            return Some(Duration::ZERO);
+        }
+
+        for (time, transmitted) in self.transmissions.iter() {
+            used -= *transmitted;
+            if used + airtime <= self.limit {
+                return Some((*time + self.window).saturating_duration_since(now));
+            }
+        }
+
+        Some(self.window)
+    }
+
+    // Accounts a DataReq about to be sent, the retries are added once its
+    // DataCnf arrives, see confirm.
+    pub fn try_reserve(
+        &mut self,
+        now: Instant,
+        handle: u8,
+        airtime: Duration,
+    ) -> Result<(), Error> {
+        match self.delay(now, airtime) {
+            Some(Duration::ZERO) => {
+                self.record(now, airtime);
+                self.pending.insert(handle, airtime);
+                Ok(())
+            }
+            Some(delay) => Err(Error::DutyCycleExceeded(delay)),
+            None => Err(Error::DutyCycleExceeded(self.window)),
+        }
+    }
+
+    pub fn confirm(&mut self, now: Instant, data_cnf: &DataCnf) {
+        if let Some(airtime) = self.pending.remove(&data_cnf.handle)
+            && data_cnf.retries > 0
+        {
+            self.record(now, airtime * data_cnf.retries as u32);
+        }
+    }
+
+    fn record(&mut self, now: Instant, airtime: Duration) {
+        self.transmissions.push_back((now, airtime));
+    }
+
+    fn expire(&mut self, now: Instant) {
+        while let Some((time, _)) = self.transmissions.front() {
+            if now.saturating_duration_since(*time) < self.window {
+                break;
+            }
+            self.transmissions.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DutyCycle, data_req_airtime, data_req_psdu_length, frame_airtime};
+    use crate::error::Error;
+    use crate::phy::PhyDescriptor;
+    use crate::subsystem::mac::areq::DataCnf;
+    use crate::subsystem::mac::sreq::DataReq;
+    use crate::types::{
+        Address, AddressMode, FHIEFlags, KeyIdMode, KeySource, PhyId, SecurityLevel, ShortAddress,
+        Status, TxOption,
+    };
+    use std::time::{Duration, Instant};
+
+    fn data_req(length: usize) -> DataReq {
+        DataReq {
+            dest_address: Address::Addr16Bit(ShortAddress { address: 0x1 }),
+            dest_pan_id: 0x1234,
+            src_address_mode: AddressMode::Addr64Bit,
+            handle: 0x1,
+            tx_option: TxOption::ACK,
+            channel: 0,
+            power: 0,
+            key_source: KeySource { key: [0; 8] },
+            security_level: SecurityLevel::AESEncryptionMIC32,
+            key_id_mode: KeyIdMode::Key1ByteIndex,
+            key_index: 0,
+            include_fh_ies: FHIEFlags::empty(),
+            data_payload: vec![0; length],
+            ie_payload: Vec::new(),
+        }
+    }
+
+    fn data_cnf(handle: u8, retries: u8) -> DataCnf {
+        DataCnf {
+            status: Status::Success,
+            handle,
+            timestamp: 0,
+            timestamp2: 0,
+            retries,
+            link_quality: 0,
+            correlation: 0,
+            rssi: 0,
+            frame_counter: 0,
+        }
+    }
+
+    #[test]
+    fn airtime() {
+        let phy = PhyDescriptor::get(PhyId::STD_ETSI_863_PHY_3).unwrap();
+        // 8 bytes at 50 kbps.
+        assert_eq!(frame_airtime(phy, 4, 0), Duration::from_micros(1280));
+
+        let data_req = data_req(100);
+        assert_eq!(data_req_psdu_length(&data_req), 5 + 2 + 8 + 10 + 100 + 4);
+        assert_eq!(
+            data_req_airtime(phy, 4, &data_req),
+            Duration::from_micros(1280 + 129 * 160)
+        );
+
+        let phy = PhyDescriptor::get(PhyId::GENERIC_ETSI_LRM_863_PHY_131).unwrap();
+        assert_eq!(frame_airtime(phy, 4, 0), Duration::from_micros(2400 + 3200));
+    }
+
+    #[test]
+    fn duty_cycle_budget() {
+        assert_eq!(DutyCycle::etsi_1_percent().limit(), Duration::from_secs(36));
+        assert!(matches!(
+            DutyCycle::new(Duration::from_secs(100), 1.5),
+            Err(Error::InvalidDutyCycleRatio(_))
+        ));
+        assert!(matches!(
+            DutyCycle::new(Duration::from_secs(100), f64::NAN),
+            Err(Error::InvalidDutyCycleRatio(_))
+        ));
+
+        let mut duty_cycle = DutyCycle::new(Duration::from_secs(100), 0.01).unwrap();
+        let airtime = Duration::from_millis(400);
+        let start = Instant::now();
+
+        duty_cycle.try_reserve(start, 1, airtime).unwrap();
+        duty_cycle.confirm(start, &data_cnf(1, 1));
+        assert_eq!(duty_cycle.used(start), Duration::from_millis(800));
+
+        let later = start + Duration::from_secs(10);
+        assert!(matches!(
+            duty_cycle.try_reserve(later, 2, airtime),
+            Err(Error::DutyCycleExceeded(x)) if x == Duration::from_secs(90)
+        ));
+        assert_eq!(
+            duty_cycle.delay(later, Duration::from_millis(200)),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(duty_cycle.delay(later, Duration::from_secs(2)), None);
+
+        let expired = start + Duration::from_secs(100);
+        assert_eq!(duty_cycle.available(expired), Duration::from_secs(1));
+        duty_cycle.try_reserve(expired, 2, airtime).unwrap();
+    }
+}
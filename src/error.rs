@@ -1,3 +1,33 @@
+use crate::subsystem::rpc::MTCommandError;
+use crate::types::{
+    AssociationStatus, FHPIBAttributeId, MACPIBAttributeId, MTExtendedHeaderStatus, Status,
+};
+
+// The step of Coordinator::start which failed.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum StartStep {
+    SysReset,
+    MACReset,
+    SetPANId,
+    SetShortAddress,
+    SetRxOnWhenIdle,
+    SetAssociationPermit,
+    SetAttribute(MACPIBAttributeId),
+    EnableFrequencyHopping,
+    SetFHAttribute(FHPIBAttributeId),
+    Start,
+    StartCnf,
+}
+
+// The step of Device::join which failed.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum JoinStep {
+    Scan,
+    ScanCnf,
+    Associate,
+    AssociateCnf,
+}
+
 #[derive(Debug)]
 pub enum Error {
     InvalidStartOfFrame(u8),
@@ -9,9 +39,12 @@ pub enum Error {
     InvalidErrorCode(u8),
     InvalidAddressMode(u8),
     InvalidTxOption(u8),
+    InvalidIEId(u8),
     InvalidSecurityLevel(u8),
     InvalidKeyIdMode(u8),
     InvalidFrameType(u8),
+    InvalidMACFrameType(u8),
+    InvalidKeyIdLookupSize(u8),
     InvalidAssociationStatus(u8),
     InvalidDisassociationReason(u8),
     InvalidMACPIBAttributeId(u8),
@@ -19,8 +52,55 @@ pub enum Error {
     InvalidSecurityPIBAttributeId(u8),
     InvalidScanType(u8),
     InvalidPhyId(u8),
+    InvalidChannel(u8),
+    InvalidResultListCount(usize),
     InvalidStatus(u8),
     InvalidBeaconType(u8),
+    InvalidBeaconOrder(u8),
+    InvalidSuperframeOrder(u8),
+    InvalidDutyCycleRatio(f64),
+    InvalidChannelFunction(u8),
+    InvalidChannelPlan(u8),
+    InvalidExcludedChannels(u8),
+    InvalidRegistryVersion(u8),
+    InvalidNetworkName,
     NotEnoughBytes,
+    TrailingBytes(usize),
+    InvalidLength(u8),
+    PayloadTooLong(usize),
+    DiscardedBytes(usize),
     NotImplemented,
+    MTCommandError(MTCommandError),
+    Timeout,
+    DutyCycleExceeded(std::time::Duration),
+    Status(Status),
+    AssociationRefused(AssociationStatus),
+    StartFailed(StartStep, Box<Error>),
+    JoinFailed(JoinStep, Box<Error>),
+    Fragmentation(MTExtendedHeaderStatus),
+    Io(std::io::Error),
+}
+
+impl Error {
+    // The Status of a request the CoP refused, also through a failed step.
+    pub fn status(&self) -> Option<Status> {
+        match self {
+            Error::Status(status) => Some(*status),
+            Error::StartFailed(_, error) => error.status(),
+            Error::JoinFailed(_, error) => error.status(),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+impl From<bytes::TryGetError> for Error {
+    fn from(_: bytes::TryGetError) -> Self {
+        Error::NotEnoughBytes
+    }
 }
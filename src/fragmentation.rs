@@ -0,0 +1,391 @@
+use crate::error::Error;
+use crate::frame::{CommandCode, MAX_DATA_LENGTH, MTExtendedHeader, MTFrame, MTHeader};
+use crate::types::{CommandType, MTExtendedHeaderStatus};
+use std::collections::VecDeque;
+
+pub const MAX_BLOCK_LENGTH: usize = MAX_DATA_LENGTH - 4;
+pub const MAX_PACKET_LENGTH: usize = 256 * MAX_BLOCK_LENGTH;
+
+struct Sequence {
+    header: MTHeader,
+    packet_length: u16,
+    block_length: usize,
+    next_block: u8,
+    payload: Vec<u8>,
+}
+
+pub struct Reassembler {
+    sequence: Option<Sequence>,
+    acks: VecDeque<MTFrame>,
+}
+
+impl Reassembler {
+    pub fn new() -> Reassembler {
+        Reassembler {
+            sequence: None,
+            acks: VecDeque::new(),
+        }
+    }
+
+    // Returns the frame once it is complete, frames without a fragmentation
+    // extended header are returned as they are. Every fragment queues an ack
+    // frame which has to be sent back to the CoP, see pop_ack.
+    pub fn push(&mut self, frame: MTFrame) -> Result<Option<MTFrame>, Error> {
+        let (stack_id, block, packet_length) = match frame.extended_header {
+            Some(MTExtendedHeader::V2 {
+                stack_id,
+                block,
+                packet_length,
+            }) => (stack_id, block, packet_length),
+            Some(MTExtendedHeader::V4 { status, .. }) if is_abort(status) => {
+                // The CoP gave up on the sequence, it will never complete.
+                if let Some(ref sequence) = self.sequence
+                    && is_same_command(&sequence.header.command, &frame.header.command)
+                {
+                    self.sequence = None;
+                }
+                return Ok(Some(frame));
+            }
+            _ => return Ok(Some(frame)),
+        };
+
+        let command = frame.header.command.clone();
+        let result = self.push_block(frame, block, packet_length);
+
+        let status = match result {
+            Ok(Some(_)) => MTExtendedHeaderStatus::FragmentationSequenceCompleted,
+            Ok(None) => MTExtendedHeaderStatus::Success,
+            Err(Error::Fragmentation(status)) => status,
+            Err(_) => MTExtendedHeaderStatus::FragmentationSequenceAborted,
+        };
+
+        if result.is_err() {
+            self.sequence = None;
+        }
+
+        self.acks
+            .push_back(ack_frame(&command, stack_id, block, status));
+
+        result
+    }
+
+    pub fn pop_ack(&mut self) -> Option<MTFrame> {
+        self.acks.pop_front()
+    }
+
+    pub fn reset(&mut self) {
+        self.sequence = None;
+        self.acks.clear();
+    }
+
+    fn push_block(
+        &mut self,
+        frame: MTFrame,
+        block: u8,
+        packet_length: u16,
+    ) -> Result<Option<MTFrame>, Error> {
+        let block_length = frame.payload.len();
+
+        if block == 0 {
+            // A new sequence replaces any unfinished one.
+            if block_length == 0 || block_length > packet_length as usize {
+                return Err(Error::Fragmentation(
+                    MTExtendedHeaderStatus::BlockLengthChanged,
+                ));
+            }
+
+            self.sequence = Some(Sequence {
+                header: frame.header,
+                packet_length,
+                block_length,
+                next_block: 1,
+                payload: frame.payload,
+            });
+        } else {
+            let sequence = match self.sequence {
+                Some(ref mut sequence)
+                    if sequence.next_block == block
+                        && sequence.packet_length == packet_length
+                        && is_same_command(&sequence.header.command, &frame.header.command) =>
+                {
+                    sequence
+                }
+                _ => {
+                    return Err(Error::Fragmentation(
+                        MTExtendedHeaderStatus::BlockOutOfOrder,
+                    ));
+                }
+            };
+
+            // All blocks but the last one have the length of the first block.
+            let remaining = packet_length as usize - sequence.payload.len();
+            if block_length != sequence.block_length.min(remaining) {
+                return Err(Error::Fragmentation(
+                    MTExtendedHeaderStatus::BlockLengthChanged,
+                ));
+            }
+
+            sequence.payload.extend(frame.payload);
+            sequence.next_block = sequence.next_block.wrapping_add(1);
+        }
+
+        match self.sequence {
+            Some(ref sequence) if sequence.payload.len() == packet_length as usize => {
+                let sequence = self.sequence.take().unwrap();
+                // The reassembled frame is returned as a plain frame. Its
+                // payload doesn't fit the one byte header length, which is left
+                // at 0: use payload.len() instead.
+                Ok(Some(MTFrame {
+                    header: MTHeader {
+                        length: 0,
+                        command: CommandCode {
+                            is_extended: false,
+                            ..sequence.header.command
+                        },
+                    },
+                    extended_header: None,
+                    payload: sequence.payload,
+                }))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+impl Default for Reassembler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Splits a payload which doesn't fit into a single MT frame into V2
+// fragments, each of which has to be acked by the receiver.
+pub fn fragment(command: &CommandCode, payload: &[u8]) -> Result<Vec<MTFrame>, Error> {
+    // Block numbers are a single byte.
+    if payload.len() > MAX_PACKET_LENGTH {
+        return Err(Error::PayloadTooLong(payload.len()));
+    }
+    let packet_length = payload.len() as u16;
+
+    let fragments = payload
+        .chunks(MAX_BLOCK_LENGTH)
+        .enumerate()
+        .map(|(block, payload)| MTFrame {
+            header: MTHeader {
+                length: (4 + payload.len()) as u8,
+                command: CommandCode {
+                    is_extended: true,
+                    ..command.clone()
+                },
+            },
+            extended_header: Some(MTExtendedHeader::V2 {
+                stack_id: 0,
+                block: block as u8,
+                packet_length,
+            }),
+            payload: payload.to_vec(),
+        })
+        .collect();
+
+    Ok(fragments)
+}
+
+// Returns the block and status of fragmentation acks and extended status
+// frames, None for any other frame.
+pub fn fragment_status(frame: &MTFrame) -> Option<(u8, MTExtendedHeaderStatus)> {
+    match frame.extended_header {
+        Some(MTExtendedHeader::V3 { block, status, .. })
+        | Some(MTExtendedHeader::V4 { block, status, .. }) => Some((block, status)),
+        _ => None,
+    }
+}
+
+// Acks go back with the same subsystem and id, an SREQ is acked by an SRSP
+// and an AREQ by an AREQ.
+pub fn ack_frame(
+    command: &CommandCode,
+    stack_id: u8,
+    block: u8,
+    status: MTExtendedHeaderStatus,
+) -> MTFrame {
+    let cmd_type = match command.cmd_type {
+        CommandType::SREQ => CommandType::SRSP,
+        cmd_type => cmd_type,
+    };
+
+    MTFrame {
+        header: MTHeader {
+            length: 3,
+            command: CommandCode {
+                is_extended: true,
+                cmd_type,
+                subsystem: command.subsystem,
+                id: command.id,
+            },
+        },
+        extended_header: Some(MTExtendedHeader::V3 {
+            stack_id,
+            block,
+            status,
+        }),
+        payload: Vec::new(),
+    }
+}
+
+fn is_same_command(a: &CommandCode, b: &CommandCode) -> bool {
+    a.cmd_type == b.cmd_type && a.subsystem == b.subsystem && a.id == b.id
+}
+
+fn is_abort(status: MTExtendedHeaderStatus) -> bool {
+    matches!(
+        status,
+        MTExtendedHeaderStatus::MemoryAllocationError
+            | MTExtendedHeaderStatus::FragmentationSequenceAborted
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MAX_PACKET_LENGTH, Reassembler};
+    use crate::error::Error;
+    use crate::frame::{CommandCode, MTExtendedHeader, MTFrame, MTHeader};
+    use crate::types::{CommandType, MTExtendedHeaderStatus, MTSubsystem};
+
+    fn fragment(block: u8, packet_length: u16, payload: &[u8]) -> MTFrame {
+        MTFrame {
+            header: MTHeader {
+                length: (4 + payload.len()) as u8,
+                command: CommandCode {
+                    is_extended: true,
+                    cmd_type: CommandType::AREQ,
+                    subsystem: MTSubsystem::MAC,
+                    id: 0x85,
+                },
+            },
+            extended_header: Some(MTExtendedHeader::V2 {
+                stack_id: 0,
+                block,
+                packet_length,
+            }),
+            payload: payload.to_vec(),
+        }
+    }
+
+    fn ack_status(reassembler: &mut Reassembler) -> MTExtendedHeaderStatus {
+        let ack = reassembler.pop_ack().unwrap();
+        let mut buffer = Vec::new();
+        ack.encode_into(&mut buffer);
+        assert_eq!(buffer[..5], [0x3, 0xc2, 0x85, 0x18, ack_block(&ack)]);
+
+        match ack.extended_header {
+            Some(MTExtendedHeader::V3 { status, .. }) => status,
+            x => panic!("Unexpected extended header {:?}", x),
+        }
+    }
+
+    fn ack_block(ack: &MTFrame) -> u8 {
+        match ack.extended_header {
+            Some(MTExtendedHeader::V3 { block, .. }) => block,
+            _ => 0xff,
+        }
+    }
+
+    #[test]
+    fn reassemble_blocks() {
+        let mut reassembler = Reassembler::new();
+
+        assert!(reassembler.push(fragment(0, 5, &[1, 2])).unwrap().is_none());
+        assert_eq!(
+            ack_status(&mut reassembler),
+            MTExtendedHeaderStatus::Success
+        );
+        assert!(reassembler.push(fragment(1, 5, &[3, 4])).unwrap().is_none());
+        assert_eq!(
+            ack_status(&mut reassembler),
+            MTExtendedHeaderStatus::Success
+        );
+
+        let frame = reassembler.push(fragment(2, 5, &[5])).unwrap().unwrap();
+        assert_eq!(
+            ack_status(&mut reassembler),
+            MTExtendedHeaderStatus::FragmentationSequenceCompleted
+        );
+        assert!(reassembler.pop_ack().is_none());
+        assert_eq!(frame.payload, [1, 2, 3, 4, 5]);
+        assert_eq!(frame.header.command.id, 0x85);
+        assert!(!frame.header.command.is_extended);
+        assert_eq!(frame.header.length, 0);
+        assert!(frame.extended_header.is_none());
+    }
+
+    #[test]
+    fn fragment_and_reassemble() {
+        let command = fragment(0, 0, &[]).header.command;
+        let payload: Vec<u8> = (0..500).map(|x| x as u8).collect();
+
+        let fragments = super::fragment(&command, &payload).unwrap();
+        let lengths: Vec<usize> = fragments.iter().map(|x| x.payload.len()).collect();
+        assert_eq!(lengths, [246, 246, 8]);
+
+        let mut reassembler = Reassembler::new();
+        let mut reassembled = None;
+        for fragment in fragments {
+            assert_eq!(fragment.header.length as usize, 4 + fragment.payload.len());
+            reassembled = reassembler.push(fragment).unwrap();
+        }
+        assert_eq!(reassembled.unwrap().payload, payload);
+    }
+
+    #[test]
+    fn fragment_max_packet_length() {
+        let command = fragment(0, 0, &[]).header.command;
+
+        let payload = vec![0; MAX_PACKET_LENGTH];
+        let fragments = super::fragment(&command, &payload).unwrap();
+        assert_eq!(fragments.len(), 256);
+        assert!(matches!(
+            fragments[255].extended_header,
+            Some(MTExtendedHeader::V2 { block: 255, .. })
+        ));
+
+        let payload = vec![0; MAX_PACKET_LENGTH + 1];
+        assert!(matches!(
+            super::fragment(&command, &payload),
+            Err(Error::PayloadTooLong(length)) if length == MAX_PACKET_LENGTH + 1
+        ));
+    }
+
+    #[test]
+    fn reassemble_rejects_out_of_order_and_length_change() {
+        let mut reassembler = Reassembler::new();
+
+        reassembler.push(fragment(0, 6, &[1, 2])).unwrap();
+        ack_status(&mut reassembler);
+        assert!(matches!(
+            reassembler.push(fragment(2, 6, &[3, 4])),
+            Err(Error::Fragmentation(
+                MTExtendedHeaderStatus::BlockOutOfOrder
+            ))
+        ));
+        assert_eq!(
+            ack_status(&mut reassembler),
+            MTExtendedHeaderStatus::BlockOutOfOrder
+        );
+
+        reassembler.push(fragment(0, 6, &[1, 2])).unwrap();
+        ack_status(&mut reassembler);
+        assert!(matches!(
+            reassembler.push(fragment(1, 6, &[3])),
+            Err(Error::Fragmentation(
+                MTExtendedHeaderStatus::BlockLengthChanged
+            ))
+        ));
+        assert_eq!(
+            ack_status(&mut reassembler),
+            MTExtendedHeaderStatus::BlockLengthChanged
+        );
+
+        // The aborted sequence can't be continued.
+        assert!(reassembler.push(fragment(1, 6, &[3, 4])).is_err());
+    }
+}
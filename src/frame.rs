@@ -4,6 +4,8 @@ use bytes::{Buf, BufMut};
 use num_traits::FromPrimitive;
 use std::io::{Cursor, Read};
 
+pub const MAX_DATA_LENGTH: usize = 250;
+
 #[derive(Debug, Clone)]
 pub struct MTFrame {
     pub header: MTHeader,
@@ -12,6 +14,21 @@ pub struct MTFrame {
 }
 
 impl MTFrame {
+    pub fn from_payload(command: CommandCode, payload: Vec<u8>) -> Result<Self, Error> {
+        if payload.len() > MAX_DATA_LENGTH {
+            return Err(Error::PayloadTooLong(payload.len()));
+        }
+
+        Ok(MTFrame {
+            header: MTHeader {
+                length: payload.len() as u8,
+                command,
+            },
+            extended_header: None,
+            payload,
+        })
+    }
+
     pub fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
         let header = MTHeader::try_decode(Read::by_ref(cursor))?;
 
@@ -33,6 +50,19 @@ impl MTFrame {
         })
     }
 
+    // Same as try_decode, but also checks the header length against the
+    // number of bytes following the header.
+    pub fn try_decode_strict(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let frame = Self::try_decode(cursor)?;
+
+        let extended_header_size = frame.extended_header.as_ref().map_or(0, |x| x.size());
+        if frame.header.length as usize != extended_header_size + frame.payload.len() {
+            return Err(Error::InvalidLength(frame.header.length));
+        }
+
+        Ok(frame)
+    }
+
     pub fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.header.encode_into(buffer);
 
@@ -74,7 +104,7 @@ impl MTHeader {
     }
 
     pub fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
-        let length = cursor.get_u8();
+        let length = cursor.try_get_u8()?;
         let command = CommandCode::try_decode(cursor)?;
         Ok(MTHeader { length, command })
     }
@@ -95,8 +125,8 @@ pub struct CommandCode {
 
 impl CommandCode {
     pub fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
-        let type_and_subsystem = cursor.get_u8();
-        let id = cursor.get_u8();
+        let type_and_subsystem = cursor.try_get_u8()?;
+        let id = cursor.try_get_u8()?;
 
         let is_extended = (type_and_subsystem & 0x80) != 0;
 
@@ -153,8 +183,16 @@ pub enum MTExtendedHeader {
 }
 
 impl MTExtendedHeader {
+    pub fn size(&self) -> usize {
+        match self {
+            MTExtendedHeader::V1 { .. } => 1,
+            MTExtendedHeader::V2 { .. } => 4,
+            MTExtendedHeader::V3 { .. } | MTExtendedHeader::V4 { .. } => 3,
+        }
+    }
+
     pub fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
-        let version_and_stack_id = cursor.get_u8();
+        let version_and_stack_id = cursor.try_get_u8()?;
         let version = (version_and_stack_id & 0xf8) >> 3;
         let stack_id = version_and_stack_id & 0x07;
 
@@ -162,10 +200,10 @@ impl MTExtendedHeader {
             return Ok(MTExtendedHeader::V1 { stack_id });
         }
 
-        let block = cursor.get_u8();
+        let block = cursor.try_get_u8()?;
 
         if version == 2 {
-            let packet_length = cursor.get_u16_le();
+            let packet_length = cursor.try_get_u16_le()?;
             return Ok(MTExtendedHeader::V2 {
                 stack_id,
                 block,
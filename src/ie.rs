@@ -0,0 +1,872 @@
+use crate::error::Error;
+use crate::subsystem::mac::fh_pib::NetworkName;
+use crate::types::{ChannelFunction, WiSUNAsyncFrameType};
+use bytes::{Buf, BufMut};
+use num_traits::FromPrimitive;
+use std::io::Cursor;
+use std::io::Read;
+
+// Header IE element ids.
+const HEADER_IE_WISUN: u8 = 0x2a;
+const HEADER_IE_TERMINATION_1: u8 = 0x7e;
+const HEADER_IE_TERMINATION_2: u8 = 0x7f;
+
+// Payload IE group ids.
+const PAYLOAD_IE_MLME: u8 = 0x1;
+const PAYLOAD_IE_WISUN: u8 = 0x4;
+const PAYLOAD_IE_TERMINATION: u8 = 0xf;
+
+// Wi-SUN header IE sub ids, the first byte of the header IE content.
+const WH_IE_UTT: u8 = 0x01;
+const WH_IE_BT: u8 = 0x02;
+
+// Wi-SUN payload IE sub ids, US and BS are long nested IEs, the others short.
+const WP_IE_US: u8 = 0x01;
+const WP_IE_BS: u8 = 0x02;
+const WP_IE_PAN: u8 = 0x04;
+const WP_IE_NETNAME: u8 = 0x05;
+const WP_IE_PANVER: u8 = 0x06;
+const WP_IE_GTKHASH: u8 = 0x07;
+
+const GTK_HASH_SIZE: usize = 8;
+const GTK_COUNT: usize = 4;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum HeaderIE {
+    UTT(UnicastTiming),
+    BT(BroadcastTiming),
+    // Other Wi-SUN header IEs, e.g. FC or RSL.
+    WiSUN { sub_id: u8, content: Vec<u8> },
+    // Followed by payload IEs.
+    Termination1,
+    // Followed by the MAC payload.
+    Termination2,
+    Other { element_id: u8, content: Vec<u8> },
+}
+
+impl HeaderIE {
+    pub fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let descriptor = cursor.try_get_u16_le()?;
+        let length = (descriptor & 0x7f) as usize;
+        let element_id = ((descriptor >> 7) & 0xff) as u8;
+        let content = read_content(cursor, length)?;
+
+        let ie = match element_id {
+            HEADER_IE_WISUN => {
+                let sub_id = *content.first().ok_or(Error::NotEnoughBytes)?;
+                match sub_id {
+                    WH_IE_UTT => {
+                        HeaderIE::UTT(decode_content(&content[1..], UnicastTiming::try_decode)?)
+                    }
+                    WH_IE_BT => {
+                        HeaderIE::BT(decode_content(&content[1..], BroadcastTiming::try_decode)?)
+                    }
+                    _ => HeaderIE::WiSUN {
+                        sub_id,
+                        content: content[1..].to_vec(),
+                    },
+                }
+            }
+            HEADER_IE_TERMINATION_1 if length == 0 => HeaderIE::Termination1,
+            HEADER_IE_TERMINATION_2 if length == 0 => HeaderIE::Termination2,
+            _ => HeaderIE::Other {
+                element_id,
+                content,
+            },
+        };
+
+        Ok(ie)
+    }
+
+    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+        let mut content = Vec::new();
+        let element_id = match self {
+            HeaderIE::UTT(utt) => {
+                content.put_u8(WH_IE_UTT);
+                utt.encode_into(&mut content);
+                HEADER_IE_WISUN
+            }
+            HeaderIE::BT(bt) => {
+                content.put_u8(WH_IE_BT);
+                bt.encode_into(&mut content);
+                HEADER_IE_WISUN
+            }
+            HeaderIE::WiSUN {
+                sub_id,
+                content: data,
+            } => {
+                content.put_u8(*sub_id);
+                content.extend(data.iter());
+                HEADER_IE_WISUN
+            }
+            HeaderIE::Termination1 => HEADER_IE_TERMINATION_1,
+            HeaderIE::Termination2 => HEADER_IE_TERMINATION_2,
+            HeaderIE::Other {
+                element_id,
+                content: data,
+            } => {
+                content.extend(data.iter());
+                *element_id
+            }
+        };
+
+        buffer.put_u16_le((content.len() as u16 & 0x7f) | (element_id as u16) << 7);
+        buffer.extend(content);
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct UnicastTiming {
+    pub frame_type: WiSUNAsyncFrameType,
+    // Unicast fractional sequence interval, 24 bits.
+    pub ufsi: u32,
+}
+
+impl UnicastTiming {
+    pub fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let frame_type = WiSUNAsyncFrameType::try_decode(cursor)?;
+        let ufsi = cursor.try_get_uint_le(3)? as u32;
+        Ok(UnicastTiming { frame_type, ufsi })
+    }
+
+    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+        self.frame_type.encode_into(buffer);
+        buffer.put_uint_le(self.ufsi as u64, 3);
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct BroadcastTiming {
+    pub slot_number: u16,
+    // Milliseconds since the start of the broadcast interval, 24 bits.
+    pub interval_offset: u32,
+}
+
+impl BroadcastTiming {
+    pub fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let slot_number = cursor.try_get_u16_le()?;
+        let interval_offset = cursor.try_get_uint_le(3)? as u32;
+        Ok(BroadcastTiming {
+            slot_number,
+            interval_offset,
+        })
+    }
+
+    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+        buffer.put_u16_le(self.slot_number);
+        buffer.put_uint_le(self.interval_offset as u64, 3);
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum NestedIE {
+    Short { sub_id: u8, content: Vec<u8> },
+    Long { sub_id: u8, content: Vec<u8> },
+}
+
+impl NestedIE {
+    pub fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let descriptor = cursor.try_get_u16_le()?;
+        let ie = match descriptor & 0x8000 != 0 {
+            true => {
+                let length = (descriptor & 0x7ff) as usize;
+                let sub_id = ((descriptor >> 11) & 0xf) as u8;
+                let content = read_content(cursor, length)?;
+                NestedIE::Long { sub_id, content }
+            }
+            false => {
+                let length = (descriptor & 0xff) as usize;
+                let sub_id = ((descriptor >> 8) & 0x7f) as u8;
+                let content = read_content(cursor, length)?;
+                NestedIE::Short { sub_id, content }
+            }
+        };
+
+        Ok(ie)
+    }
+
+    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+        match self {
+            NestedIE::Short { sub_id, content } => {
+                buffer.put_u16_le((content.len() as u16 & 0xff) | (*sub_id as u16 & 0x7f) << 8);
+                buffer.extend(content.iter());
+            }
+            NestedIE::Long { sub_id, content } => {
+                buffer.put_u16_le(
+                    (content.len() as u16 & 0x7ff) | (*sub_id as u16 & 0xf) << 11 | 0x8000,
+                );
+                buffer.extend(content.iter());
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum PayloadIE {
+    MLME(Vec<NestedIE>),
+    WiSUN(Vec<WiSUNPayloadIE>),
+    Termination,
+    Other { group_id: u8, content: Vec<u8> },
+}
+
+impl PayloadIE {
+    pub fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let descriptor = cursor.try_get_u16_le()?;
+        let length = (descriptor & 0x7ff) as usize;
+        let group_id = ((descriptor >> 11) & 0xf) as u8;
+        let content = read_content(cursor, length)?;
+
+        let ie = match group_id {
+            PAYLOAD_IE_MLME => {
+                let mut cursor = Cursor::new(&content[..]);
+                let mut ies = Vec::new();
+                while cursor.has_remaining() {
+                    ies.push(NestedIE::try_decode(&mut cursor)?);
+                }
+                PayloadIE::MLME(ies)
+            }
+            PAYLOAD_IE_WISUN => {
+                let mut cursor = Cursor::new(&content[..]);
+                let mut ies = Vec::new();
+                while cursor.has_remaining() {
+                    ies.push(WiSUNPayloadIE::try_decode(&mut cursor)?);
+                }
+                PayloadIE::WiSUN(ies)
+            }
+            PAYLOAD_IE_TERMINATION if length == 0 => PayloadIE::Termination,
+            _ => PayloadIE::Other { group_id, content },
+        };
+
+        Ok(ie)
+    }
+
+    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+        let mut content = Vec::new();
+        let group_id = match self {
+            PayloadIE::MLME(ies) => {
+                for ie in ies.iter() {
+                    ie.encode_into(&mut content);
+                }
+                PAYLOAD_IE_MLME
+            }
+            PayloadIE::WiSUN(ies) => {
+                for ie in ies.iter() {
+                    ie.encode_into(&mut content);
+                }
+                PAYLOAD_IE_WISUN
+            }
+            PayloadIE::Termination => PAYLOAD_IE_TERMINATION,
+            PayloadIE::Other {
+                group_id,
+                content: data,
+            } => {
+                content.extend(data.iter());
+                *group_id
+            }
+        };
+
+        buffer.put_u16_le((content.len() as u16 & 0x7ff) | (group_id as u16) << 11 | 0x8000);
+        buffer.extend(content);
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum WiSUNPayloadIE {
+    US(Schedule),
+    BS(BroadcastSchedule),
+    PAN(PanInformation),
+    NetworkName(NetworkName),
+    PANVersion(u16),
+    GTKHash([[u8; GTK_HASH_SIZE]; GTK_COUNT]),
+    Other(NestedIE),
+}
+
+impl WiSUNPayloadIE {
+    pub fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let ie = match NestedIE::try_decode(cursor)? {
+            NestedIE::Long {
+                sub_id: WP_IE_US,
+                ref content,
+            } => WiSUNPayloadIE::US(decode_content(content, Schedule::try_decode)?),
+            NestedIE::Long {
+                sub_id: WP_IE_BS,
+                ref content,
+            } => WiSUNPayloadIE::BS(decode_content(content, BroadcastSchedule::try_decode)?),
+            NestedIE::Short {
+                sub_id: WP_IE_PAN,
+                ref content,
+            } => WiSUNPayloadIE::PAN(decode_content(content, PanInformation::try_decode)?),
+            NestedIE::Short {
+                sub_id: WP_IE_NETNAME,
+                ref content,
+            } => {
+                let name = std::str::from_utf8(content).map_err(|_| Error::InvalidNetworkName)?;
+                WiSUNPayloadIE::NetworkName(NetworkName::new(name)?)
+            }
+            NestedIE::Short {
+                sub_id: WP_IE_PANVER,
+                ref content,
+            } => WiSUNPayloadIE::PANVersion(decode_content(content, |cursor| {
+                Ok(cursor.try_get_u16_le()?)
+            })?),
+            NestedIE::Short {
+                sub_id: WP_IE_GTKHASH,
+                ref content,
+            } => WiSUNPayloadIE::GTKHash(decode_content(content, |cursor| {
+                let mut hashes = [[0x00; GTK_HASH_SIZE]; GTK_COUNT];
+                for hash in hashes.iter_mut() {
+                    cursor.try_copy_to_slice(hash)?;
+                }
+                Ok(hashes)
+            })?),
+            nested => WiSUNPayloadIE::Other(nested),
+        };
+
+        Ok(ie)
+    }
+
+    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+        let mut content = Vec::new();
+        let nested = match self {
+            WiSUNPayloadIE::US(schedule) => {
+                schedule.encode_into(&mut content);
+                NestedIE::Long {
+                    sub_id: WP_IE_US,
+                    content,
+                }
+            }
+            WiSUNPayloadIE::BS(schedule) => {
+                schedule.encode_into(&mut content);
+                NestedIE::Long {
+                    sub_id: WP_IE_BS,
+                    content,
+                }
+            }
+            WiSUNPayloadIE::PAN(pan) => {
+                pan.encode_into(&mut content);
+                NestedIE::Short {
+                    sub_id: WP_IE_PAN,
+                    content,
+                }
+            }
+            WiSUNPayloadIE::NetworkName(name) => NestedIE::Short {
+                sub_id: WP_IE_NETNAME,
+                content: name.as_str().as_bytes().to_vec(),
+            },
+            WiSUNPayloadIE::PANVersion(version) => {
+                content.put_u16_le(*version);
+                NestedIE::Short {
+                    sub_id: WP_IE_PANVER,
+                    content,
+                }
+            }
+            WiSUNPayloadIE::GTKHash(hashes) => {
+                for hash in hashes.iter() {
+                    content.extend(hash.iter());
+                }
+                NestedIE::Short {
+                    sub_id: WP_IE_GTKHASH,
+                    content,
+                }
+            }
+            WiSUNPayloadIE::Other(nested) => return nested.encode_into(buffer),
+        };
+
+        nested.encode_into(buffer);
+    }
+}
+
+// The channel plan, function and exclusions shared by the US and BS IEs.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Schedule {
+    pub dwell_interval: u8, // ms
+    pub clock_drift: u8,
+    pub timing_accuracy: u8,
+    pub channel_plan: ChannelPlan,
+    pub channel_hopping: ChannelHopping,
+    pub excluded_channels: ExcludedChannels,
+}
+
+impl Schedule {
+    pub fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let dwell_interval = cursor.try_get_u8()?;
+        let clock_drift = cursor.try_get_u8()?;
+        let timing_accuracy = cursor.try_get_u8()?;
+        let channel_information = cursor.try_get_u8()?;
+        let channel_plan = ChannelPlan::try_decode(channel_information & 0x7, cursor)?;
+        let channel_hopping = ChannelHopping::try_decode((channel_information >> 3) & 0x7, cursor)?;
+        let excluded_channels = ExcludedChannels::try_decode(channel_information >> 6, cursor)?;
+        Ok(Schedule {
+            dwell_interval,
+            clock_drift,
+            timing_accuracy,
+            channel_plan,
+            channel_hopping,
+            excluded_channels,
+        })
+    }
+
+    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+        buffer.put_u8(self.dwell_interval);
+        buffer.put_u8(self.clock_drift);
+        buffer.put_u8(self.timing_accuracy);
+        buffer.put_u8(
+            self.channel_plan.id()
+                | (self.channel_hopping.channel_function() as u8) << 3
+                | self.excluded_channels.id() << 6,
+        );
+        self.channel_plan.encode_into(buffer);
+        self.channel_hopping.encode_into(buffer);
+        self.excluded_channels.encode_into(buffer);
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct BroadcastSchedule {
+    pub broadcast_interval: u32, // ms
+    pub schedule_id: u16,
+    pub schedule: Schedule,
+}
+
+impl BroadcastSchedule {
+    pub fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let broadcast_interval = cursor.try_get_u32_le()?;
+        let schedule_id = cursor.try_get_u16_le()?;
+        let schedule = Schedule::try_decode(cursor)?;
+        Ok(BroadcastSchedule {
+            broadcast_interval,
+            schedule_id,
+            schedule,
+        })
+    }
+
+    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+        buffer.put_u32_le(self.broadcast_interval);
+        buffer.put_u16_le(self.schedule_id);
+        self.schedule.encode_into(buffer);
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ChannelPlan {
+    RegulatoryDomain {
+        domain: u8,
+        operating_class: u8,
+    },
+    Explicit {
+        channel0_frequency: u32, // kHz, 24 bits
+        channel_spacing: u8,
+        channel_count: u16,
+    },
+    ChannelPlanId {
+        domain: u8,
+        plan_id: u8,
+    },
+}
+
+impl ChannelPlan {
+    fn try_decode(id: u8, cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let plan = match id {
+            0 => ChannelPlan::RegulatoryDomain {
+                domain: cursor.try_get_u8()?,
+                operating_class: cursor.try_get_u8()?,
+            },
+            1 => ChannelPlan::Explicit {
+                channel0_frequency: cursor.try_get_uint_le(3)? as u32,
+                channel_spacing: cursor.try_get_u8()? & 0xf,
+                channel_count: cursor.try_get_u16_le()?,
+            },
+            2 => ChannelPlan::ChannelPlanId {
+                domain: cursor.try_get_u8()?,
+                plan_id: cursor.try_get_u8()?,
+            },
+            _ => return Err(Error::InvalidChannelPlan(id)),
+        };
+
+        Ok(plan)
+    }
+
+    fn id(&self) -> u8 {
+        match self {
+            ChannelPlan::RegulatoryDomain { .. } => 0,
+            ChannelPlan::Explicit { .. } => 1,
+            ChannelPlan::ChannelPlanId { .. } => 2,
+        }
+    }
+
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
+        match self {
+            ChannelPlan::RegulatoryDomain {
+                domain,
+                operating_class,
+            } => {
+                buffer.put_u8(*domain);
+                buffer.put_u8(*operating_class);
+            }
+            ChannelPlan::Explicit {
+                channel0_frequency,
+                channel_spacing,
+                channel_count,
+            } => {
+                buffer.put_uint_le(*channel0_frequency as u64, 3);
+                buffer.put_u8(*channel_spacing & 0xf);
+                buffer.put_u16_le(*channel_count);
+            }
+            ChannelPlan::ChannelPlanId { domain, plan_id } => {
+                buffer.put_u8(*domain);
+                buffer.put_u8(*plan_id);
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ChannelHopping {
+    FixedChannel(u16),
+    TR51CF,
+    DH1CF,
+    // The channel hop list.
+    VendorDefined(Vec<u8>),
+}
+
+impl ChannelHopping {
+    fn try_decode(id: u8, cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let function: ChannelFunction =
+            FromPrimitive::from_u8(id).ok_or(Error::InvalidChannelFunction(id))?;
+        let hopping = match function {
+            ChannelFunction::FixedChannel => ChannelHopping::FixedChannel(cursor.try_get_u16_le()?),
+            ChannelFunction::TR51CF => ChannelHopping::TR51CF,
+            ChannelFunction::DH1CF => ChannelHopping::DH1CF,
+            ChannelFunction::VendorDefined => {
+                let count = cursor.try_get_u8()?;
+                ChannelHopping::VendorDefined(read_content(cursor, count as usize)?)
+            }
+        };
+
+        Ok(hopping)
+    }
+
+    pub fn channel_function(&self) -> ChannelFunction {
+        match self {
+            ChannelHopping::FixedChannel(_) => ChannelFunction::FixedChannel,
+            ChannelHopping::TR51CF => ChannelFunction::TR51CF,
+            ChannelHopping::DH1CF => ChannelFunction::DH1CF,
+            ChannelHopping::VendorDefined(_) => ChannelFunction::VendorDefined,
+        }
+    }
+
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
+        match self {
+            ChannelHopping::FixedChannel(channel) => buffer.put_u16_le(*channel),
+            ChannelHopping::TR51CF | ChannelHopping::DH1CF => {}
+            ChannelHopping::VendorDefined(channels) => {
+                buffer.put_u8(channels.len() as u8);
+                buffer.extend(channels.iter());
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ExcludedChannels {
+    None,
+    // Inclusive ranges of excluded channels.
+    Ranges(Vec<(u16, u16)>),
+    // A bit per channel up to the end of the IE.
+    Mask(Vec<u8>),
+}
+
+impl ExcludedChannels {
+    fn try_decode(id: u8, cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let excluded = match id {
+            0 => ExcludedChannels::None,
+            1 => {
+                let count = cursor.try_get_u8()?;
+                let mut ranges = Vec::new();
+                for _ in 0..count {
+                    ranges.push((cursor.try_get_u16_le()?, cursor.try_get_u16_le()?));
+                }
+                ExcludedChannels::Ranges(ranges)
+            }
+            2 => ExcludedChannels::Mask(read_content(cursor, cursor.remaining())?),
+            _ => return Err(Error::InvalidExcludedChannels(id)),
+        };
+
+        Ok(excluded)
+    }
+
+    fn id(&self) -> u8 {
+        match self {
+            ExcludedChannels::None => 0,
+            ExcludedChannels::Ranges(_) => 1,
+            ExcludedChannels::Mask(_) => 2,
+        }
+    }
+
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
+        match self {
+            ExcludedChannels::None => {}
+            ExcludedChannels::Ranges(ranges) => {
+                buffer.put_u8(ranges.len() as u8);
+                for (start, end) in ranges.iter() {
+                    buffer.put_u16_le(*start);
+                    buffer.put_u16_le(*end);
+                }
+            }
+            ExcludedChannels::Mask(mask) => buffer.extend(mask.iter()),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct PanInformation {
+    pub pan_size: u16,
+    pub routing_cost: u16,
+    pub use_parent_bs_ie: bool,
+    // Layer 3 routing (RPL) when set, otherwise layer 2.
+    pub routing_method: bool,
+    pub fan_tps_version: u8,
+}
+
+impl PanInformation {
+    pub fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let pan_size = cursor.try_get_u16_le()?;
+        let routing_cost = cursor.try_get_u16_le()?;
+        let flags = cursor.try_get_u8()?;
+        Ok(PanInformation {
+            pan_size,
+            routing_cost,
+            use_parent_bs_ie: flags & 0x01 != 0,
+            routing_method: flags & 0x02 != 0,
+            fan_tps_version: flags >> 5,
+        })
+    }
+
+    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+        buffer.put_u16_le(self.pan_size);
+        buffer.put_u16_le(self.routing_cost);
+        buffer.put_u8(
+            self.use_parent_bs_ie as u8
+                | (self.routing_method as u8) << 1
+                | (self.fan_tps_version & 0x7) << 5,
+        );
+    }
+}
+
+pub fn decode_header_ies(buffer: &[u8]) -> Result<Vec<HeaderIE>, Error> {
+    let mut cursor = Cursor::new(buffer);
+    let mut ies = Vec::new();
+    while cursor.has_remaining() {
+        ies.push(HeaderIE::try_decode(&mut cursor)?);
+    }
+
+    Ok(ies)
+}
+
+pub fn encode_header_ies(ies: &[HeaderIE]) -> Vec<u8> {
+    let mut buffer = Vec::new();
+    for ie in ies.iter() {
+        ie.encode_into(&mut buffer);
+    }
+    buffer
+}
+
+pub fn decode_payload_ies(buffer: &[u8]) -> Result<Vec<PayloadIE>, Error> {
+    let mut cursor = Cursor::new(buffer);
+    let mut ies = Vec::new();
+    while cursor.has_remaining() {
+        ies.push(PayloadIE::try_decode(&mut cursor)?);
+    }
+
+    Ok(ies)
+}
+
+pub fn encode_payload_ies(ies: &[PayloadIE]) -> Vec<u8> {
+    let mut buffer = Vec::new();
+    for ie in ies.iter() {
+        ie.encode_into(&mut buffer);
+    }
+    buffer
+}
+
+fn read_content(cursor: &mut Cursor<&[u8]>, length: usize) -> Result<Vec<u8>, Error> {
+    let mut content = vec![0x00; length];
+    cursor
+        .read_exact(&mut content)
+        .map_err(|_| Error::NotEnoughBytes)?;
+    Ok(content)
+}
+
+// Known IEs have a fixed layout, left over content means a malformed IE.
+fn decode_content<T>(
+    content: &[u8],
+    decode: impl FnOnce(&mut Cursor<&[u8]>) -> Result<T, Error>,
+) -> Result<T, Error> {
+    let mut cursor = Cursor::new(content);
+    let value = decode(&mut cursor)?;
+    match cursor.remaining() {
+        0 => Ok(value),
+        remaining => Err(Error::TrailingBytes(remaining)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        BroadcastSchedule, BroadcastTiming, ChannelHopping, ChannelPlan, ExcludedChannels,
+        HeaderIE, NestedIE, PanInformation, PayloadIE, Schedule, UnicastTiming, WiSUNPayloadIE,
+        decode_header_ies, decode_payload_ies, encode_header_ies, encode_payload_ies,
+    };
+    use crate::error::Error;
+    use crate::subsystem::mac::fh_pib::NetworkName;
+    use crate::types::WiSUNAsyncFrameType;
+
+    #[test]
+    fn wisun_header_ies() {
+        let data = [
+            0x05, 0x15, 0x01, 0x00, 0x56, 0x34, 0x12, 0x06, 0x15, 0x02, 0x10, 0x00, 0xe8, 0x03,
+            0x00, 0x80, 0x3f,
+        ];
+        let ies = decode_header_ies(&data).unwrap();
+        assert_eq!(
+            ies,
+            vec![
+                HeaderIE::UTT(UnicastTiming {
+                    frame_type: WiSUNAsyncFrameType::PANAdvert,
+                    ufsi: 0x123456,
+                }),
+                HeaderIE::BT(BroadcastTiming {
+                    slot_number: 0x10,
+                    interval_offset: 1000,
+                }),
+                HeaderIE::Termination2,
+            ]
+        );
+        assert_eq!(encode_header_ies(&ies), data);
+    }
+
+    #[test]
+    fn wisun_payload_ies() {
+        let data = [
+            0x1a, 0xa0, 0x06, 0x88, 0xff, 0xff, 0x00, 0x10, 0x01, 0x01, 0x05, 0x04, 0x0a, 0x00,
+            0x20, 0x00, 0x23, 0x05, 0x05, b'w', b'i', b's', b'u', b'n', 0x02, 0x06, 0x34, 0x12,
+            0x00, 0xf8,
+        ];
+        let ies = decode_payload_ies(&data).unwrap();
+        assert_eq!(
+            ies,
+            vec![
+                PayloadIE::WiSUN(vec![
+                    WiSUNPayloadIE::US(Schedule {
+                        dwell_interval: 0xff,
+                        clock_drift: 0xff,
+                        timing_accuracy: 0,
+                        channel_plan: ChannelPlan::RegulatoryDomain {
+                            domain: 1,
+                            operating_class: 1,
+                        },
+                        channel_hopping: ChannelHopping::DH1CF,
+                        excluded_channels: ExcludedChannels::None,
+                    }),
+                    WiSUNPayloadIE::PAN(PanInformation {
+                        pan_size: 10,
+                        routing_cost: 32,
+                        use_parent_bs_ie: true,
+                        routing_method: true,
+                        fan_tps_version: 1,
+                    }),
+                    WiSUNPayloadIE::NetworkName(NetworkName::new("wisun").unwrap()),
+                    WiSUNPayloadIE::PANVersion(0x1234),
+                ]),
+                PayloadIE::Termination,
+            ]
+        );
+        assert_eq!(encode_payload_ies(&ies), data);
+    }
+
+    #[test]
+    fn broadcast_schedules() {
+        let schedule = Schedule {
+            dwell_interval: 250,
+            clock_drift: 255,
+            timing_accuracy: 10,
+            channel_plan: ChannelPlan::Explicit {
+                channel0_frequency: 863_100,
+                channel_spacing: 0,
+                channel_count: 35,
+            },
+            channel_hopping: ChannelHopping::FixedChannel(12),
+            excluded_channels: ExcludedChannels::Ranges(vec![(1, 3), (20, 21)]),
+        };
+        let ies = vec![
+            PayloadIE::WiSUN(vec![
+                WiSUNPayloadIE::BS(BroadcastSchedule {
+                    broadcast_interval: 1020,
+                    schedule_id: 7,
+                    schedule: schedule.clone(),
+                }),
+                WiSUNPayloadIE::US(Schedule {
+                    channel_hopping: ChannelHopping::VendorDefined(vec![1, 5, 9]),
+                    excluded_channels: ExcludedChannels::Mask(vec![0x0f, 0x00, 0x80]),
+                    ..schedule
+                }),
+                WiSUNPayloadIE::GTKHash([[1; 8], [2; 8], [0; 8], [0; 8]]),
+            ]),
+            PayloadIE::Other {
+                group_id: 0x2,
+                content: vec![0xaa],
+            },
+        ];
+
+        let data = encode_payload_ies(&ies);
+        assert_eq!(decode_payload_ies(&data).unwrap(), ies);
+    }
+
+    #[test]
+    fn mlme_nested_ies() {
+        let data = [0x05, 0x88, 0x01, 0x1a, 0xff, 0x00, 0xc8];
+        let ies = decode_payload_ies(&data).unwrap();
+        assert_eq!(
+            ies,
+            vec![PayloadIE::MLME(vec![
+                NestedIE::Short {
+                    sub_id: 0x1a,
+                    content: vec![0xff],
+                },
+                NestedIE::Long {
+                    sub_id: 0x9,
+                    content: vec![],
+                },
+            ])]
+        );
+        assert_eq!(encode_payload_ies(&ies), data);
+    }
+
+    #[test]
+    fn malformed_ies() {
+        // PAN version with a trailing byte.
+        let data = [0x05, 0xa0, 0x03, 0x06, 0x34, 0x12, 0x00];
+        assert!(matches!(
+            decode_payload_ies(&data),
+            Err(Error::TrailingBytes(1))
+        ));
+
+        let data = [0x05, 0xa0, 0x03, 0x06, 0x34, 0x12];
+        assert!(matches!(
+            decode_payload_ies(&data),
+            Err(Error::NotEnoughBytes)
+        ));
+
+        let data = [0x08, 0xa0, 0x06, 0x88, 0xff, 0xff, 0x00, 0x15, 0x01, 0x01];
+        assert!(matches!(
+            decode_payload_ies(&data),
+            Err(Error::InvalidChannelPlan(5))
+        ));
+
+        let data = [0x01, 0x15, 0x01];
+        assert!(matches!(
+            decode_header_ies(&data),
+            Err(Error::NotEnoughBytes)
+        ));
+    }
+}
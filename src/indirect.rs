@@ -0,0 +1,554 @@
+use crate::client::Client;
+use crate::error::Error;
+use crate::subsystem::mac::areq::{DataCnf, PollInd};
+use crate::subsystem::mac::pib::check_status;
+use crate::subsystem::mac::sreq::{DataReq, PurgeReq};
+use crate::types::{Address, Status, TxOption};
+use std::collections::{HashMap, VecDeque};
+use std::io::{Read, Write};
+use std::ops::RangeInclusive;
+use std::time::{Duration, Instant};
+
+const DEFAULT_MAX_ATTEMPTS: u8 = 3;
+
+// Waited for past the persistence time before a transaction without a
+// DataCnf is purged.
+const PURGE_MARGIN: Duration = Duration::from_secs(1);
+
+// Assigned by the queue, DataReq handles are reused once confirmed.
+pub type MessageId = u64;
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Delivery {
+    Delivered(MessageId),
+    Failed(MessageId, Status),
+}
+
+#[derive(Debug, Clone)]
+struct Message {
+    id: MessageId,
+    request: DataReq,
+    attempts: u8,
+}
+
+#[derive(Debug, Clone)]
+struct Transaction {
+    message: Message,
+    sent: Instant,
+    // The CoP no longer knew the handle when purged, the DataCnf is waited for.
+    purged: bool,
+}
+
+// Outbound queues of the devices with RxOnWhenIdle=false. The CoP holds at
+// most capacity transactions until their destination polls, one per device to
+// keep the messages of a device in order. Expired transactions are sent again
+// until max_attempts. The DataReq handles are taken from a range, so that
+// other DataReqs can use the rest.
+#[derive(Debug)]
+pub struct IndirectQueue {
+    capacity: usize,
+    persistence: Duration,
+    max_attempts: u8,
+    next_id: MessageId,
+    handles: RangeInclusive<u8>,
+    next_handle: u8,
+    queues: HashMap<Address, VecDeque<Message>>,
+    transactions: HashMap<u8, Transaction>,
+    polls: HashMap<Address, Instant>,
+}
+
+impl IndirectQueue {
+    // See PhyDescriptor::transaction_persistence_time for the persistence of
+    // the CoP.
+    pub fn new(capacity: usize, persistence: Duration) -> IndirectQueue {
+        IndirectQueue {
+            capacity,
+            persistence,
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            next_id: 0,
+            handles: 0..=u8::MAX,
+            next_handle: 0,
+            queues: HashMap::new(),
+            transactions: HashMap::new(),
+            polls: HashMap::new(),
+        }
+    }
+
+    pub fn max_attempts(&self) -> u8 {
+        self.max_attempts
+    }
+
+    pub fn set_max_attempts(&mut self, max_attempts: u8) {
+        self.max_attempts = max_attempts;
+    }
+
+    pub fn handles(&self) -> RangeInclusive<u8> {
+        self.handles.clone()
+    }
+
+    // Pending transactions keep their handles.
+    pub fn set_handles(&mut self, handles: RangeInclusive<u8>) {
+        self.handles = handles;
+    }
+
+    // Messages waiting to be handed to the CoP.
+    pub fn len(&self) -> usize {
+        self.queues.values().map(|x| x.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queues.is_empty()
+    }
+
+    // Transactions held by the CoP.
+    pub fn pending(&self) -> usize {
+        self.transactions.len()
+    }
+
+    pub fn queued(&self, address: &Address) -> usize {
+        self.queues.get(address).map_or(0, |x| x.len())
+    }
+
+    pub fn last_poll(&self, address: &Address) -> Option<Instant> {
+        self.polls.get(address).copied()
+    }
+
+    // The handle is assigned when the message is sent.
+    pub fn push(&mut self, mut request: DataReq) -> MessageId {
+        request.tx_option |= TxOption::INDIRECT;
+
+        let id = self.next_id;
+        self.next_id += 1;
+        self.queues
+            .entry(request.dest_address)
+            .or_default()
+            .push_back(Message {
+                id,
+                request,
+                attempts: 0,
+            });
+        id
+    }
+
+    // Drops the queued messages of a device, e.g. once it left the network.
+    pub fn remove(&mut self, address: &Address) -> Vec<MessageId> {
+        self.polls.remove(address);
+        self.queues
+            .remove(address)
+            .map_or(Vec::new(), |x| x.iter().map(|y| y.id).collect())
+    }
+
+    // The next DataReq to hand to the CoP, None if the CoP is at capacity, the
+    // handles are used up or every device with queued messages already has a
+    // transaction pending.
+    pub fn next_request(&mut self, now: Instant) -> Option<DataReq> {
+        if self.transactions.len() >= self.capacity {
+            return None;
+        }
+
+        let address = self
+            .queues
+            .iter()
+            .filter(|(address, _)| !self.is_pending(address))
+            .filter_map(|(address, queue)| queue.front().map(|x| (x.id, *address)))
+            .min_by_key(|(id, _)| *id)?
+            .1;
+        let handle = self.allocate_handle()?;
+        let mut message = self.pop_front(&address)?;
+
+        message.request.handle = handle;
+        message.attempts += 1;
+        let request = message.request.clone();
+        self.transactions.insert(
+            handle,
+            Transaction {
+                message,
+                sent: now,
+                purged: false,
+            },
+        );
+        Some(request)
+    }
+
+    // Hands the queued messages to the CoP up to its capacity, returns how
+    // many were sent. Stops once the CoP reports it is out of transactions.
+    pub fn send_pending<T: Read + Write>(
+        &mut self,
+        client: &mut Client<T>,
+        now: Instant,
+    ) -> Result<usize, Error> {
+        let mut sent = 0;
+        while let Some(request) = self.next_request(now) {
+            // Without a response the CoP may hold the transaction, it stays
+            // pending until confirmed or purged.
+            let response = client.request(&request)?;
+            match response.status {
+                Status::Success => sent += 1,
+                Status::TransactionOverflow => {
+                    self.requeue(request.handle, None);
+                    break;
+                }
+                status => {
+                    self.requeue(request.handle, None);
+                    return Err(Error::Status(status));
+                }
+            }
+        }
+        Ok(sent)
+    }
+
+    // A message is requeued if it expired before its destination polled, or
+    // if the CoP was out of transactions. The latter isn't an attempt.
+    pub fn confirm(&mut self, data_cnf: &DataCnf) -> Option<Delivery> {
+        match data_cnf.status {
+            Status::Success => {
+                let transaction = self.transactions.remove(&data_cnf.handle)?;
+                Some(Delivery::Delivered(transaction.message.id))
+            }
+            Status::TransactionExpired => {
+                self.requeue(data_cnf.handle, Some(Status::TransactionExpired))
+            }
+            Status::TransactionOverflow => self.requeue(data_cnf.handle, None),
+            status => {
+                let transaction = self.transactions.remove(&data_cnf.handle)?;
+                Some(Delivery::Failed(transaction.message.id, status))
+            }
+        }
+    }
+
+    // Returns whether messages are queued for the device, a poll answered
+    // without data is a chance to send them.
+    pub fn poll(&mut self, now: Instant, poll_ind: &PollInd) -> bool {
+        self.polls.insert(poll_ind.dev_addr, now);
+        self.queued(&poll_ind.dev_addr) > 0
+    }
+
+    // Handles of the transactions the CoP should have confirmed by now, or
+    // whose DataCnf didn't follow the purge.
+    pub fn stale(&self, now: Instant) -> Vec<u8> {
+        let mut handles: Vec<u8> = self
+            .transactions
+            .iter()
+            .filter(|(_, x)| {
+                now.saturating_duration_since(x.sent) > self.persistence + PURGE_MARGIN
+            })
+            .map(|(handle, _)| *handle)
+            .collect();
+        handles.sort();
+        handles
+    }
+
+    // Purges the stale transactions from the CoP and requeues their messages
+    // like expired ones. A transaction which already left the CoP was usually
+    // sent with its DataCnf still on the way: its handle is kept for the
+    // DataCnf, and fails with InvalidHandle if none came once stale again.
+    pub fn purge_stale<T: Read + Write>(
+        &mut self,
+        client: &mut Client<T>,
+        now: Instant,
+    ) -> Result<Vec<Delivery>, Error> {
+        let mut deliveries = Vec::new();
+        for handle in self.stale(now) {
+            if self.transactions.get(&handle).is_some_and(|x| x.purged) {
+                let transaction = self.transactions.remove(&handle).unwrap();
+                deliveries.push(Delivery::Failed(
+                    transaction.message.id,
+                    Status::InvalidHandle,
+                ));
+                continue;
+            }
+
+            let response = client.request(&PurgeReq { handle })?;
+            match response.status {
+                Status::InvalidHandle => {
+                    if let Some(transaction) = self.transactions.get_mut(&handle) {
+                        transaction.purged = true;
+                        transaction.sent = now;
+                    }
+                }
+                status => {
+                    check_status(status)?;
+                    if let Some(delivery) = self.requeue(handle, Some(Status::TransactionExpired)) {
+                        deliveries.push(delivery);
+                    }
+                }
+            }
+        }
+        Ok(deliveries)
+    }
+
+    fn is_pending(&self, address: &Address) -> bool {
+        self.transactions
+            .values()
+            .any(|x| x.message.request.dest_address == *address)
+    }
+
+    fn pop_front(&mut self, address: &Address) -> Option<Message> {
+        let queue = self.queues.get_mut(address)?;
+        let message = queue.pop_front();
+        if queue.is_empty() {
+            self.queues.remove(address);
+        }
+        message
+    }
+
+    // The first free handle of the range from next_handle on, wrapping
+    // around.
+    fn allocate_handle(&mut self) -> Option<u8> {
+        let (first, last) = (*self.handles.start(), *self.handles.end());
+        if first > last {
+            return None;
+        }
+
+        let start = self.next_handle.clamp(first, last);
+        let handle = (start..=last)
+            .chain(first..start)
+            .find(|x| !self.transactions.contains_key(x))?;
+        self.next_handle = handle.wrapping_add(1);
+        Some(handle)
+    }
+
+    // Puts the message back in front of the queue of its device, unless it
+    // ran out of attempts. A status of None doesn't count as an attempt.
+    fn requeue(&mut self, handle: u8, status: Option<Status>) -> Option<Delivery> {
+        let mut message = self.transactions.remove(&handle)?.message;
+        match status {
+            None => message.attempts -= 1,
+            Some(status) if message.attempts >= self.max_attempts => {
+                return Some(Delivery::Failed(message.id, status));
+            }
+            Some(_) => {}
+        }
+
+        self.queues
+            .entry(message.request.dest_address)
+            .or_default()
+            .push_front(message);
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Delivery, IndirectQueue};
+    use crate::client::Client;
+    use crate::client::tests::MockTransport;
+    use crate::error::Error;
+    use crate::subsystem::mac::areq::{DataCnf, PollInd};
+    use crate::subsystem::mac::sreq::DataReq;
+    use crate::types::{
+        Address, AddressMode, FHIEFlags, KeyIdMode, KeySource, SecurityLevel, ShortAddress, Status,
+        TxOption,
+    };
+    use std::time::{Duration, Instant};
+
+    fn address(address: u16) -> Address {
+        Address::Addr16Bit(ShortAddress { address })
+    }
+
+    fn data_req(dest_address: u16, data: u8) -> DataReq {
+        DataReq {
+            dest_address: address(dest_address),
+            dest_pan_id: 0x1234,
+            src_address_mode: AddressMode::Addr16Bit,
+            handle: 0,
+            tx_option: TxOption::ACK,
+            channel: 0,
+            power: 0,
+            key_source: KeySource { key: [0; 8] },
+            security_level: SecurityLevel::NoSecurity,
+            key_id_mode: KeyIdMode::NotUsed,
+            key_index: 0,
+            include_fh_ies: FHIEFlags::empty(),
+            data_payload: vec![data],
+            ie_payload: vec![],
+        }
+    }
+
+    fn data_cnf(status: Status, handle: u8) -> DataCnf {
+        DataCnf {
+            status,
+            handle,
+            timestamp: 0,
+            timestamp2: 0,
+            retries: 0,
+            link_quality: 0,
+            correlation: 0,
+            rssi: 0,
+            frame_counter: 0,
+        }
+    }
+
+    #[test]
+    fn queue_per_device() {
+        let now = Instant::now();
+        let mut queue = IndirectQueue::new(2, Duration::from_secs(10));
+        let first = queue.push(data_req(0x1, 1));
+        let second = queue.push(data_req(0x1, 2));
+        let other = queue.push(data_req(0x2, 3));
+        let last = queue.push(data_req(0x3, 4));
+        assert_eq!(queue.len(), 4);
+
+        // One transaction per device, up to the capacity of the CoP.
+        let request = queue.next_request(now).unwrap();
+        assert!(
+            request
+                .tx_option
+                .contains(TxOption::INDIRECT | TxOption::ACK)
+        );
+        assert_eq!((request.handle, request.data_payload[0]), (0, 1));
+        let request = queue.next_request(now).unwrap();
+        assert_eq!((request.handle, request.data_payload[0]), (1, 3));
+        assert!(queue.next_request(now).is_none());
+        assert_eq!(queue.pending(), 2);
+
+        let poll_ind = PollInd {
+            dev_addr: address(0x1),
+            pan_id: 0x1234,
+            no_response: false,
+        };
+        assert!(queue.poll(now, &poll_ind));
+        assert_eq!(queue.last_poll(&address(0x1)), Some(now));
+
+        assert_eq!(
+            queue.confirm(&data_cnf(Status::Success, 0)),
+            Some(Delivery::Delivered(first))
+        );
+        let request = queue.next_request(now).unwrap();
+        assert_eq!((request.handle, request.data_payload[0]), (2, 2));
+
+        // An expired message is sent again before the later ones.
+        assert_eq!(
+            queue.confirm(&data_cnf(Status::TransactionExpired, 1)),
+            None
+        );
+        let request = queue.next_request(now).unwrap();
+        assert_eq!((request.handle, request.data_payload[0]), (3, 3));
+        assert_eq!(
+            queue.confirm(&data_cnf(Status::NoAck, 2)),
+            Some(Delivery::Failed(second, Status::NoAck))
+        );
+
+        queue.set_max_attempts(2);
+        assert_eq!(
+            queue.confirm(&data_cnf(Status::TransactionExpired, 3)),
+            Some(Delivery::Failed(other, Status::TransactionExpired))
+        );
+        assert_eq!(queue.confirm(&data_cnf(Status::Success, 3)), None);
+
+        assert_eq!(queue.remove(&address(0x3)), vec![last]);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn handle_range_and_overflow() {
+        let purge_invalid_handle: &[u8] = &[0x1, 0x62, 0xe, 0xe7];
+        let purge_srsp: &[u8] = &[0x1, 0x62, 0xe, 0x0];
+        let mut client = Client::new(MockTransport::new(&[
+            purge_invalid_handle,
+            purge_srsp,
+            purge_invalid_handle,
+        ]));
+
+        let now = Instant::now();
+        let mut queue = IndirectQueue::new(usize::MAX, Duration::from_secs(10));
+        queue.set_handles(0x10..=0x11);
+        let first = queue.push(data_req(0x1, 1));
+        let second = queue.push(data_req(0x2, 2));
+        let third = queue.push(data_req(0x3, 3));
+        assert_eq!(queue.next_request(now).unwrap().handle, 0x10);
+        assert_eq!(queue.next_request(now).unwrap().handle, 0x11);
+        assert!(queue.next_request(now).is_none());
+        assert_eq!(queue.len(), 1);
+
+        // The CoP running out of transactions isn't an attempt.
+        queue.set_max_attempts(1);
+        assert_eq!(
+            queue.confirm(&data_cnf(Status::TransactionOverflow, 0x10)),
+            None
+        );
+        let request = queue.next_request(now).unwrap();
+        assert_eq!((request.handle, request.data_payload[0]), (0x10, 1));
+
+        // The first transaction already left the CoP, its DataCnf is waited
+        // for.
+        let later = now + Duration::from_secs(12);
+        assert_eq!(
+            queue.purge_stale(&mut client, later).unwrap(),
+            vec![Delivery::Failed(second, Status::TransactionExpired)]
+        );
+        assert_eq!(queue.pending(), 1);
+        assert!(queue.stale(later).is_empty());
+        assert_eq!(
+            queue.confirm(&data_cnf(Status::Success, 0x10)),
+            Some(Delivery::Delivered(first))
+        );
+
+        // Failed once stale again without a DataCnf.
+        let request = queue.next_request(later).unwrap();
+        assert_eq!((request.handle, request.data_payload[0]), (0x11, 3));
+        let later = later + Duration::from_secs(12);
+        assert!(queue.purge_stale(&mut client, later).unwrap().is_empty());
+        assert_eq!(queue.next_request(later).map(|x| x.handle), None);
+        let later = later + Duration::from_secs(12);
+        assert_eq!(
+            queue.purge_stale(&mut client, later).unwrap(),
+            vec![Delivery::Failed(third, Status::InvalidHandle)]
+        );
+        assert_eq!(queue.pending(), 0);
+    }
+
+    #[test]
+    fn send_and_purge() {
+        let data_srsp: &[u8] = &[0x1, 0x62, 0x5, 0x0];
+        let overflow_srsp: &[u8] = &[0x1, 0x62, 0x5, 0xf1];
+        let refused_srsp: &[u8] = &[0x1, 0x62, 0x5, 0x1a];
+        let purge_srsp: &[u8] = &[0x1, 0x62, 0xe, 0x0];
+        let mut client = Client::new(MockTransport::new(&[
+            data_srsp,
+            overflow_srsp,
+            refused_srsp,
+            purge_srsp,
+        ]));
+        client.set_timeout(Duration::from_millis(10));
+
+        // The CoP being full stops sending, a refused message is requeued.
+        let now = Instant::now();
+        let mut queue = IndirectQueue::new(4, Duration::from_secs(10));
+        let first = queue.push(data_req(0x1, 1));
+        queue.push(data_req(0x2, 2));
+        assert_eq!(queue.send_pending(&mut client, now).unwrap(), 1);
+        assert_eq!(queue.pending(), 1);
+        assert_eq!(queue.queued(&address(0x2)), 1);
+        assert!(matches!(
+            queue.send_pending(&mut client, now),
+            Err(Error::Status(Status::NoResources))
+        ));
+        assert_eq!(queue.queued(&address(0x2)), 1);
+
+        assert!(queue.stale(now + Duration::from_secs(10)).is_empty());
+        queue.set_max_attempts(1);
+        let later = now + Duration::from_secs(12);
+        assert_eq!(queue.stale(later), vec![0]);
+        assert_eq!(
+            queue.purge_stale(&mut client, later).unwrap(),
+            vec![Delivery::Failed(first, Status::TransactionExpired)]
+        );
+        assert_eq!(queue.pending(), 0);
+
+        let output = &client.get_ref().output;
+        assert_eq!(output[..4], [0xfe, 0x24, 0x22, 0x5]);
+        assert_eq!(
+            output[output.len() - 6..],
+            [0xfe, 0x1, 0x22, 0xe, 0x0, 0x2d]
+        );
+
+        // Without a response the CoP may hold the message, it isn't requeued.
+        assert!(matches!(
+            queue.send_pending(&mut client, later),
+            Err(Error::Timeout)
+        ));
+        assert_eq!(queue.pending(), 1);
+        assert_eq!(queue.queued(&address(0x2)), 0);
+    }
+}
@@ -1,15 +1,33 @@
 #[macro_use]
 extern crate bitflags;
 
+pub mod admission;
+#[cfg(feature = "tokio")]
+pub mod async_client;
+pub mod client;
+#[cfg(feature = "tokio")]
+pub mod codec;
+pub mod command;
+pub mod coordinator;
+pub mod device;
+pub mod duty_cycle;
 pub mod error;
+pub mod fragmentation;
 pub mod frame;
+pub mod ie;
+pub mod indirect;
 pub mod parser;
+pub mod phy;
+pub mod registry;
+pub mod scan;
 pub mod subsystem;
 pub mod types;
 
 #[cfg(test)]
 mod tests {
-    use crate::{frame, subsystem, types};
+    use crate::command::{Command, Request};
+    use crate::error::Error;
+    use crate::{frame, parser, subsystem, types};
     use std::io::Cursor;
 
     #[test]
@@ -169,6 +187,25 @@ mod tests {
         decode_encode_test(&data);
     }
 
+    #[test]
+    fn encode_mac_data_req_lengths_from_payloads() {
+        let data = [
+            0x2, 0x1, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x34, 0x12, 0x2, 0x1, 0x1, 0x0, 0x0, 0x0,
+            0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x1, 0x0, 0x0,
+            0x0, 0xaa,
+        ];
+        let mut request = subsystem::mac::sreq::DataReq::try_decode(&data).unwrap();
+        assert_eq!(request.data_payload, [0xaa]);
+
+        request.data_payload = vec![0x1, 0x2, 0x3];
+        request.ie_payload = vec![0x4, 0x5];
+        let mut buffer = Vec::new();
+        request.encode_into(&mut buffer);
+
+        assert_eq!(buffer[31..35], [0x3, 0x0, 0x2, 0x0]);
+        assert_eq!(buffer[35..], [0x1, 0x2, 0x3, 0x4, 0x5]);
+    }
+
     #[test]
     fn decode_encode_mac_associate_ind() {
         let data = [
@@ -187,6 +224,413 @@ mod tests {
         decode_encode_test(&data);
     }
 
+    #[test]
+    fn parser_feed_slice_resynchronises() {
+        let ping_req = [0xfe, 0x0, 0x21, 0x1, 0x20];
+        let mut data = vec![0x13, 0x37, 0xfe, 0x5];
+        data.extend_from_slice(&ping_req);
+        data.extend_from_slice(&ping_req);
+
+        let mut parser = parser::Parser::new();
+        let mut results = parser.feed_slice(&data[..12]);
+
+        assert!(matches!(
+            results.next(),
+            Some(Err(Error::DiscardedBytes(2)))
+        ));
+        assert!(matches!(
+            results.next(),
+            Some(Err(Error::InvalidFrameCheckSequence(_)))
+        ));
+        assert!(matches!(
+            results.next(),
+            Some(Err(Error::DiscardedBytes(1)))
+        ));
+        assert_eq!(results.next().unwrap().unwrap().header.command.id, 0x01);
+        assert!(results.next().is_none());
+
+        let mut results = parser.feed_slice(&data[12..]);
+        assert_eq!(results.next().unwrap().unwrap().header.command.id, 0x01);
+        assert!(results.next().is_none());
+    }
+
+    #[test]
+    fn into_mt_frame_sets_length_from_payload() {
+        let data = [0x1, 0x62, 0x1, 0x0];
+        let mut cursor = Cursor::new(&data[..]);
+        let frame = frame::MTFrame::try_decode(&mut cursor).unwrap();
+
+        let payload = subsystem::mac::srsp::ResetReq::try_decode(&frame.payload).unwrap();
+        let frame = payload.into_mt_frame().unwrap();
+        assert_eq!(frame.header.length, 0x01);
+
+        let command = frame.header.command.clone();
+        let frame = frame::MTFrame::from_payload(command.clone(), vec![0; 250]).unwrap();
+        assert_eq!(frame.header.length, 250);
+        assert!(matches!(
+            frame::MTFrame::from_payload(command, vec![0; 251]),
+            Err(Error::PayloadTooLong(251))
+        ));
+    }
+
+    #[test]
+    fn decode_truncated_payloads_does_not_panic() {
+        let subsystems = [
+            types::MTSubsystem::RPC,
+            types::MTSubsystem::SYS,
+            types::MTSubsystem::MAC,
+            types::MTSubsystem::UTIL,
+        ];
+        let cmd_types = [
+            types::CommandType::SREQ,
+            types::CommandType::AREQ,
+            types::CommandType::SRSP,
+        ];
+
+        for subsystem in subsystems.iter() {
+            for cmd_type in cmd_types.iter() {
+                for id in 0..=255 {
+                    for length in 0..64 {
+                        for fill in [0x00, 0x01, 0xff] {
+                            let payload = vec![fill; length];
+                            let _ = subsystem::MTFramePayload::try_decode(
+                                subsystem, cmd_type, id, &payload,
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn decode_strict() {
+        let data = [0x1, 0x62, 0x1, 0x0, 0x0];
+        let mut cursor = Cursor::new(&data[..]);
+        assert!(matches!(
+            frame::MTFrame::try_decode_strict(&mut cursor),
+            Err(Error::InvalidLength(0x01))
+        ));
+
+        let mut cursor = Cursor::new(&data[..]);
+        let frame = frame::MTFrame::try_decode(&mut cursor).unwrap();
+        let command = &frame.header.command;
+        assert!(
+            subsystem::MTFramePayload::try_decode(
+                &command.subsystem,
+                &command.cmd_type,
+                command.id,
+                &frame.payload,
+            )
+            .is_ok()
+        );
+        assert!(matches!(
+            subsystem::MTFramePayload::try_decode_strict(
+                &command.subsystem,
+                &command.cmd_type,
+                command.id,
+                &frame.payload,
+            ),
+            Err(Error::TrailingBytes(1))
+        ));
+
+        let data = [0x1, 0x62, 0x1, 0x0];
+        let mut cursor = Cursor::new(&data[..]);
+        assert!(frame::MTFrame::try_decode_strict(&mut cursor).is_ok());
+    }
+
+    #[test]
+    fn command_constants_and_response() {
+        fn response_command_code<R: Request>() -> frame::CommandCode {
+            R::Response::command_code()
+        }
+
+        let mut buffer = Vec::new();
+        subsystem::mac::sreq::ScanReq::command_code().encode_into(&mut buffer);
+        response_command_code::<subsystem::mac::sreq::ScanReq>().encode_into(&mut buffer);
+        assert_eq!(buffer, [0x22, 0x0c, 0x62, 0x0c]);
+
+        assert_eq!(
+            subsystem::sys::areq::ResetInd::SUBSYSTEM,
+            types::MTSubsystem::SYS
+        );
+        assert_eq!(
+            subsystem::sys::areq::ResetInd::CMD_TYPE,
+            types::CommandType::AREQ
+        );
+        assert_eq!(
+            subsystem::sys::areq::ResetInd::ID,
+            types::SYSCommandId::ResetInd as u8
+        );
+    }
+
+    #[test]
+    fn decode_mac_scan_cnf_results() {
+        use subsystem::mac::areq::{ScanCnf, ScanResults};
+
+        // Energy detect scan of channels 0 to 3, channel 2 left unscanned.
+        let mut data = vec![0x19, 0x42, 0x8c, 0x0, 0x0, 0x0, 0x1, 0x4];
+        data.extend([0x0; 16]);
+        data.extend([0x3, 0xa, 0x14, 0x1e]);
+        decode_encode_test(&data);
+
+        let scan_cnf = ScanCnf::try_decode(&data[3..]).unwrap();
+        assert_eq!(
+            scan_cnf.results,
+            ScanResults::EnergyDetect(vec![10, 20, 30])
+        );
+
+        let scan_channels = types::ChannelsBitMap {
+            channels: [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xf],
+        };
+        assert_eq!(
+            scan_cnf.energy_readings(&scan_channels).unwrap(),
+            [(0, 10), (1, 20), (3, 30)]
+        );
+
+        // Energies of a scan of other channels than the ones given.
+        let scan_channels = types::ChannelsBitMap {
+            channels: [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x1f],
+        };
+        assert!(matches!(
+            scan_cnf.energy_readings(&scan_channels),
+            Err(Error::InvalidResultListCount(3))
+        ));
+
+        // Active scan with a single PAN descriptor.
+        let mut data = vec![0x37, 0x42, 0x8c, 0x0, 0x1, 0x0, 0x1];
+        data.extend([0x0; 17]);
+        data.extend([0x1, 0x2, 0x1, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x34, 0x12]);
+        data.extend([0xff, 0xcf, 0xb, 0x0, 0x0, 0xc8, 0x0, 0x0, 0x0, 0x0, 0x0]);
+        data.extend([0x0; 11]);
+        decode_encode_test(&data);
+
+        let scan_cnf = ScanCnf::try_decode(&data[3..]).unwrap();
+        match scan_cnf.results {
+            ScanResults::PanDescriptors(ref descriptors) => {
+                assert_eq!(descriptors.len(), 1);
+                assert_eq!(descriptors[0].pan_id, 0x1234);
+                assert_eq!(descriptors[0].logical_channel, 11);
+                assert_eq!(descriptors[0].link_quality, 200);
+            }
+            ref x => panic!("Unexpected results {:?}", x),
+        }
+    }
+
+    #[test]
+    fn channels_bit_map_set() {
+        use types::{ChannelsBitMap, PhyId};
+
+        let mut channels: ChannelsBitMap = (0..4).chain(11..=26).collect();
+        assert_eq!(channels.len(), 20);
+        assert_eq!(format!("{:?}", channels), "ChannelsBitMap {0-3, 11-26}");
+
+        assert!(channels.insert(7).unwrap());
+        assert!(!channels.insert(7).unwrap());
+        assert!(channels.remove(0));
+        assert!(!channels.remove(0));
+        assert!(channels.contains(7) && !channels.contains(0));
+        assert!(matches!(
+            channels.insert(136),
+            Err(Error::InvalidChannel(136))
+        ));
+
+        // Channel 0 is the least significant bit of the first byte sent.
+        let mut buffer = Vec::new();
+        ChannelsBitMap::from_iter([0, 9, 135]).encode_into(&mut buffer);
+        assert_eq!(buffer[..2], [0x1, 0x2]);
+        assert_eq!(buffer[16], 0x80);
+
+        let low: ChannelsBitMap = (0..8).collect();
+        let even: ChannelsBitMap = (0..16).step_by(2).collect();
+        assert_eq!(low.union(&even).len(), 12);
+        assert_eq!((low & even).iter().collect::<Vec<u8>>(), [0, 2, 4, 6]);
+        assert_eq!((!low).len(), 128);
+        assert!((low | !low).len() == 136);
+
+        assert_eq!(
+            ChannelsBitMap::all(PhyId::GENERIC_ETSI_863_PHY_133).len(),
+            17
+        );
+        assert!(even.validate(PhyId::GENERIC_ETSI_863_PHY_133).is_ok());
+        assert!(matches!(
+            low.validate(PhyId::GENERIC_CHINA_433_PHY_128),
+            Err(Error::InvalidChannel(7))
+        ));
+        assert!(matches!(
+            channels.insert_for(PhyId::STD_ETSI_863_PHY_3, 34),
+            Err(Error::InvalidChannel(34))
+        ));
+    }
+
+    #[test]
+    fn validate_logical_channel() {
+        use subsystem::mac::sreq;
+        use types::PhyId;
+
+        let mut sync_req = sreq::SyncReq {
+            logical_channel: 33,
+            channel_page: 9,
+            track_beacon: false,
+            phy_id: PhyId::STD_ETSI_863_PHY_3,
+        };
+        assert!(sync_req.validate_channel().is_ok());
+
+        sync_req.logical_channel = 34;
+        assert!(matches!(
+            sync_req.validate_channel(),
+            Err(Error::InvalidChannel(34))
+        ));
+
+        sync_req.phy_id = PhyId::PHY_ID_NONE;
+        assert!(matches!(
+            sync_req.validate_channel(),
+            Err(Error::InvalidPhyId(0))
+        ));
+
+        let data = [
+            0x16, 0x22, 0x6, 0x80, 0x9, 0x3, 0x3, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x34,
+            0x12, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+        ];
+        let associate_req = sreq::AssociateReq::try_decode(&data[3..]).unwrap();
+        assert!(matches!(
+            associate_req.validate_channel(),
+            Err(Error::InvalidChannel(0x80))
+        ));
+    }
+
+    #[test]
+    fn decode_capability_information() {
+        use subsystem::mac::areq;
+        use types::CapabilityInformation;
+
+        let mut data = [
+            0x14, 0x42, 0x81, 0x80, 0xbd, 0xc9, 0xb, 0x0, 0x4b, 0x12, 0x0, 0x8e, 0x0, 0x0, 0x0,
+            0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+        ];
+        let associate_ind = areq::AssociateInd::try_decode(&data[3..]).unwrap();
+        assert_eq!(
+            associate_ind.capabilities,
+            CapabilityInformation::FFD
+                | CapabilityInformation::MAINS_POWER
+                | CapabilityInformation::RX_ON_IDLE
+                | CapabilityInformation::ALLOC_ADDR
+        );
+        assert!(
+            !associate_ind
+                .capabilities
+                .contains(CapabilityInformation::SECURITY)
+        );
+
+        data[11] = 0x90;
+        let associate_ind = areq::AssociateInd::try_decode(&data[3..]).unwrap();
+        assert_eq!(associate_ind.capabilities.bits(), 0x90);
+        assert_eq!(associate_ind.encode(), &data[3..]);
+    }
+
+    #[test]
+    fn fh_ie_flags_and_ie_id_list() {
+        use subsystem::mac::sreq;
+        use types::{FHIEFlags, IEId};
+
+        let mut data = [
+            0x2c, 0x22, 0x3, 0x0, 0x0, 0x0, 0x0, 0xff, 0xff, 0x0, 0x9, 0x1, 0xf, 0xf, 0x1, 0x0,
+            0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+            0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x1, 0xf, 0xa, 0xff, 0x3f, 0x2, 0x1, 0x10,
+        ];
+        decode_encode_test(&data);
+
+        let start_req = sreq::StartReq::try_decode(&data[3..]).unwrap();
+        assert_eq!(start_req.ie_id_list, vec![IEId::UTT, IEId::US]);
+
+        let flags: FHIEFlags = start_req.ie_id_list.iter().copied().collect();
+        assert_eq!(flags, FHIEFlags::UTT | FHIEFlags::US);
+        assert_eq!(flags.bits(), 0x00010002);
+        assert_eq!(flags.ie_ids(), start_req.ie_id_list);
+        assert_eq!(FHIEFlags::from(IEId::BS), FHIEFlags::BS);
+
+        // The IE count is taken from the list.
+        let mut start_req = start_req;
+        start_req.ie_id_list.push(IEId::BS);
+        let mut buffer = Vec::new();
+        start_req.encode_into(&mut buffer);
+        assert_eq!(buffer[41..], [0x3, 0x1, 0x10, 0x11]);
+
+        data[46] = 0x15;
+        let start_req = sreq::StartReq::try_decode(&data[3..]).unwrap();
+        assert_eq!(start_req.ie_id_list, vec![IEId::UTT, IEId::GTKHASH]);
+        decode_encode_test(&data);
+
+        data[46] = 0x4;
+        assert!(matches!(
+            sreq::StartReq::try_decode(&data[3..]),
+            Err(Error::InvalidIEId(0x4))
+        ));
+
+        let mut cursor = Cursor::new(&[0x8, 0x0, 0x4, 0x0][..]);
+        let flags = FHIEFlags::try_decode(&mut cursor).unwrap();
+        assert_eq!(flags, FHIEFlags::BT | FHIEFlags::PAN);
+        assert_eq!(flags.ie_ids(), vec![IEId::BT, IEId::PAN]);
+
+        let all = FHIEFlags::RSL
+            | FHIEFlags::NETNAME
+            | FHIEFlags::PANVER
+            | FHIEFlags::GTKHASH
+            | FHIEFlags::from_bits_retain(0x80000000);
+        let mut buffer = Vec::new();
+        all.encode_into(&mut buffer);
+        assert_eq!(buffer, [0x4, 0x0, 0x38, 0x80]);
+        let decoded = FHIEFlags::try_decode(&mut Cursor::new(&buffer[..])).unwrap();
+        assert_eq!(decoded, all);
+        assert_eq!(
+            decoded.ie_ids(),
+            vec![IEId::RSL, IEId::NETNAME, IEId::PANVER, IEId::GTKHASH]
+        );
+    }
+
+    #[test]
+    fn superframe_spec() {
+        use subsystem::mac::sreq;
+        use types::SuperframeSpec;
+
+        let data = [0x16, 0xc4];
+        let spec = SuperframeSpec::try_decode(&mut Cursor::new(&data[..])).unwrap();
+        assert_eq!(
+            spec,
+            SuperframeSpec {
+                beacon_order: 6,
+                superframe_order: 1,
+                final_cap_slot: 4,
+                battery_life_extension: false,
+                pan_coordinator: true,
+                association_permit: true,
+            }
+        );
+
+        let mut buffer = Vec::new();
+        spec.encode_into(&mut buffer);
+        assert_eq!(buffer, data);
+
+        let data = [
+            0x2a, 0x22, 0x3, 0x0, 0x0, 0x0, 0x0, 0xff, 0xff, 0x0, 0x9, 0x1, 0xf, 0xf, 0x1, 0x0,
+            0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+            0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x1, 0xf, 0xa, 0xff, 0x3f, 0x0,
+        ];
+        let mut start_req = sreq::StartReq::try_decode(&data[3..]).unwrap();
+        assert!(start_req.beacon_timing().unwrap().is_none());
+
+        start_req.beacon_order = 6;
+        start_req.super_frame_order = 1;
+        let timing = start_req.beacon_timing().unwrap().unwrap();
+        assert_eq!(timing.superframe_duration * 32, timing.beacon_interval);
+
+        start_req.super_frame_order = 7;
+        assert!(matches!(
+            start_req.beacon_timing(),
+            Err(Error::InvalidSuperframeOrder(7))
+        ));
+    }
+
     fn decode_encode_test(data: &[u8]) {
         let mut cursor = Cursor::new(&data[..]);
         let frame = frame::MTFrame::try_decode(&mut cursor).unwrap();
@@ -17,6 +17,11 @@ enum State {
 pub struct Parser {
     buffer: Vec<u8>,
     state: State,
+    pending: Vec<u8>,
+}
+
+pub struct Frames<'a> {
+    parser: &'a mut Parser,
 }
 
 impl Parser {
@@ -24,10 +29,15 @@ impl Parser {
         Parser {
             buffer: Vec::new(),
             state: State::WaitingStartOfFrame,
+            pending: Vec::new(),
         }
     }
 
     pub fn feed(&mut self, new_byte: u8) -> Option<Result<MTFrame, Error>> {
+        if !self.pending.is_empty() {
+            return self.feed_slice(&[new_byte]).next();
+        }
+
         match self.state {
             State::WaitingStartOfFrame => {
                 if new_byte != START_OF_FRAME_TOKEN {
@@ -64,13 +74,65 @@ impl Parser {
         None
     }
 
+    // Buffers the given bytes and returns an iterator over the frames they
+    // complete. Bytes of an incomplete frame are kept for the next call.
+    pub fn feed_slice(&mut self, data: &[u8]) -> Frames<'_> {
+        if !matches!(self.state, State::WaitingStartOfFrame) {
+            self.pending.push(START_OF_FRAME_TOKEN);
+            self.pending.append(&mut self.buffer);
+            self.state = State::WaitingStartOfFrame;
+        }
+        self.pending.extend_from_slice(data);
+        Frames { parser: self }
+    }
+
     pub fn reset(&mut self) {
         self.buffer.clear();
+        self.pending.clear();
         self.state = State::WaitingStartOfFrame;
     }
 
+    fn next_pending(&mut self) -> Option<Result<MTFrame, Error>> {
+        let start = self
+            .pending
+            .iter()
+            .position(|byte| *byte == START_OF_FRAME_TOKEN)
+            .unwrap_or(self.pending.len());
+
+        if start > 0 {
+            self.pending.drain(..start);
+            return Some(Err(Error::DiscardedBytes(start)));
+        }
+
+        let payload_length = *self.pending.get(1)? as usize;
+        let frame_end = 1 + MTHeader::size() + payload_length;
+        let fcs = *self.pending.get(frame_end)?;
+        let frame_bytes = &self.pending[1..frame_end];
+
+        if MTFrame::compute_frame_check_sequence(frame_bytes) != fcs {
+            // Only the start of frame token is dropped, as the next frame
+            // may start within the bytes gathered for this one.
+            let error = Error::InvalidFrameCheckSequence(frame_bytes.to_vec());
+            self.pending.remove(0);
+            return Some(Err(error));
+        }
+
+        let mut cursor = Cursor::new(frame_bytes);
+        let result = MTFrame::try_decode(&mut cursor);
+        self.pending.drain(..=frame_end);
+        Some(result)
+    }
+
     fn parse_frame(&self) -> Result<MTFrame, Error> {
         let mut cursor = Cursor::new(self.buffer.as_slice());
         MTFrame::try_decode(&mut cursor)
     }
 }
+
+impl Iterator for Frames<'_> {
+    type Item = Result<MTFrame, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.parser.next_pending()
+    }
+}
@@ -0,0 +1,289 @@
+use crate::error::Error;
+use crate::types::{ChannelsBitMap, PhyId};
+use std::time::Duration;
+
+// Max PSDU of the 802.15.4g SUN PHYs, the PHY header carries an 11 bit
+// frame length.
+pub const SUN_MAX_PSDU_SIZE: u16 = 2047;
+
+// aBaseSuperframeDuration, 16 slots of 60 symbols.
+pub const BASE_SUPERFRAME_DURATION: u32 = 960;
+
+// A beacon order of 15 starts a non beacon-enabled network.
+pub const NON_BEACON_ORDER: u8 = 15;
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Modulation {
+    FSK2,
+    GFSK2,
+    // TI long range mode, 2-FSK with FEC and spreading.
+    LRM,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct PhyDescriptor {
+    pub phy_id: PhyId,
+    pub channel0_frequency: u32, // kHz
+    pub channel_spacing: u32,    // kHz
+    pub channel_count: u16,
+    pub modulation: Modulation,
+    pub data_rate: u32,   // bps
+    pub symbol_rate: u32, // symbols/s
+    pub max_psdu_size: u16,
+}
+
+const PHY_DESCRIPTORS: [PhyDescriptor; 8] = [
+    PhyDescriptor {
+        phy_id: PhyId::STD_US_915_PHY_1,
+        channel0_frequency: 902_200,
+        channel_spacing: 200,
+        channel_count: 129,
+        modulation: Modulation::FSK2,
+        data_rate: 50_000,
+        symbol_rate: 50_000,
+        max_psdu_size: SUN_MAX_PSDU_SIZE,
+    },
+    PhyDescriptor {
+        phy_id: PhyId::STD_ETSI_863_PHY_3,
+        channel0_frequency: 863_125,
+        channel_spacing: 200,
+        channel_count: 34,
+        modulation: Modulation::FSK2,
+        data_rate: 50_000,
+        symbol_rate: 50_000,
+        max_psdu_size: SUN_MAX_PSDU_SIZE,
+    },
+    PhyDescriptor {
+        phy_id: PhyId::GENERIC_CHINA_433_PHY_128,
+        channel0_frequency: 433_300,
+        channel_spacing: 200,
+        channel_count: 7,
+        modulation: Modulation::FSK2,
+        data_rate: 50_000,
+        symbol_rate: 50_000,
+        max_psdu_size: SUN_MAX_PSDU_SIZE,
+    },
+    PhyDescriptor {
+        phy_id: PhyId::GENERIC_US_LRM_915_PHY_129,
+        channel0_frequency: 902_200,
+        channel_spacing: 200,
+        channel_count: 129,
+        modulation: Modulation::LRM,
+        data_rate: 5_000,
+        symbol_rate: 20_000,
+        max_psdu_size: SUN_MAX_PSDU_SIZE,
+    },
+    PhyDescriptor {
+        phy_id: PhyId::GENERIC_CHINA_LRM_433_PHY_130,
+        channel0_frequency: 433_300,
+        channel_spacing: 200,
+        channel_count: 7,
+        modulation: Modulation::LRM,
+        data_rate: 5_000,
+        symbol_rate: 20_000,
+        max_psdu_size: SUN_MAX_PSDU_SIZE,
+    },
+    PhyDescriptor {
+        phy_id: PhyId::GENERIC_ETSI_LRM_863_PHY_131,
+        channel0_frequency: 863_125,
+        channel_spacing: 200,
+        channel_count: 34,
+        modulation: Modulation::LRM,
+        data_rate: 5_000,
+        symbol_rate: 20_000,
+        max_psdu_size: SUN_MAX_PSDU_SIZE,
+    },
+    PhyDescriptor {
+        phy_id: PhyId::GENERIC_US_915_PHY_132,
+        channel0_frequency: 902_400,
+        channel_spacing: 400,
+        channel_count: 64,
+        modulation: Modulation::GFSK2,
+        data_rate: 200_000,
+        symbol_rate: 200_000,
+        max_psdu_size: SUN_MAX_PSDU_SIZE,
+    },
+    PhyDescriptor {
+        phy_id: PhyId::GENERIC_ETSI_863_PHY_133,
+        channel0_frequency: 863_225,
+        channel_spacing: 400,
+        channel_count: 17,
+        modulation: Modulation::GFSK2,
+        data_rate: 200_000,
+        symbol_rate: 200_000,
+        max_psdu_size: SUN_MAX_PSDU_SIZE,
+    },
+];
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct BeaconTiming {
+    pub beacon_interval: Duration,
+    // The active portion of the superframe.
+    pub superframe_duration: Duration,
+}
+
+impl PhyDescriptor {
+    // None for the ids which don't name a PHY, e.g. PHY_ID_NONE.
+    pub fn get(phy_id: PhyId) -> Option<&'static PhyDescriptor> {
+        PHY_DESCRIPTORS.iter().find(|x| x.phy_id == phy_id)
+    }
+
+    pub fn try_get(phy_id: PhyId) -> Result<&'static PhyDescriptor, Error> {
+        PhyDescriptor::get(phy_id).ok_or(Error::InvalidPhyId(phy_id as u8))
+    }
+
+    pub fn is_valid_channel(&self, channel: u8) -> bool {
+        (channel as u16) < self.channel_count
+    }
+
+    pub fn validate_channel(&self, channel: u8) -> Result<(), Error> {
+        match self.is_valid_channel(channel) {
+            true => Ok(()),
+            false => Err(Error::InvalidChannel(channel)),
+        }
+    }
+
+    // Centre frequency in kHz.
+    pub fn channel_frequency(&self, channel: u8) -> Option<u32> {
+        match self.is_valid_channel(channel) {
+            true => Some(self.channel0_frequency + channel as u32 * self.channel_spacing),
+            false => None,
+        }
+    }
+
+    pub fn channels(&self) -> ChannelsBitMap {
+        (0..self.channel_count).map(|x| x as u8).collect()
+    }
+
+    // Checks that a channel mask only names channels of the PHY.
+    pub fn validate_channels(&self, channels: &ChannelsBitMap) -> Result<(), Error> {
+        match channels.difference(&self.channels()).iter().next() {
+            Some(channel) => Err(Error::InvalidChannel(channel)),
+            None => Ok(()),
+        }
+    }
+
+    pub fn symbol_duration(&self) -> Duration {
+        Duration::from_nanos(1_000_000_000 / self.symbol_rate as u64)
+    }
+
+    // None for a non beacon-enabled network, the superframe order has to be
+    // at most the beacon order.
+    pub fn beacon_timing(
+        &self,
+        beacon_order: u8,
+        superframe_order: u8,
+    ) -> Result<Option<BeaconTiming>, Error> {
+        if beacon_order > NON_BEACON_ORDER {
+            return Err(Error::InvalidBeaconOrder(beacon_order));
+        }
+        if beacon_order == NON_BEACON_ORDER {
+            return Ok(None);
+        }
+        if superframe_order > beacon_order {
+            return Err(Error::InvalidSuperframeOrder(superframe_order));
+        }
+
+        Ok(Some(BeaconTiming {
+            beacon_interval: self.superframe_duration(beacon_order),
+            superframe_duration: self.superframe_duration(superframe_order),
+        }))
+    }
+
+    // macTransactionPersistenceTime counts beacon intervals, or base
+    // superframe durations in a non beacon-enabled network.
+    pub fn transaction_persistence_time(
+        &self,
+        beacon_order: u8,
+        units: u16,
+    ) -> Result<Duration, Error> {
+        let order = match beacon_order {
+            NON_BEACON_ORDER => 0,
+            x if x < NON_BEACON_ORDER => x,
+            x => return Err(Error::InvalidBeaconOrder(x)),
+        };
+
+        Ok(self.superframe_duration(order) * units as u32)
+    }
+
+    // aBaseSuperframeDuration * 2^order.
+    fn superframe_duration(&self, order: u8) -> Duration {
+        let symbols = (BASE_SUPERFRAME_DURATION as u64) << order;
+        Duration::from_nanos(symbols * 1_000_000_000 / self.symbol_rate as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BeaconTiming, Modulation, PhyDescriptor};
+    use crate::error::Error;
+    use crate::types::{ChannelsBitMap, PhyId};
+    use std::time::Duration;
+
+    #[test]
+    fn channel_frequencies() {
+        let phy = PhyDescriptor::get(PhyId::STD_US_915_PHY_1).unwrap();
+        assert_eq!(phy.channel_frequency(0), Some(902_200));
+        assert_eq!(phy.channel_frequency(128), Some(927_800));
+        assert_eq!(phy.channel_frequency(129), None);
+
+        let phy = PhyDescriptor::get(PhyId::GENERIC_ETSI_863_PHY_133).unwrap();
+        assert_eq!(phy.modulation, Modulation::GFSK2);
+        assert_eq!(phy.channel_frequency(16), Some(869_625));
+        assert!(matches!(
+            phy.validate_channel(17),
+            Err(Error::InvalidChannel(17))
+        ));
+
+        assert!(PhyDescriptor::get(PhyId::PHY_ID_NONE).is_none());
+        assert!(matches!(
+            PhyDescriptor::try_get(PhyId::MRFSK_GENERIC_PHY_ID_BEGIN),
+            Err(Error::InvalidPhyId(0x4))
+        ));
+    }
+
+    #[test]
+    fn validate_channels() {
+        let phy = PhyDescriptor::get(PhyId::GENERIC_CHINA_433_PHY_128).unwrap();
+        assert_eq!(phy.channels().len(), 7);
+        assert!(phy.validate_channels(&(0..7).collect()).is_ok());
+
+        let channels: ChannelsBitMap = (5..10).collect();
+        assert!(matches!(
+            phy.validate_channels(&channels),
+            Err(Error::InvalidChannel(7))
+        ));
+    }
+
+    #[test]
+    fn beacon_timing() {
+        let phy = PhyDescriptor::get(PhyId::STD_ETSI_863_PHY_3).unwrap();
+        assert_eq!(phy.symbol_duration(), Duration::from_micros(20));
+        assert_eq!(
+            phy.beacon_timing(6, 2).unwrap(),
+            Some(BeaconTiming {
+                beacon_interval: Duration::from_micros(1_228_800),
+                superframe_duration: Duration::from_micros(76_800),
+            })
+        );
+        assert_eq!(phy.beacon_timing(15, 15).unwrap(), None);
+
+        assert!(matches!(
+            phy.beacon_timing(4, 5),
+            Err(Error::InvalidSuperframeOrder(5))
+        ));
+        assert!(matches!(
+            phy.beacon_timing(16, 0),
+            Err(Error::InvalidBeaconOrder(16))
+        ));
+
+        assert_eq!(
+            phy.transaction_persistence_time(15, 500).unwrap(),
+            Duration::from_millis(9_600)
+        );
+        assert_eq!(
+            phy.transaction_persistence_time(6, 2).unwrap(),
+            Duration::from_micros(2_457_600)
+        );
+    }
+}
@@ -0,0 +1,546 @@
+use crate::admission::{Member, NO_SHORT_ADDRESS, USE_EXTENDED_ADDRESS, associate_rsp_device};
+use crate::command::Command;
+use crate::error::Error;
+use crate::frame::MTFrame;
+use crate::subsystem::mac::areq::{
+    AssociateInd, CommStatusInd, DataInd, DisassociateInd, OrphanInd,
+};
+use crate::subsystem::mac::sreq::{AssociateRsp, OrphanRsp};
+use crate::types::{
+    Address, AssociationStatus, CapabilityInformation, CommandType, ExtendedAddress, KeyIdMode,
+    KeySource, MTSubsystem, SecurityLevel, ShortAddress, Status,
+};
+use bytes::{Buf, BufMut};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Cursor;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const REGISTRY_VERSION: u8 = 1;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct DeviceRecord {
+    pub extended_address: ExtendedAddress,
+    pub short_address: ShortAddress,
+    pub capabilities: CapabilityInformation,
+    pub key_source: KeySource,
+    pub security_level: SecurityLevel,
+    pub key_id_mode: KeyIdMode,
+    pub key_index: u8,
+    // Of the last secured frame received from the device.
+    pub frame_counter: u32,
+    // Saved with a resolution of one second.
+    pub last_seen: Option<SystemTime>,
+    pub link_quality: u8,
+    pub rssi: i8,
+    // Set once an AssociateRsp was delivered to the device.
+    pub joined: bool,
+}
+
+impl DeviceRecord {
+    pub fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let extended_address = ExtendedAddress::try_decode(cursor)?;
+        let short_address = ShortAddress::try_decode(cursor)?;
+        let capabilities = CapabilityInformation::try_decode(cursor)?;
+        let key_source = KeySource::try_decode(cursor)?;
+        let security_level = SecurityLevel::try_decode(cursor)?;
+        let key_id_mode = KeyIdMode::try_decode(cursor)?;
+        let key_index = cursor.try_get_u8()?;
+        let frame_counter = cursor.try_get_u32_le()?;
+        let last_seen = match cursor.try_get_u64_le()? {
+            0 => None,
+            secs => Some(UNIX_EPOCH + Duration::from_secs(secs)),
+        };
+        let link_quality = cursor.try_get_u8()?;
+        let rssi = cursor.try_get_i8()?;
+        let joined = cursor.try_get_u8()? != 0;
+        Ok(DeviceRecord {
+            extended_address,
+            short_address,
+            capabilities,
+            key_source,
+            security_level,
+            key_id_mode,
+            key_index,
+            frame_counter,
+            last_seen,
+            link_quality,
+            rssi,
+            joined,
+        })
+    }
+
+    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+        self.extended_address.encode_into(buffer);
+        self.short_address.encode_into(buffer);
+        self.capabilities.encode_into(buffer);
+        self.key_source.encode_into(buffer);
+        self.security_level.encode_into(buffer);
+        self.key_id_mode.encode_into(buffer);
+        buffer.put_u8(self.key_index);
+        buffer.put_u32_le(self.frame_counter);
+        let last_seen = self
+            .last_seen
+            .and_then(|x| x.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |x| x.as_secs());
+        buffer.put_u64_le(last_seen);
+        buffer.put_u8(self.link_quality);
+        buffer.put_i8(self.rssi);
+        buffer.put_u8(self.joined as u8);
+    }
+
+    // The admission state of the device, e.g. to keep its address when it
+    // rejoins after a gateway restart.
+    pub fn member(&self) -> Member {
+        Member {
+            short_address: self.short_address,
+            capabilities: self.capabilities,
+            joined: self.joined,
+        }
+    }
+}
+
+// Host side table of the devices associated with the coordinator, kept up to
+// date from the MAC indications and saved across restarts.
+#[derive(Debug, Default, Clone)]
+pub struct Registry {
+    devices: HashMap<ExtendedAddress, DeviceRecord>,
+    // The record before the pending association, restored if it fails.
+    pending: HashMap<ExtendedAddress, Option<DeviceRecord>>,
+}
+
+impl Registry {
+    pub fn new() -> Registry {
+        Registry::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.devices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.devices.is_empty()
+    }
+
+    pub fn get(&self, extended_address: &ExtendedAddress) -> Option<&DeviceRecord> {
+        self.devices.get(extended_address)
+    }
+
+    // Devices without a short address can't be looked up by it.
+    pub fn get_by_short_address(&self, short_address: ShortAddress) -> Option<&DeviceRecord> {
+        if !is_unicast(short_address) {
+            return None;
+        }
+        self.devices
+            .values()
+            .find(|x| x.short_address == short_address)
+    }
+
+    pub fn get_by_address(&self, address: &Address) -> Option<&DeviceRecord> {
+        match address {
+            Address::Addr16Bit(x) => self.get_by_short_address(*x),
+            Address::Addr64Bit(x) => self.get(x),
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &DeviceRecord> {
+        self.devices.values()
+    }
+
+    pub fn insert(&mut self, record: DeviceRecord) -> Option<DeviceRecord> {
+        self.devices.insert(record.extended_address, record)
+    }
+
+    pub fn remove(&mut self, extended_address: &ExtendedAddress) -> Option<DeviceRecord> {
+        self.pending.remove(extended_address);
+        self.devices.remove(extended_address)
+    }
+
+    fn get_by_address_mut(&mut self, address: &Address) -> Option<&mut DeviceRecord> {
+        match address {
+            Address::Addr16Bit(x) if !is_unicast(*x) => None,
+            Address::Addr16Bit(x) => self.devices.values_mut().find(|y| y.short_address == *x),
+            Address::Addr64Bit(x) => self.devices.get_mut(x),
+        }
+    }
+
+    // Records the device admitted by the AssociateRsp, it joins once the
+    // CommStatusInd reports the response as delivered.
+    pub fn associate(&mut self, indication: &AssociateInd, response: &AssociateRsp) {
+        if response.assoc_status != AssociationStatus::Successful {
+            return;
+        }
+
+        let previous = self.devices.get(&indication.extended_address).cloned();
+        let record = DeviceRecord {
+            extended_address: indication.extended_address,
+            short_address: response.assoc_short_address,
+            capabilities: indication.capabilities,
+            key_source: indication.key_source,
+            security_level: indication.security_level,
+            key_id_mode: indication.key_id_mode,
+            key_index: indication.key_index,
+            frame_counter: previous.as_ref().map_or(0, |x| x.frame_counter),
+            last_seen: Some(SystemTime::now()),
+            link_quality: previous.as_ref().map_or(0, |x| x.link_quality),
+            rssi: previous.as_ref().map_or(0, |x| x.rssi),
+            joined: previous.as_ref().is_some_and(|x| x.joined),
+        };
+        self.insert(record);
+        // A repeated AssociateInd keeps the record from before the first one.
+        self.pending
+            .entry(indication.extended_address)
+            .or_insert(previous);
+    }
+
+    pub fn comm_status(&mut self, indication: &CommStatusInd) {
+        let Some(extended_address) = associate_rsp_device(indication) else {
+            return;
+        };
+        let Some(previous) = self.pending.remove(&extended_address) else {
+            return;
+        };
+
+        if indication.status == Status::Success {
+            if let Some(record) = self.devices.get_mut(&extended_address) {
+                record.joined = true;
+            }
+            return;
+        }
+
+        // A device which joined before keeps its previous record.
+        match previous {
+            Some(record) => self.devices.insert(extended_address, record),
+            None => self.devices.remove(&extended_address),
+        };
+    }
+
+    pub fn disassociate(&mut self, indication: &DisassociateInd) -> Option<DeviceRecord> {
+        self.remove(&indication.extended_address)
+    }
+
+    // Returns whether the sender is a known device.
+    pub fn data(&mut self, indication: &DataInd) -> bool {
+        let Some(record) = self.get_by_address_mut(&indication.src_address) else {
+            return false;
+        };
+
+        record.last_seen = Some(SystemTime::now());
+        record.link_quality = indication.link_quality;
+        record.rssi = indication.rssi;
+        if indication.security_level != SecurityLevel::NoSecurity {
+            record.frame_counter = indication.frame_counter;
+        }
+        true
+    }
+
+    // Updates the table from the indications handled by the registry, other
+    // frames are ignored.
+    pub fn update_frame(&mut self, frame: &MTFrame) -> Result<bool, Error> {
+        let command = &frame.header.command;
+        if command.subsystem != MTSubsystem::MAC || command.cmd_type != CommandType::AREQ {
+            return Ok(false);
+        }
+
+        match command.id {
+            CommStatusInd::ID => self.comm_status(&CommStatusInd::try_decode(&frame.payload)?),
+            DisassociateInd::ID => {
+                self.disassociate(&DisassociateInd::try_decode(&frame.payload)?);
+            }
+            DataInd::ID => return Ok(self.data(&DataInd::try_decode(&frame.payload)?)),
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    // A device which lost its coordinator gets its address back, as long as
+    // it joined the network before.
+    pub fn orphan_rsp(&self, indication: &OrphanInd) -> OrphanRsp {
+        let record = self
+            .devices
+            .get(&indication.extended_address)
+            .filter(|x| x.joined);
+
+        OrphanRsp {
+            extended_address: indication.extended_address,
+            assoc_short_address: record.map_or(
+                ShortAddress {
+                    address: NO_SHORT_ADDRESS,
+                },
+                |x| x.short_address,
+            ),
+            associated_member: record.is_some(),
+            key_source: indication.key_source,
+            security_level: indication.security_level,
+            key_id_mode: indication.key_id_mode,
+            key_index: indication.key_index,
+        }
+    }
+
+    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
+        let mut cursor = Cursor::new(buffer);
+        let version = cursor.try_get_u8()?;
+        if version != REGISTRY_VERSION {
+            return Err(Error::InvalidRegistryVersion(version));
+        }
+
+        let mut registry = Registry::new();
+        let count = cursor.try_get_u32_le()?;
+        for _ in 0..count {
+            registry.insert(DeviceRecord::try_decode(&mut cursor)?);
+        }
+
+        match cursor.remaining() {
+            0 => Ok(registry),
+            remaining => Err(Error::TrailingBytes(remaining)),
+        }
+    }
+
+    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+        buffer.put_u8(REGISTRY_VERSION);
+        buffer.put_u32_le(self.devices.len() as u32);
+        for record in self.devices.values() {
+            record.encode_into(buffer);
+        }
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Registry, Error> {
+        Registry::try_decode(&fs::read(path)?)
+    }
+
+    // Written to a temporary file first, so that a crash while saving keeps
+    // the previous registry.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        let mut buffer = Vec::new();
+        self.encode_into(&mut buffer);
+
+        let path = path.as_ref();
+        let mut temporary = path.as_os_str().to_owned();
+        temporary.push(".tmp");
+        fs::write(&temporary, buffer)?;
+        fs::rename(&temporary, path)?;
+        Ok(())
+    }
+}
+
+fn is_unicast(short_address: ShortAddress) -> bool {
+    !matches!(
+        short_address.address,
+        USE_EXTENDED_ADDRESS | NO_SHORT_ADDRESS
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DeviceRecord, Registry};
+    use crate::admission::tests::{associate_ind, comm_status_ind, extended_address};
+    use crate::admission::{Admission, AdmissionConfig, AdmissionHandler};
+    use crate::command::Command;
+    use crate::error::Error;
+    use crate::subsystem::mac::areq::{DataInd, DisassociateInd, OrphanInd};
+    use crate::types::{
+        Address, CapabilityInformation, DisassociateReason, KeyIdMode, KeySource, SecurityLevel,
+        ShortAddress, Status,
+    };
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn record(x: u8, address: u16) -> DeviceRecord {
+        DeviceRecord {
+            extended_address: extended_address(x),
+            short_address: ShortAddress { address },
+            capabilities: CapabilityInformation::RX_ON_IDLE | CapabilityInformation::ALLOC_ADDR,
+            key_source: KeySource { key: [x; 8] },
+            security_level: SecurityLevel::AESEncryptionMIC32,
+            key_id_mode: KeyIdMode::Key1ByteIndex,
+            key_index: 1,
+            frame_counter: 0x1234,
+            last_seen: Some(UNIX_EPOCH + Duration::from_secs(1_700_000_000)),
+            link_quality: 180,
+            rssi: -70,
+            joined: true,
+        }
+    }
+
+    fn orphan_ind(x: u8) -> OrphanInd {
+        OrphanInd {
+            extended_address: extended_address(x),
+            key_source: KeySource { key: [0; 8] },
+            security_level: SecurityLevel::NoSecurity,
+            key_id_mode: KeyIdMode::NotUsed,
+            key_index: 0,
+        }
+    }
+
+    fn data_ind(address: u16) -> DataInd {
+        DataInd {
+            src_address: Address::Addr16Bit(ShortAddress { address }),
+            dest_address: Address::Addr16Bit(ShortAddress { address: 0x0 }),
+            timestamp: 0,
+            timestamp2: 0,
+            src_pan_id: 0x1234,
+            dest_pan_id: 0x1234,
+            link_quality: 0,
+            correlation: 0,
+            rssi: 0,
+            dsn: 0,
+            key_source: KeySource { key: [0; 8] },
+            security_level: SecurityLevel::NoSecurity,
+            key_id_mode: KeyIdMode::NotUsed,
+            key_index: 0,
+            frame_counter: 0,
+            data_length: 0,
+            ie_length: 0,
+            data_payload: Vec::new(),
+            ie_payload: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn track_membership() {
+        let mut admission = Admission::new(AdmissionConfig::new());
+        let mut registry = Registry::new();
+        let indication = associate_ind(
+            1,
+            CapabilityInformation::RX_ON_IDLE | CapabilityInformation::ALLOC_ADDR,
+        );
+        let response = admission.associate(&indication);
+        registry.associate(&indication, &response);
+        assert!(!registry.orphan_rsp(&orphan_ind(1)).associated_member);
+
+        let frame = comm_status_ind(1, Status::Success).into_mt_frame().unwrap();
+        assert!(registry.update_frame(&frame).unwrap());
+
+        let orphan_rsp = registry.orphan_rsp(&orphan_ind(1));
+        assert!(orphan_rsp.associated_member);
+        assert_eq!(orphan_rsp.assoc_short_address.address, 0x1);
+        let orphan_rsp = registry.orphan_rsp(&orphan_ind(2));
+        assert!(!orphan_rsp.associated_member);
+        assert_eq!(orphan_rsp.assoc_short_address.address, 0xffff);
+
+        let frame = DisassociateInd {
+            extended_address: extended_address(1),
+            disassociate_reason: DisassociateReason::DevWishesLeave,
+            key_source: KeySource { key: [0; 8] },
+            security_level: SecurityLevel::NoSecurity,
+            key_id_mode: KeyIdMode::NotUsed,
+            key_index: 0,
+        }
+        .into_mt_frame()
+        .unwrap();
+        assert!(registry.update_frame(&frame).unwrap());
+        assert!(registry.is_empty());
+    }
+
+    #[test]
+    fn failed_rejoin_keeps_record() {
+        let mut admission = Admission::new(AdmissionConfig::new());
+        let mut registry = Registry::new();
+        let mut joined = record(1, 0xfffe);
+        joined.capabilities = CapabilityInformation::RX_ON_IDLE;
+        admission.insert(joined.extended_address, joined.member());
+        registry.insert(joined.clone());
+
+        // Rejoins asking for a short address, the response expires.
+        let indication = associate_ind(
+            1,
+            CapabilityInformation::RX_ON_IDLE | CapabilityInformation::ALLOC_ADDR,
+        );
+        let response = admission.associate(&indication);
+        registry.associate(&indication, &response);
+        assert_eq!(
+            registry.get(&extended_address(1)).unwrap().short_address,
+            response.assoc_short_address
+        );
+
+        let indication = comm_status_ind(1, Status::TransactionExpired);
+        admission.comm_status(&indication);
+        registry.comm_status(&indication);
+        assert_eq!(registry.get(&extended_address(1)), Some(&joined));
+        assert_eq!(
+            admission.get(&extended_address(1)).unwrap().short_address,
+            joined.short_address
+        );
+
+        // A new device is dropped.
+        let indication = associate_ind(2, CapabilityInformation::ALLOC_ADDR);
+        let response = admission.associate(&indication);
+        registry.associate(&indication, &response);
+        registry.comm_status(&comm_status_ind(2, Status::TransactionExpired));
+        assert!(registry.get(&extended_address(2)).is_none());
+    }
+
+    #[test]
+    fn data_from_reserved_short_address() {
+        let mut registry = Registry::new();
+        registry.insert(record(1, 0xfffe));
+        registry.insert(record(2, 0xffff));
+
+        for address in [0xfffe, 0xffff] {
+            let mut indication = data_ind(address);
+            indication.link_quality = 10;
+            assert!(!registry.data(&indication));
+        }
+        assert!(registry.iter().all(|x| x.link_quality == 180));
+
+        let mut indication = data_ind(0xfffe);
+        indication.src_address = Address::Addr64Bit(extended_address(1));
+        assert!(registry.data(&indication));
+        for address in [0xfffe, 0xffff] {
+            assert!(
+                registry
+                    .get_by_address(&Address::Addr16Bit(ShortAddress { address }))
+                    .is_none()
+            );
+        }
+    }
+
+    #[test]
+    fn save_and_restore() {
+        let mut registry = Registry::new();
+        registry.insert(record(1, 0x1));
+        let mut unknown = record(2, 0xfffe);
+        unknown.last_seen = None;
+        unknown.joined = false;
+        registry.insert(unknown);
+
+        let path = std::env::temp_dir().join(format!("ti154-registry-{}", std::process::id()));
+        registry.save(&path).unwrap();
+        let restored = Registry::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(restored.len(), 2);
+        assert_eq!(restored.get(&extended_address(1)), Some(&record(1, 0x1)));
+        assert_eq!(
+            restored.get_by_short_address(ShortAddress { address: 0x1 }),
+            registry.get(&extended_address(1))
+        );
+        assert!(
+            restored
+                .get_by_short_address(ShortAddress { address: 0xfffe })
+                .is_none()
+        );
+
+        // Known devices keep their address after a restart.
+        let mut admission = Admission::new(AdmissionConfig::new());
+        for record in restored.iter() {
+            admission.insert(record.extended_address, record.member());
+        }
+        assert_eq!(admission.len(), 2);
+
+        let mut buffer = Vec::new();
+        registry.encode_into(&mut buffer);
+        assert_eq!(buffer[..5], [0x1, 0x2, 0x0, 0x0, 0x0]);
+        buffer[0] = 0x2;
+        assert!(matches!(
+            Registry::try_decode(&buffer),
+            Err(Error::InvalidRegistryVersion(0x2))
+        ));
+        buffer[0] = 0x1;
+        buffer.push(0x0);
+        assert!(matches!(
+            Registry::try_decode(&buffer),
+            Err(Error::TrailingBytes(1))
+        ));
+    }
+}
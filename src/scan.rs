@@ -0,0 +1,197 @@
+use crate::command::Command;
+use crate::error::Error;
+use crate::frame::MTFrame;
+use crate::subsystem::mac::areq::{BeaconNotifyInd, PanDescriptor, ScanCnf, ScanResults};
+use crate::types::{CommandType, MTSubsystem};
+use std::cmp::Reverse;
+
+// PAN descriptors collected from the beacons heard during an active or
+// passive scan, one per coordinator.
+#[derive(Debug, Default, Clone)]
+pub struct PanCandidates {
+    candidates: Vec<PanDescriptor>,
+}
+
+impl PanCandidates {
+    pub fn new() -> PanCandidates {
+        PanCandidates::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.candidates.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.candidates.is_empty()
+    }
+
+    // A later beacon of the same coordinator replaces the earlier one.
+    pub fn insert(&mut self, descriptor: PanDescriptor) {
+        let existing = self
+            .candidates
+            .iter_mut()
+            .find(|x| x.pan_id == descriptor.pan_id && x.coord_address == descriptor.coord_address);
+
+        match existing {
+            Some(candidate) => *candidate = descriptor,
+            None => self.candidates.push(descriptor),
+        }
+    }
+
+    pub fn insert_beacon(&mut self, beacon: &BeaconNotifyInd) -> bool {
+        match beacon.pan_descriptor() {
+            Some(descriptor) => {
+                self.insert(descriptor);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn insert_scan_cnf(&mut self, scan_cnf: &ScanCnf) {
+        if let ScanResults::PanDescriptors(ref descriptors) = scan_cnf.results {
+            for descriptor in descriptors.iter() {
+                self.insert(descriptor.clone());
+            }
+        }
+    }
+
+    // Takes the beacon notifications out of the indications received during
+    // the scan, other frames are ignored.
+    pub fn insert_frame(&mut self, frame: &MTFrame) -> Result<bool, Error> {
+        let command = &frame.header.command;
+        if command.subsystem != MTSubsystem::MAC
+            || command.cmd_type != CommandType::AREQ
+            || command.id != BeaconNotifyInd::ID
+        {
+            return Ok(false);
+        }
+
+        let beacon = BeaconNotifyInd::try_decode(&frame.payload)?;
+        Ok(self.insert_beacon(&beacon))
+    }
+
+    pub fn retain<F: FnMut(&PanDescriptor) -> bool>(&mut self, f: F) {
+        self.candidates.retain(f);
+    }
+
+    // Coordinators permitting association first, then by link quality.
+    // Beacons which failed the security processing aren't candidates.
+    pub fn ranked(&self) -> Vec<&PanDescriptor> {
+        let mut candidates: Vec<_> = self
+            .candidates
+            .iter()
+            .filter(|x| !x.security_failure)
+            .collect();
+        candidates.sort_by_key(|x| Reverse((x.superframe_spec.association_permit, x.link_quality)));
+        candidates
+    }
+
+    pub fn best(&self) -> Option<&PanDescriptor> {
+        self.ranked().first().copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PanCandidates;
+    use crate::command::Command;
+    use crate::subsystem::mac::areq::{BeaconNotifyInd, PanDescriptor};
+    use crate::subsystem::mac::sreq::AssociateReq;
+    use crate::types::{
+        Address, CapabilityInformation, KeyIdMode, KeySource, PhyId, SecurityLevel, ShortAddress,
+        SuperframeSpec,
+    };
+
+    fn descriptor(address: u16, link_quality: u8, association_permit: bool) -> PanDescriptor {
+        PanDescriptor {
+            coord_address: Address::Addr16Bit(ShortAddress { address }),
+            pan_id: 0x1234,
+            superframe_spec: SuperframeSpec {
+                beacon_order: 15,
+                superframe_order: 15,
+                final_cap_slot: 15,
+                battery_life_extension: false,
+                pan_coordinator: address == 0,
+                association_permit,
+            },
+            logical_channel: 3,
+            channel_page: 9,
+            gts_permit: false,
+            link_quality,
+            timestamp: 0,
+            security_failure: false,
+            key_source: KeySource { key: [0; 8] },
+            security_level: SecurityLevel::NoSecurity,
+            key_id_mode: KeyIdMode::NotUsed,
+            key_index: 0,
+        }
+    }
+
+    #[test]
+    fn rank_candidates() {
+        let mut candidates = PanCandidates::new();
+        candidates.insert(descriptor(0x0, 100, false));
+        candidates.insert(descriptor(0x1, 80, true));
+        candidates.insert(descriptor(0x2, 120, true));
+        candidates.insert(descriptor(0x1, 200, true));
+
+        let mut failed = descriptor(0x3, 255, true);
+        failed.security_failure = true;
+        candidates.insert(failed);
+
+        assert_eq!(candidates.len(), 4);
+        let ranked: Vec<_> = candidates
+            .ranked()
+            .iter()
+            .map(|x| (x.coord_address, x.link_quality))
+            .collect();
+        assert_eq!(
+            ranked,
+            vec![
+                (Address::Addr16Bit(ShortAddress { address: 0x1 }), 200),
+                (Address::Addr16Bit(ShortAddress { address: 0x2 }), 120),
+                (Address::Addr16Bit(ShortAddress { address: 0x0 }), 100),
+            ]
+        );
+
+        let associate_req = AssociateReq::from_pan_descriptor(
+            candidates.best().unwrap(),
+            PhyId::STD_ETSI_863_PHY_3,
+            CapabilityInformation::FFD | CapabilityInformation::ALLOC_ADDR,
+        );
+        assert_eq!(associate_req.phy_id, 0x3);
+        assert_eq!(associate_req.logical_channel, 3);
+        assert_eq!(associate_req.coord_pan_id, 0x1234);
+        assert!(associate_req.validate_channel().is_ok());
+
+        candidates.retain(|x| x.superframe_spec.pan_coordinator);
+        assert_eq!(candidates.len(), 1);
+    }
+
+    #[test]
+    fn collect_beacon_frames() {
+        let payload = [
+            0x0, 0x7, 0x0, 0x0, 0x0, 0x0, 0x2, 0xcd, 0xab, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x34,
+            0x12, 0xff, 0xcf, 0x5, 0x9, 0x0, 0xc8, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+            0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+        ];
+        let frame = BeaconNotifyInd::try_decode(&payload)
+            .unwrap()
+            .into_mt_frame()
+            .unwrap();
+
+        let mut candidates = PanCandidates::new();
+        assert!(candidates.insert_frame(&frame).unwrap());
+
+        let best = candidates.best().unwrap();
+        assert_eq!(
+            best.coord_address,
+            Address::Addr16Bit(ShortAddress { address: 0xabcd })
+        );
+        assert_eq!(best.pan_id, 0x1234);
+        assert_eq!(best.logical_channel, 5);
+        assert_eq!(best.link_quality, 200);
+        assert!(best.superframe_spec.association_permit);
+    }
+}
@@ -1,5 +1,6 @@
+use crate::command::Command;
 use crate::error::Error;
-use crate::frame::{CommandCode, MTFrame, MTHeader};
+use crate::ie::{self, PayloadIE};
 use crate::types::*;
 use bytes::{Buf, BufMut};
 use std::io::Cursor;
@@ -18,18 +19,21 @@ pub struct DataCnf {
     pub frame_counter: u32,
 }
 
-impl DataCnf {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let status = Status::try_decode(&mut cursor)?;
-        let handle = cursor.get_u8();
-        let timestamp = cursor.get_u32_le();
-        let timestamp2 = cursor.get_u16_le();
-        let retries = cursor.get_u8();
-        let link_quality = cursor.get_u8();
-        let correlation = cursor.get_u8();
-        let rssi = cursor.get_u8();
-        let frame_counter = cursor.get_u32_le();
+impl Command for DataCnf {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::AREQ;
+    const ID: u8 = MACCommandId::DataCnf as u8;
+
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let status = Status::try_decode(cursor)?;
+        let handle = cursor.try_get_u8()?;
+        let timestamp = cursor.try_get_u32_le()?;
+        let timestamp2 = cursor.try_get_u16_le()?;
+        let retries = cursor.try_get_u8()?;
+        let link_quality = cursor.try_get_u8()?;
+        let correlation = cursor.try_get_u8()?;
+        let rssi = cursor.try_get_u8()?;
+        let frame_counter = cursor.try_get_u32_le()?;
 
         Ok(DataCnf {
             status,
@@ -44,13 +48,7 @@ impl DataCnf {
         })
     }
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
-    }
-
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.status.encode_into(buffer);
         buffer.put_u8(self.handle);
         buffer.put_u32_le(self.timestamp);
@@ -61,22 +59,6 @@ impl DataCnf {
         buffer.put_u8(self.rssi);
         buffer.put_u32_le(self.frame_counter);
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x10,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::AREQ,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::DataCnf as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -103,25 +85,34 @@ pub struct DataInd {
 }
 
 impl DataInd {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let src_address = Address::try_decode(&mut cursor)?;
-        let dest_address = Address::try_decode(&mut cursor)?;
-        let timestamp = cursor.get_u32_le();
-        let timestamp2 = cursor.get_u16_le();
-        let src_pan_id = cursor.get_u16_le();
-        let dest_pan_id = cursor.get_u16_le();
-        let link_quality = cursor.get_u8();
-        let correlation = cursor.get_u8();
-        let rssi = cursor.get_i8();
-        let dsn = cursor.get_u8();
-        let key_source = KeySource::try_decode(&mut cursor)?;
-        let security_level = SecurityLevel::try_decode(&mut cursor)?;
-        let key_id_mode = KeyIdMode::try_decode(&mut cursor)?;
-        let key_index = cursor.get_u8();
-        let frame_counter = cursor.get_u32_le();
-        let data_length = cursor.get_u16_le();
-        let ie_length = cursor.get_u16_le();
+    pub fn payload_ies(&self) -> Result<Vec<PayloadIE>, Error> {
+        ie::decode_payload_ies(&self.ie_payload)
+    }
+}
+
+impl Command for DataInd {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::AREQ;
+    const ID: u8 = MACCommandId::DataInd as u8;
+
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let src_address = Address::try_decode(cursor)?;
+        let dest_address = Address::try_decode(cursor)?;
+        let timestamp = cursor.try_get_u32_le()?;
+        let timestamp2 = cursor.try_get_u16_le()?;
+        let src_pan_id = cursor.try_get_u16_le()?;
+        let dest_pan_id = cursor.try_get_u16_le()?;
+        let link_quality = cursor.try_get_u8()?;
+        let correlation = cursor.try_get_u8()?;
+        let rssi = cursor.try_get_i8()?;
+        let dsn = cursor.try_get_u8()?;
+        let key_source = KeySource::try_decode(cursor)?;
+        let security_level = SecurityLevel::try_decode(cursor)?;
+        let key_id_mode = KeyIdMode::try_decode(cursor)?;
+        let key_index = cursor.try_get_u8()?;
+        let frame_counter = cursor.try_get_u32_le()?;
+        let data_length = cursor.try_get_u16_le()?;
+        let ie_length = cursor.try_get_u16_le()?;
 
         let mut data_payload = vec![0x00; data_length as usize];
         cursor
@@ -156,13 +147,7 @@ impl DataInd {
         })
     }
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
-    }
-
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.src_address.encode_into(buffer);
         self.dest_address.encode_into(buffer);
         buffer.put_u32_le(self.timestamp);
@@ -183,22 +168,6 @@ impl DataInd {
         buffer.extend(self.data_payload.iter());
         buffer.extend(self.ie_payload.iter());
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: (0x33 + self.data_payload.len() + self.ie_payload.len()) as u8,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::AREQ,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::DataInd as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -207,40 +176,21 @@ pub struct PurgeCnf {
     pub handle: u8,
 }
 
-impl PurgeCnf {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let status = Status::try_decode(&mut cursor)?;
-        let handle = cursor.get_u8();
-        Ok(PurgeCnf { status, handle })
-    }
+impl Command for PurgeCnf {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::AREQ;
+    const ID: u8 = MACCommandId::PurgeCnf as u8;
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let status = Status::try_decode(cursor)?;
+        let handle = cursor.try_get_u8()?;
+        Ok(PurgeCnf { status, handle })
     }
 
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.status.encode_into(buffer);
         buffer.put_u8(self.handle);
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x02,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::AREQ,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::PurgeCnf as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -268,33 +218,42 @@ pub struct WSAsyncInd {
 }
 
 impl WSAsyncInd {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let src_address = Address::try_decode(&mut cursor)?;
-        let dest_address = Address::try_decode(&mut cursor)?;
-        let timestamp = cursor.get_u32_le();
-        let timestamp2 = cursor.get_u16_le();
-        let src_pan_id = cursor.get_u16_le();
-        let dest_pan_id = cursor.get_u16_le();
-        let link_quality = cursor.get_u8();
-        let correlation = cursor.get_u8();
-        let rssi = cursor.get_u8();
-        let dsn = cursor.get_u8();
-        let key_source = KeySource::try_decode(&mut cursor)?;
-        let security_level = SecurityLevel::try_decode(&mut cursor)?;
-        let key_id_mode = KeyIdMode::try_decode(&mut cursor)?;
-        let key_index = cursor.get_u8();
-        let frame_counter = cursor.get_u32_le();
-        let frame_type = WiSUNAsyncFrameType::try_decode(&mut cursor)?;
-        let data_length = cursor.get_u16_le();
-        let ie_length = cursor.get_u16_le();
+    pub fn payload_ies(&self) -> Result<Vec<PayloadIE>, Error> {
+        ie::decode_payload_ies(&self.ie_payload)
+    }
+}
+
+impl Command for WSAsyncInd {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::AREQ;
+    const ID: u8 = MACCommandId::WSAsyncInd as u8;
+
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let src_address = Address::try_decode(cursor)?;
+        let dest_address = Address::try_decode(cursor)?;
+        let timestamp = cursor.try_get_u32_le()?;
+        let timestamp2 = cursor.try_get_u16_le()?;
+        let src_pan_id = cursor.try_get_u16_le()?;
+        let dest_pan_id = cursor.try_get_u16_le()?;
+        let link_quality = cursor.try_get_u8()?;
+        let correlation = cursor.try_get_u8()?;
+        let rssi = cursor.try_get_u8()?;
+        let dsn = cursor.try_get_u8()?;
+        let key_source = KeySource::try_decode(cursor)?;
+        let security_level = SecurityLevel::try_decode(cursor)?;
+        let key_id_mode = KeyIdMode::try_decode(cursor)?;
+        let key_index = cursor.try_get_u8()?;
+        let frame_counter = cursor.try_get_u32_le()?;
+        let frame_type = WiSUNAsyncFrameType::try_decode(cursor)?;
+        let data_length = cursor.try_get_u16_le()?;
+        let ie_length = cursor.try_get_u16_le()?;
 
         let mut data_payload = vec![0x00; data_length as usize];
         cursor
             .read_exact(&mut data_payload)
             .map_err(|_| Error::NotEnoughBytes)?;
 
-        let mut ie_payload = vec![0x00; data_length as usize];
+        let mut ie_payload = vec![0x00; ie_length as usize];
         cursor
             .read_exact(&mut ie_payload)
             .map_err(|_| Error::NotEnoughBytes)?;
@@ -323,13 +282,7 @@ impl WSAsyncInd {
         })
     }
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
-    }
-
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.src_address.encode_into(buffer);
         self.dest_address.encode_into(buffer);
         buffer.put_u32_le(self.timestamp);
@@ -351,22 +304,6 @@ impl WSAsyncInd {
         buffer.extend(self.data_payload.iter());
         buffer.extend(self.ie_payload.iter());
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: (0x34 + self.data_payload.len() + self.ie_payload.len()) as u8,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::AREQ,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::WSAsyncInd as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -382,18 +319,21 @@ pub struct SyncLossInd {
     pub key_index: u8,
 }
 
-impl SyncLossInd {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let status = Status::try_decode(&mut cursor)?;
-        let pan_id = cursor.get_u16_le();
-        let logical_channel = cursor.get_u8();
-        let channel_page = cursor.get_u8();
-        let phy_id = PhyId::try_decode(&mut cursor)?;
-        let key_source = KeySource::try_decode(&mut cursor)?;
-        let security_level = SecurityLevel::try_decode(&mut cursor)?;
-        let key_id_mode = KeyIdMode::try_decode(&mut cursor)?;
-        let key_index = cursor.get_u8();
+impl Command for SyncLossInd {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::AREQ;
+    const ID: u8 = MACCommandId::SyncLossInd as u8;
+
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let status = Status::try_decode(cursor)?;
+        let pan_id = cursor.try_get_u16_le()?;
+        let logical_channel = cursor.try_get_u8()?;
+        let channel_page = cursor.try_get_u8()?;
+        let phy_id = PhyId::try_decode(cursor)?;
+        let key_source = KeySource::try_decode(cursor)?;
+        let security_level = SecurityLevel::try_decode(cursor)?;
+        let key_id_mode = KeyIdMode::try_decode(cursor)?;
+        let key_index = cursor.try_get_u8()?;
 
         Ok(SyncLossInd {
             status,
@@ -408,13 +348,7 @@ impl SyncLossInd {
         })
     }
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
-    }
-
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.status.encode_into(buffer);
         buffer.put_u16_le(self.pan_id);
         buffer.put_u8(self.logical_channel);
@@ -425,43 +359,30 @@ impl SyncLossInd {
         self.key_id_mode.encode_into(buffer);
         buffer.put_u8(self.key_index);
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x11,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::AREQ,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::SyncLossInd as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
 
 #[derive(Debug, Clone)]
 pub struct AssociateInd {
     pub extended_address: ExtendedAddress,
-    pub capabilities: u8,
+    pub capabilities: CapabilityInformation,
     pub key_source: KeySource,
     pub security_level: SecurityLevel,
     pub key_id_mode: KeyIdMode,
     pub key_index: u8,
 }
 
-impl AssociateInd {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let extended_address = ExtendedAddress::try_decode(&mut cursor)?;
-        let capabilities = cursor.get_u8();
-        let key_source = KeySource::try_decode(&mut cursor)?;
-        let security_level = SecurityLevel::try_decode(&mut cursor)?;
-        let key_id_mode = KeyIdMode::try_decode(&mut cursor)?;
-        let key_index = cursor.get_u8();
+impl Command for AssociateInd {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::AREQ;
+    const ID: u8 = MACCommandId::AssociateInd as u8;
+
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let extended_address = ExtendedAddress::try_decode(cursor)?;
+        let capabilities = CapabilityInformation::try_decode(cursor)?;
+        let key_source = KeySource::try_decode(cursor)?;
+        let security_level = SecurityLevel::try_decode(cursor)?;
+        let key_id_mode = KeyIdMode::try_decode(cursor)?;
+        let key_index = cursor.try_get_u8()?;
 
         Ok(AssociateInd {
             extended_address,
@@ -473,41 +394,19 @@ impl AssociateInd {
         })
     }
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
-    }
-
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.extended_address.encode_into(buffer);
-        buffer.put_u8(self.capabilities);
+        self.capabilities.encode_into(buffer);
         self.key_source.encode_into(buffer);
         self.security_level.encode_into(buffer);
         self.key_id_mode.encode_into(buffer);
         buffer.put_u8(self.key_index);
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x14,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::AREQ,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::AssociateInd as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
 
 #[derive(Debug, Clone)]
 pub struct AssociateCnf {
-    pub status: Status,
+    pub status: AssociateCnfStatus,
     pub short_address: ShortAddress,
     pub key_source: KeySource,
     pub security_level: SecurityLevel,
@@ -515,15 +414,18 @@ pub struct AssociateCnf {
     pub key_index: u8,
 }
 
-impl AssociateCnf {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let status = Status::try_decode(&mut cursor)?;
-        let short_address = ShortAddress::try_decode(&mut cursor)?;
-        let key_source = KeySource::try_decode(&mut cursor)?;
-        let security_level = SecurityLevel::try_decode(&mut cursor)?;
-        let key_id_mode = KeyIdMode::try_decode(&mut cursor)?;
-        let key_index = cursor.get_u8();
+impl Command for AssociateCnf {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::AREQ;
+    const ID: u8 = MACCommandId::AssociateCnf as u8;
+
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let status = AssociateCnfStatus::try_decode(cursor)?;
+        let short_address = ShortAddress::try_decode(cursor)?;
+        let key_source = KeySource::try_decode(cursor)?;
+        let security_level = SecurityLevel::try_decode(cursor)?;
+        let key_id_mode = KeyIdMode::try_decode(cursor)?;
+        let key_index = cursor.try_get_u8()?;
 
         Ok(AssociateCnf {
             status,
@@ -535,13 +437,7 @@ impl AssociateCnf {
         })
     }
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
-    }
-
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.status.encode_into(buffer);
         self.short_address.encode_into(buffer);
         self.key_source.encode_into(buffer);
@@ -549,22 +445,6 @@ impl AssociateCnf {
         self.key_id_mode.encode_into(buffer);
         buffer.put_u8(self.key_index);
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x0e,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::AREQ,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::AssociateCnf as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -574,28 +454,35 @@ pub enum BeaconNotifyInd {
 }
 
 impl BeaconNotifyInd {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
+    // Enhanced beacons don't carry the coordinator address.
+    pub fn pan_descriptor(&self) -> Option<PanDescriptor> {
+        match self {
+            BeaconNotifyInd::StandardFrame(frame) => Some(frame.pan_descriptor()),
+            BeaconNotifyInd::EnhancedFrame(_) => None,
+        }
+    }
+}
+
+impl Command for BeaconNotifyInd {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::AREQ;
+    const ID: u8 = MACCommandId::BeaconNotifyInd as u8;
+
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
         use BeaconNotifyInd::{EnhancedFrame, StandardFrame};
 
-        let beacon_type = cursor.get_u8();
+        let beacon_type = cursor.try_get_u8()?;
 
         let beacon_frame = match beacon_type {
-            0 => StandardFrame(StandardBeaconFrame::try_decode(&mut cursor)?),
-            1 => EnhancedFrame(EnhancedBeaconFrame::try_decode(&mut cursor)?),
+            0 => StandardFrame(StandardBeaconFrame::try_decode(cursor)?),
+            1 => EnhancedFrame(EnhancedBeaconFrame::try_decode(cursor)?),
             _ => return Err(Error::InvalidBeaconType(beacon_type)),
         };
 
         Ok(beacon_frame)
     }
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
-    }
-
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         match self {
             BeaconNotifyInd::StandardFrame(frame) => {
                 buffer.put_u8(0);
@@ -607,30 +494,6 @@ impl BeaconNotifyInd {
             }
         }
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        use BeaconNotifyInd::*;
-        let length = match self {
-            StandardFrame(ref frame) => {
-                0x26 + (2 * frame.short_addr_list.len() + 8 * frame.ext_addr_list.len()) as u8
-                    + frame.sdu_length
-            }
-            EnhancedFrame(_) => 0x0a,
-        };
-        MTFrame {
-            header: MTHeader {
-                length,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::AREQ,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::BeaconNotifyInd as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -640,7 +503,7 @@ pub struct StandardBeaconFrame {
     pub coord_address_mode: AddressMode,
     pub coord_extended_address: ExtendedAddress,
     pub pan_id: u16,
-    pub superframe_spec: u16,
+    pub superframe_spec: SuperframeSpec,
     pub logical_channel: u8,
     pub channel_page: u8,
     pub gts_permit: bool,
@@ -660,24 +523,24 @@ pub struct StandardBeaconFrame {
 
 impl StandardBeaconFrame {
     pub fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
-        let bsn = cursor.get_u8();
-        let timestamp = cursor.get_u32_le();
+        let bsn = cursor.try_get_u8()?;
+        let timestamp = cursor.try_get_u32_le()?;
         let coord_address_mode = AddressMode::try_decode(cursor)?;
         let coord_extended_address = ExtendedAddress::try_decode(cursor)?;
-        let pan_id = cursor.get_u16_le();
-        let superframe_spec = cursor.get_u16_le();
-        let logical_channel = cursor.get_u8();
-        let channel_page = cursor.get_u8();
-        let gts_permit = cursor.get_u8() != 0;
-        let link_quality = cursor.get_u8();
-        let security_failure = cursor.get_u8() != 0;
+        let pan_id = cursor.try_get_u16_le()?;
+        let superframe_spec = SuperframeSpec::try_decode(cursor)?;
+        let logical_channel = cursor.try_get_u8()?;
+        let channel_page = cursor.try_get_u8()?;
+        let gts_permit = cursor.try_get_u8()? != 0;
+        let link_quality = cursor.try_get_u8()?;
+        let security_failure = cursor.try_get_u8()? != 0;
         let key_source = KeySource::try_decode(cursor)?;
         let security_level = SecurityLevel::try_decode(cursor)?;
         let key_id_mode = KeyIdMode::try_decode(cursor)?;
-        let key_index = cursor.get_u8();
-        let short_addrs = cursor.get_u8();
-        let ext_addrs = cursor.get_u8();
-        let sdu_length = cursor.get_u8();
+        let key_index = cursor.try_get_u8()?;
+        let short_addrs = cursor.try_get_u8()?;
+        let ext_addrs = cursor.try_get_u8()?;
+        let sdu_length = cursor.try_get_u8()?;
 
         let mut short_addr_list = Vec::new();
         for _ in 0..short_addrs {
@@ -719,6 +582,38 @@ impl StandardBeaconFrame {
         })
     }
 
+    // A 16 bit coordinator address is sent in the first two bytes of the
+    // extended address field.
+    pub fn coord_address(&self) -> Address {
+        match self.coord_address_mode {
+            AddressMode::Addr16Bit => {
+                let address = &self.coord_extended_address.address;
+                Address::Addr16Bit(ShortAddress {
+                    address: u16::from_le_bytes([address[7], address[6]]),
+                })
+            }
+            AddressMode::Addr64Bit => Address::Addr64Bit(self.coord_extended_address),
+        }
+    }
+
+    pub fn pan_descriptor(&self) -> PanDescriptor {
+        PanDescriptor {
+            coord_address: self.coord_address(),
+            pan_id: self.pan_id,
+            superframe_spec: self.superframe_spec,
+            logical_channel: self.logical_channel,
+            channel_page: self.channel_page,
+            gts_permit: self.gts_permit,
+            link_quality: self.link_quality,
+            timestamp: self.timestamp,
+            security_failure: self.security_failure,
+            key_source: self.key_source,
+            security_level: self.security_level,
+            key_id_mode: self.key_id_mode,
+            key_index: self.key_index,
+        }
+    }
+
     pub fn encode(&self) -> Vec<u8> {
         let mut buffer = Vec::new();
         self.encode_into(&mut buffer);
@@ -731,7 +626,7 @@ impl StandardBeaconFrame {
         self.coord_address_mode.encode_into(buffer);
         self.coord_extended_address.encode_into(buffer);
         buffer.put_u16_le(self.pan_id);
-        buffer.put_u16_le(self.superframe_spec);
+        self.superframe_spec.encode_into(buffer);
         buffer.put_u8(self.logical_channel);
         buffer.put_u8(self.channel_page);
         buffer.put_u8(if self.gts_permit { 1 } else { 0 });
@@ -771,14 +666,14 @@ pub struct EnhancedBeaconFrame {
 
 impl EnhancedBeaconFrame {
     pub fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
-        let bsn = cursor.get_u8();
-        let beacon_order = cursor.get_u8();
-        let super_frame_order = cursor.get_u8();
-        let final_cap_slot = cursor.get_u8();
-        let enh_beacon_order = cursor.get_u8();
-        let ofs_time_slot = cursor.get_u8();
-        let cap_back_off = cursor.get_u8();
-        let non_beacon_order = cursor.get_u16_le();
+        let bsn = cursor.try_get_u8()?;
+        let beacon_order = cursor.try_get_u8()?;
+        let super_frame_order = cursor.try_get_u8()?;
+        let final_cap_slot = cursor.try_get_u8()?;
+        let enh_beacon_order = cursor.try_get_u8()?;
+        let ofs_time_slot = cursor.try_get_u8()?;
+        let cap_back_off = cursor.try_get_u8()?;
+        let non_beacon_order = cursor.try_get_u16_le()?;
         Ok(EnhancedBeaconFrame {
             bsn,
             beacon_order,
@@ -819,15 +714,18 @@ pub struct DisassociateInd {
     pub key_index: u8,
 }
 
-impl DisassociateInd {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let extended_address = ExtendedAddress::try_decode(&mut cursor)?;
-        let disassociate_reason = DisassociateReason::try_decode(&mut cursor)?;
-        let key_source = KeySource::try_decode(&mut cursor)?;
-        let security_level = SecurityLevel::try_decode(&mut cursor)?;
-        let key_id_mode = KeyIdMode::try_decode(&mut cursor)?;
-        let key_index = cursor.get_u8();
+impl Command for DisassociateInd {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::AREQ;
+    const ID: u8 = MACCommandId::DisassociateInd as u8;
+
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let extended_address = ExtendedAddress::try_decode(cursor)?;
+        let disassociate_reason = DisassociateReason::try_decode(cursor)?;
+        let key_source = KeySource::try_decode(cursor)?;
+        let security_level = SecurityLevel::try_decode(cursor)?;
+        let key_id_mode = KeyIdMode::try_decode(cursor)?;
+        let key_index = cursor.try_get_u8()?;
 
         Ok(DisassociateInd {
             extended_address,
@@ -839,13 +737,7 @@ impl DisassociateInd {
         })
     }
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
-    }
-
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.extended_address.encode_into(buffer);
         self.disassociate_reason.encode_into(buffer);
         self.key_source.encode_into(buffer);
@@ -853,22 +745,6 @@ impl DisassociateInd {
         self.key_id_mode.encode_into(buffer);
         buffer.put_u8(self.key_index);
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x14,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::AREQ,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::DisassociateInd as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -878,12 +754,15 @@ pub struct DisassociateCnf {
     pub device_pan_id: u16,
 }
 
-impl DisassociateCnf {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let status = Status::try_decode(&mut cursor)?;
-        let device_addr = Address::try_decode(&mut cursor)?;
-        let device_pan_id = cursor.get_u16_le();
+impl Command for DisassociateCnf {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::AREQ;
+    const ID: u8 = MACCommandId::DisassociateCnf as u8;
+
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let status = Status::try_decode(cursor)?;
+        let device_addr = Address::try_decode(cursor)?;
+        let device_pan_id = cursor.try_get_u16_le()?;
 
         Ok(DisassociateCnf {
             status,
@@ -892,33 +771,11 @@ impl DisassociateCnf {
         })
     }
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
-    }
-
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.status.encode_into(buffer);
         self.device_addr.encode_into(buffer);
         buffer.put_u16_le(self.device_pan_id);
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x0c,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::AREQ,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::DisassociateCnf as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -930,14 +787,17 @@ pub struct OrphanInd {
     pub key_index: u8,
 }
 
-impl OrphanInd {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let extended_address = ExtendedAddress::try_decode(&mut cursor)?;
-        let key_source = KeySource::try_decode(&mut cursor)?;
-        let security_level = SecurityLevel::try_decode(&mut cursor)?;
-        let key_id_mode = KeyIdMode::try_decode(&mut cursor)?;
-        let key_index = cursor.get_u8();
+impl Command for OrphanInd {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::AREQ;
+    const ID: u8 = MACCommandId::OrphanInd as u8;
+
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let extended_address = ExtendedAddress::try_decode(cursor)?;
+        let key_source = KeySource::try_decode(cursor)?;
+        let security_level = SecurityLevel::try_decode(cursor)?;
+        let key_id_mode = KeyIdMode::try_decode(cursor)?;
+        let key_index = cursor.try_get_u8()?;
         Ok(OrphanInd {
             extended_address,
             key_source,
@@ -947,35 +807,13 @@ impl OrphanInd {
         })
     }
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
-    }
-
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.extended_address.encode_into(buffer);
         self.key_source.encode_into(buffer);
         self.security_level.encode_into(buffer);
         self.key_id_mode.encode_into(buffer);
         buffer.put_u8(self.key_index);
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x13,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::AREQ,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::OrphanInd as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -984,43 +822,24 @@ pub struct PollCnf {
     pub frame_pending: bool,
 }
 
-impl PollCnf {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let status = Status::try_decode(&mut cursor)?;
-        let frame_pending = cursor.get_u8() != 0;
+impl Command for PollCnf {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::AREQ;
+    const ID: u8 = MACCommandId::PollCnf as u8;
+
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let status = Status::try_decode(cursor)?;
+        let frame_pending = cursor.try_get_u8()? != 0;
         Ok(PollCnf {
             status,
             frame_pending,
         })
     }
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
-    }
-
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.status.encode_into(buffer);
         buffer.put_u8(if self.frame_pending { 1 } else { 0 });
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x02,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::AREQ,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::PollCnf as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -1030,12 +849,15 @@ pub struct PollInd {
     pub no_response: bool,
 }
 
-impl PollInd {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let dev_addr = Address::try_decode(&mut cursor)?;
-        let pan_id = cursor.get_u16_le();
-        let no_response = cursor.get_u8() != 0;
+impl Command for PollInd {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::AREQ;
+    const ID: u8 = MACCommandId::PollInd as u8;
+
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let dev_addr = Address::try_decode(cursor)?;
+        let pan_id = cursor.try_get_u16_le()?;
+        let no_response = cursor.try_get_u8()? != 0;
         Ok(PollInd {
             dev_addr,
             pan_id,
@@ -1043,31 +865,124 @@ impl PollInd {
         })
     }
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
+        self.dev_addr.encode_into(buffer);
+        buffer.put_u16_le(self.pan_id);
+        buffer.put_u8(if self.no_response { 1 } else { 0 });
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct PanDescriptor {
+    pub coord_address: Address,
+    pub pan_id: u16,
+    pub superframe_spec: SuperframeSpec,
+    pub logical_channel: u8,
+    pub channel_page: u8,
+    pub gts_permit: bool,
+    pub link_quality: u8,
+    pub timestamp: u32,
+    pub security_failure: bool,
+    pub key_source: KeySource,
+    pub security_level: SecurityLevel,
+    pub key_id_mode: KeyIdMode,
+    pub key_index: u8,
+}
+
+impl PanDescriptor {
+    pub fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        Ok(PanDescriptor {
+            coord_address: Address::try_decode(cursor)?,
+            pan_id: cursor.try_get_u16_le()?,
+            superframe_spec: SuperframeSpec::try_decode(cursor)?,
+            logical_channel: cursor.try_get_u8()?,
+            channel_page: cursor.try_get_u8()?,
+            gts_permit: cursor.try_get_u8()? != 0,
+            link_quality: cursor.try_get_u8()?,
+            timestamp: cursor.try_get_u32_le()?,
+            security_failure: cursor.try_get_u8()? != 0,
+            key_source: KeySource::try_decode(cursor)?,
+            security_level: SecurityLevel::try_decode(cursor)?,
+            key_id_mode: KeyIdMode::try_decode(cursor)?,
+            key_index: cursor.try_get_u8()?,
+        })
     }
 
     pub fn encode_into(&self, buffer: &mut Vec<u8>) {
-        self.dev_addr.encode_into(buffer);
+        self.coord_address.encode_into(buffer);
         buffer.put_u16_le(self.pan_id);
-        buffer.put_u8(if self.no_response { 1 } else { 0 });
+        self.superframe_spec.encode_into(buffer);
+        buffer.put_u8(self.logical_channel);
+        buffer.put_u8(self.channel_page);
+        buffer.put_u8(if self.gts_permit { 1 } else { 0 });
+        buffer.put_u8(self.link_quality);
+        buffer.put_u32_le(self.timestamp);
+        buffer.put_u8(if self.security_failure { 1 } else { 0 });
+        self.key_source.encode_into(buffer);
+        self.security_level.encode_into(buffer);
+        self.key_id_mode.encode_into(buffer);
+        buffer.put_u8(self.key_index);
     }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ScanResults {
+    // One energy value per scanned channel, in channel order, see
+    // ScanCnf::energy_readings.
+    EnergyDetect(Vec<u8>),
+    PanDescriptors(Vec<PanDescriptor>),
+    Orphan,
+}
 
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x0c,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::AREQ,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::PollInd as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
+impl ScanResults {
+    pub fn try_decode(
+        scan_type: ScanType,
+        count: u8,
+        cursor: &mut Cursor<&[u8]>,
+    ) -> Result<Self, Error> {
+        let results = match scan_type {
+            ScanType::EnergyDetect => {
+                let mut energies = vec![0x00; count as usize];
+                cursor
+                    .read_exact(&mut energies)
+                    .map_err(|_| Error::NotEnoughBytes)?;
+                ScanResults::EnergyDetect(energies)
+            }
+            ScanType::Active | ScanType::Passive | ScanType::Active2 => {
+                let mut descriptors = Vec::new();
+                for _ in 0..count {
+                    descriptors.push(PanDescriptor::try_decode(cursor)?);
+                }
+                ScanResults::PanDescriptors(descriptors)
+            }
+            ScanType::Orphan => ScanResults::Orphan,
+        };
+
+        Ok(results)
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            ScanResults::EnergyDetect(x) => x.len(),
+            ScanResults::PanDescriptors(x) => x.len(),
+            ScanResults::Orphan => 0,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+        buffer.put_u8(self.len() as u8);
+        match self {
+            ScanResults::EnergyDetect(energies) => buffer.extend(energies.iter()),
+            ScanResults::PanDescriptors(descriptors) => {
+                for descriptor in descriptors.iter() {
+                    descriptor.encode_into(buffer);
+                }
+            }
+            ScanResults::Orphan => {}
         }
     }
 }
@@ -1079,24 +994,44 @@ pub struct ScanCnf {
     pub channel_page: u8,
     pub phy_id: PhyId,
     pub unscanned_channels: ChannelsBitMap,
-    pub result_list_count: u8,
-    pub result_list: Vec<u8>,
+    pub results: ScanResults,
 }
 
 impl ScanCnf {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let status = Status::try_decode(&mut cursor)?;
-        let scan_type = ScanType::try_decode(&mut cursor)?;
-        let channel_page = cursor.get_u8();
-        let phy_id = PhyId::try_decode(&mut cursor)?;
-        let unscanned_channels = ChannelsBitMap::try_decode(&mut cursor)?;
-        let result_list_count = cursor.get_u8();
-
-        let mut result_list = Vec::new();
-        cursor
-            .read_to_end(&mut result_list)
-            .map_err(|_| Error::NotEnoughBytes)?;
+    // The confirm doesn't repeat the channels of the ScanReq, energies are
+    // matched against the requested channels which were actually scanned.
+    pub fn energy_readings(&self, scan_channels: &ChannelsBitMap) -> Result<Vec<(u8, u8)>, Error> {
+        let energies = match self.results {
+            ScanResults::EnergyDetect(ref energies) => energies,
+            _ => return Ok(Vec::new()),
+        };
+
+        // A count mismatch means the channels don't belong to this confirm.
+        let channels: Vec<u8> = scan_channels
+            .difference(&self.unscanned_channels)
+            .iter()
+            .collect();
+        if channels.len() != energies.len() {
+            return Err(Error::InvalidResultListCount(energies.len()));
+        }
+
+        Ok(channels.into_iter().zip(energies.iter().copied()).collect())
+    }
+}
+
+impl Command for ScanCnf {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::AREQ;
+    const ID: u8 = MACCommandId::ScanCnf as u8;
+
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let status = Status::try_decode(cursor)?;
+        let scan_type = ScanType::try_decode(cursor)?;
+        let channel_page = cursor.try_get_u8()?;
+        let phy_id = PhyId::try_decode(cursor)?;
+        let unscanned_channels = ChannelsBitMap::try_decode(cursor)?;
+        let result_list_count = cursor.try_get_u8()?;
+        let results = ScanResults::try_decode(scan_type, result_list_count, cursor)?;
 
         Ok(ScanCnf {
             status,
@@ -1104,41 +1039,17 @@ impl ScanCnf {
             channel_page,
             phy_id,
             unscanned_channels,
-            result_list_count,
-            result_list,
+            results,
         })
     }
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
-    }
-
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.status.encode_into(buffer);
         self.scan_type.encode_into(buffer);
         buffer.put_u8(self.channel_page);
         self.phy_id.encode_into(buffer);
         self.unscanned_channels.encode_into(buffer);
-        buffer.put_u8(self.result_list_count);
-        buffer.extend(self.result_list.iter());
-    }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x0c + self.result_list.len() as u8,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::AREQ,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::ScanCnf as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
+        self.results.encode_into(buffer);
     }
 }
 
@@ -1155,18 +1066,21 @@ pub struct CommStatusInd {
     pub key_index: u8,
 }
 
-impl CommStatusInd {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let status = Status::try_decode(&mut cursor)?;
-        let src_addr = Address::try_decode(&mut cursor)?;
-        let dst_addr = Address::try_decode(&mut cursor)?;
-        let device_pan_id = cursor.get_u16_le();
-        let reason = CommEventReason::try_decode(&mut cursor)?;
-        let key_source = KeySource::try_decode(&mut cursor)?;
-        let security_level = SecurityLevel::try_decode(&mut cursor)?;
-        let key_id_mode = KeyIdMode::try_decode(&mut cursor)?;
-        let key_index = cursor.get_u8();
+impl Command for CommStatusInd {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::AREQ;
+    const ID: u8 = MACCommandId::CommStatusInd as u8;
+
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let status = Status::try_decode(cursor)?;
+        let src_addr = Address::try_decode(cursor)?;
+        let dst_addr = Address::try_decode(cursor)?;
+        let device_pan_id = cursor.try_get_u16_le()?;
+        let reason = CommEventReason::try_decode(cursor)?;
+        let key_source = KeySource::try_decode(cursor)?;
+        let security_level = SecurityLevel::try_decode(cursor)?;
+        let key_id_mode = KeyIdMode::try_decode(cursor)?;
+        let key_index = cursor.try_get_u8()?;
 
         Ok(CommStatusInd {
             status,
@@ -1181,13 +1095,7 @@ impl CommStatusInd {
         })
     }
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
-    }
-
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.status.encode_into(buffer);
         self.src_addr.encode_into(buffer);
         self.dst_addr.encode_into(buffer);
@@ -1198,22 +1106,6 @@ impl CommStatusInd {
         self.key_id_mode.encode_into(buffer);
         buffer.put_u8(self.key_index);
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x21,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::AREQ,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::CommStatusInd as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -1221,38 +1113,19 @@ pub struct StartCnf {
     pub status: Status,
 }
 
-impl StartCnf {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let status = Status::try_decode(&mut cursor)?;
-        Ok(StartCnf { status })
-    }
+impl Command for StartCnf {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::AREQ;
+    const ID: u8 = MACCommandId::StartCnf as u8;
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let status = Status::try_decode(cursor)?;
+        Ok(StartCnf { status })
     }
 
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.status.encode_into(buffer);
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x01,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::AREQ,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::StartCnf as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -1260,36 +1133,17 @@ pub struct WSAsyncCnf {
     pub status: Status,
 }
 
-impl WSAsyncCnf {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let status = Status::try_decode(&mut cursor)?;
-        Ok(WSAsyncCnf { status })
-    }
+impl Command for WSAsyncCnf {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::AREQ;
+    const ID: u8 = MACCommandId::WSAsyncCnf as u8;
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let status = Status::try_decode(cursor)?;
+        Ok(WSAsyncCnf { status })
     }
 
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.status.encode_into(buffer);
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x01,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::AREQ,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::WSAsyncCnf as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
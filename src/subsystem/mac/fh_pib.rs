@@ -0,0 +1,235 @@
+use crate::error::Error;
+use crate::subsystem::mac::pib::{Attribute, PIBValue, check_status};
+use crate::subsystem::mac::{sreq, srsp};
+use crate::types::{self, ChannelFunction, ChannelsBitMap, FHPIBAttributeId};
+use bytes::Buf;
+use std::io::Cursor;
+
+const NET_NAME_SIZE: usize = 32;
+
+// The Wi-SUN network name, sent zero padded to 32 bytes.
+#[derive(Debug, PartialEq, Clone)]
+pub struct NetworkName(String);
+
+impl NetworkName {
+    pub fn new(name: &str) -> Result<Self, Error> {
+        if name.len() > NET_NAME_SIZE || name.contains('\0') {
+            return Err(Error::InvalidNetworkName);
+        }
+
+        Ok(NetworkName(name.to_string()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl PIBValue for NetworkName {
+    fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let data = <[u8; NET_NAME_SIZE]>::try_decode(cursor)?;
+        let length = data.iter().position(|x| *x == 0).unwrap_or(NET_NAME_SIZE);
+
+        match std::str::from_utf8(&data[..length]) {
+            Ok(name) => Ok(NetworkName(name.to_string())),
+            Err(_) => Err(Error::InvalidNetworkName),
+        }
+    }
+
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
+        buffer.extend(self.0.as_bytes());
+        buffer.resize(buffer.len() + NET_NAME_SIZE - self.0.len(), 0);
+    }
+}
+
+macro_rules! fh_pib_attributes {
+    ($($name:ident($value:ty),)*) => {
+        $(
+            #[derive(Debug, PartialEq, Clone)]
+            pub struct $name(pub $value);
+
+            impl Attribute for $name {
+                type GetReq = sreq::FHGetReq;
+                type SetReq = sreq::FHSetReq;
+
+                fn get_req() -> sreq::FHGetReq {
+                    sreq::FHGetReq {
+                        attribute_id: FHPIBAttributeId::$name,
+                    }
+                }
+
+                fn set_req(&self) -> Result<sreq::FHSetReq, Error> {
+                    FHPIBValue::$name(self.0.clone()).into_set_req()
+                }
+
+                fn from_get_rsp(response: srsp::FHGetReq) -> Result<Self, Error> {
+                    match FHPIBValue::from_get_rsp(FHPIBAttributeId::$name, &response)? {
+                        FHPIBValue::$name(value) => Ok($name(value)),
+                        _ => unreachable!(),
+                    }
+                }
+
+                fn check_set_rsp(response: srsp::FHSetReq) -> Result<(), Error> {
+                    check_status(response.status)
+                }
+            }
+        )*
+
+        #[derive(Debug, PartialEq, Clone)]
+        pub enum FHPIBValue {
+            $($name($value),)*
+        }
+
+        impl FHPIBValue {
+            pub fn attribute_id(&self) -> FHPIBAttributeId {
+                match self {
+                    $(FHPIBValue::$name(_) => FHPIBAttributeId::$name,)*
+                }
+            }
+
+            // Every FH PIB attribute has a fixed size, anything left over
+            // means the value doesn't belong to the attribute.
+            pub fn try_decode(
+                attribute_id: FHPIBAttributeId,
+                cursor: &mut Cursor<&[u8]>,
+            ) -> Result<Self, Error> {
+                let value = match attribute_id {
+                    $(FHPIBAttributeId::$name => {
+                        FHPIBValue::$name(PIBValue::try_decode(cursor)?)
+                    })*
+                };
+
+                match cursor.remaining() {
+                    0 => Ok(value),
+                    count => Err(Error::TrailingBytes(count)),
+                }
+            }
+
+            pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+                match self {
+                    $(FHPIBValue::$name(x) => x.encode_into(buffer),)*
+                }
+            }
+        }
+    };
+}
+
+fh_pib_attributes! {
+    TrackParentEUI(types::ExtendedAddress),
+    BCInterval(u32),
+    UCExcludedChannels(ChannelsBitMap),
+    BCExcludedChannels(ChannelsBitMap),
+    UCDwellInterval(u8),
+    BCDwellInterval(u8),
+    ClockDrift(u8),
+    TimingAccuracy(u8),
+    UCChannelFunction(ChannelFunction),
+    BCChannelFunction(ChannelFunction),
+    UseParentBSIE(bool),
+    BrocastSchedId(u16),
+    UCFixedChannel(u16),
+    BCFixedChannel(u16),
+    PANSize(u16),
+    RoutingCost(u8),
+    RoutingMethod(u8),
+    EAPOLReady(bool),
+    FANTPSVersion(u8),
+    NetName(NetworkName),
+    PANVersion(u16),
+    GTK0Hash([u8; 8]),
+    GTK1Hash([u8; 8]),
+    GTK2Hash([u8; 8]),
+    GTK3Hash([u8; 8]),
+    NeighborValidTime(u16),
+}
+
+impl FHPIBValue {
+    // Read only attributes are refused by the CoP with FHAPIReadOnlyPIB.
+    pub fn into_set_req(self) -> Result<sreq::FHSetReq, Error> {
+        let attribute_id = self.attribute_id();
+        let mut data = Vec::new();
+        self.encode_into(&mut data);
+        Ok(sreq::FHSetReq { attribute_id, data })
+    }
+
+    pub fn from_get_rsp(
+        attribute_id: FHPIBAttributeId,
+        response: &srsp::FHGetReq,
+    ) -> Result<Self, Error> {
+        check_status(response.status)?;
+        let mut cursor = Cursor::new(response.data.as_slice());
+        FHPIBValue::try_decode(attribute_id, &mut cursor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BCInterval, FHPIBValue, GTK0Hash, NetName, NetworkName, UCChannelFunction};
+    use crate::command::Command;
+    use crate::error::Error;
+    use crate::subsystem::mac::pib::Attribute;
+    use crate::subsystem::mac::srsp;
+    use crate::types::{ChannelFunction, FHPIBAttributeId};
+
+    #[test]
+    fn set_req_encodes_value() {
+        let set_req = UCChannelFunction(ChannelFunction::DH1CF).set_req().unwrap();
+        assert_eq!(set_req.encode(), [0x08, 0x20, 0x02]);
+
+        let name = NetworkName::new("wisun").unwrap();
+        let set_req = NetName(name).set_req().unwrap();
+        assert_eq!(set_req.data.len(), 32);
+        assert_eq!(set_req.data[..6], *b"wisun\0");
+
+        assert!(matches!(
+            NetworkName::new(&"x".repeat(33)),
+            Err(Error::InvalidNetworkName)
+        ));
+
+        // The broadcast schedule of a coordinator is writable.
+        let set_req = BCInterval(1000).set_req().unwrap();
+        assert_eq!(set_req.encode(), [0x01, 0x20, 0xe8, 0x3, 0x0, 0x0]);
+    }
+
+    #[test]
+    fn get_rsp_decodes_value() {
+        let response = srsp::FHGetReq::try_decode(&[0x0, 0xe8, 0x3, 0x0, 0x0]).unwrap();
+        assert_eq!(
+            BCInterval::from_get_rsp(response).unwrap(),
+            BCInterval(1000)
+        );
+
+        let mut buffer = vec![0x0];
+        buffer.extend(b"wisun");
+        buffer.resize(33, 0);
+        let response = srsp::FHGetReq::try_decode(&buffer).unwrap();
+        assert_eq!(NetName::from_get_rsp(response).unwrap().0.as_str(), "wisun");
+
+        let response = srsp::FHGetReq::try_decode(&[0x0, 1, 2, 3, 4, 5, 6, 7, 8]).unwrap();
+        assert_eq!(
+            FHPIBValue::from_get_rsp(FHPIBAttributeId::GTK0Hash, &response).unwrap(),
+            FHPIBValue::GTK0Hash([1, 2, 3, 4, 5, 6, 7, 8])
+        );
+        assert_eq!(
+            GTK0Hash::from_get_rsp(response).unwrap(),
+            GTK0Hash([1, 2, 3, 4, 5, 6, 7, 8])
+        );
+
+        // Wrong sizes and values are rejected.
+        let response = srsp::FHGetReq::try_decode(&[0x0, 0x2, 0x0]).unwrap();
+        assert!(matches!(
+            UCChannelFunction::from_get_rsp(response),
+            Err(Error::TrailingBytes(1))
+        ));
+        let response = srsp::FHGetReq::try_decode(&[0x0, 0x7]).unwrap();
+        assert!(matches!(
+            UCChannelFunction::from_get_rsp(response),
+            Err(Error::InvalidChannelFunction(0x7))
+        ));
+        let response = srsp::FHGetReq::try_decode(&[0x0, 0xe8, 0x3]).unwrap();
+        assert!(matches!(
+            BCInterval::from_get_rsp(response),
+            Err(Error::NotEnoughBytes)
+        ));
+    }
+}
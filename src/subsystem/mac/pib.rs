@@ -0,0 +1,421 @@
+use crate::command::Request;
+use crate::error::Error;
+use crate::subsystem::mac::{sreq, srsp};
+use crate::types::{self, MACPIBAttributeId, Status};
+use bytes::{Buf, BufMut};
+use std::io::Cursor;
+
+const MAC_PIB_VALUE_SIZE: usize = 16;
+
+// Encoding of the value types found in the PIB attribute tables.
+pub trait PIBValue: Sized {
+    fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error>;
+
+    fn encode_into(&self, buffer: &mut Vec<u8>);
+}
+
+impl PIBValue for bool {
+    fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        Ok(cursor.try_get_u8()? != 0)
+    }
+
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
+        buffer.put_u8(*self as u8);
+    }
+}
+
+impl PIBValue for u8 {
+    fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        Ok(cursor.try_get_u8()?)
+    }
+
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
+        buffer.put_u8(*self);
+    }
+}
+
+impl PIBValue for i8 {
+    fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        Ok(cursor.try_get_i8()?)
+    }
+
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
+        buffer.put_i8(*self);
+    }
+}
+
+impl PIBValue for u16 {
+    fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        Ok(cursor.try_get_u16_le()?)
+    }
+
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
+        buffer.put_u16_le(*self);
+    }
+}
+
+impl PIBValue for u32 {
+    fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        Ok(cursor.try_get_u32_le()?)
+    }
+
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
+        buffer.put_u32_le(*self);
+    }
+}
+
+impl PIBValue for types::ExtendedAddress {
+    fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        types::ExtendedAddress::try_decode(cursor)
+    }
+
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
+        types::ExtendedAddress::encode_into(self, buffer)
+    }
+}
+
+impl PIBValue for types::ChannelsBitMap {
+    fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        types::ChannelsBitMap::try_decode(cursor)
+    }
+
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
+        types::ChannelsBitMap::encode_into(self, buffer)
+    }
+}
+
+impl PIBValue for types::ChannelFunction {
+    fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        types::ChannelFunction::try_decode(cursor)
+    }
+
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
+        types::ChannelFunction::encode_into(self, buffer)
+    }
+}
+
+impl PIBValue for types::SecurityLevel {
+    fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        types::SecurityLevel::try_decode(cursor)
+    }
+
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
+        types::SecurityLevel::encode_into(self, buffer)
+    }
+}
+
+impl PIBValue for types::KeyIdMode {
+    fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        types::KeyIdMode::try_decode(cursor)
+    }
+
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
+        types::KeyIdMode::encode_into(self, buffer)
+    }
+}
+
+impl PIBValue for types::KeySource {
+    fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        types::KeySource::try_decode(cursor)
+    }
+
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
+        types::KeySource::encode_into(self, buffer)
+    }
+}
+
+impl PIBValue for types::MACFrameType {
+    fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        types::MACFrameType::try_decode(cursor)
+    }
+
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
+        types::MACFrameType::encode_into(self, buffer)
+    }
+}
+
+// Attributes which are only written to trigger an action, e.g. KeyTable.
+impl PIBValue for () {
+    fn try_decode(_cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        Ok(())
+    }
+
+    fn encode_into(&self, _buffer: &mut Vec<u8>) {}
+}
+
+// Fixed size arrays are kept in over the air order, e.g. GTK hashes.
+impl<const N: usize> PIBValue for [u8; N] {
+    fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let mut value = [0; N];
+        cursor.try_copy_to_slice(&mut value)?;
+        Ok(value)
+    }
+
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
+        buffer.extend(self.iter());
+    }
+}
+
+// Variable length values take the rest of the buffer, i.e. the whole 16
+// bytes value of a GetReq. See BeaconPayload::truncate for the padding.
+impl PIBValue for Vec<u8> {
+    fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        Ok(cursor.chunk().to_vec())
+    }
+
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
+        buffer.extend(self.iter());
+    }
+}
+
+// A PIB attribute which can be read and written with a pair of requests,
+// e.g. MAC_GET_REQ and MAC_SET_REQ for the MAC PIB.
+pub trait Attribute: Sized {
+    type GetReq: Request;
+    type SetReq: Request;
+
+    fn get_req() -> Self::GetReq;
+
+    fn set_req(&self) -> Result<Self::SetReq, Error>;
+
+    fn from_get_rsp(response: <Self::GetReq as Request>::Response) -> Result<Self, Error>;
+
+    fn check_set_rsp(response: <Self::SetReq as Request>::Response) -> Result<(), Error>;
+}
+
+pub fn check_status(status: Status) -> Result<(), Error> {
+    match status {
+        Status::Success => Ok(()),
+        status => Err(Error::Status(status)),
+    }
+}
+
+// MAC_GET_REQ and MAC_SET_REQ carry values in a 16 bytes field, which the
+// GetReq and SetReq commands keep byte-reversed.
+fn to_raw_value(mut data: Vec<u8>) -> Result<[u8; MAC_PIB_VALUE_SIZE], Error> {
+    if data.len() > MAC_PIB_VALUE_SIZE {
+        return Err(Error::PayloadTooLong(data.len()));
+    }
+
+    data.resize(MAC_PIB_VALUE_SIZE, 0);
+    data.reverse();
+
+    let mut raw = [0; MAC_PIB_VALUE_SIZE];
+    raw.copy_from_slice(&data);
+    Ok(raw)
+}
+
+fn from_raw_value(raw: &[u8; MAC_PIB_VALUE_SIZE]) -> [u8; MAC_PIB_VALUE_SIZE] {
+    let mut data = *raw;
+    data.reverse();
+    data
+}
+
+macro_rules! mac_pib_attributes {
+    ($($name:ident($value:ty),)*) => {
+        $(
+            #[derive(Debug, PartialEq, Clone)]
+            pub struct $name(pub $value);
+
+            impl Attribute for $name {
+                type GetReq = sreq::GetReq;
+                type SetReq = sreq::SetReq;
+
+                fn get_req() -> sreq::GetReq {
+                    sreq::GetReq {
+                        attribute_id: MACPIBAttributeId::$name,
+                    }
+                }
+
+                fn set_req(&self) -> Result<sreq::SetReq, Error> {
+                    MACPIBValue::$name(self.0.clone()).into_set_req()
+                }
+
+                fn from_get_rsp(response: srsp::GetReq) -> Result<Self, Error> {
+                    match MACPIBValue::from_get_rsp(MACPIBAttributeId::$name, &response)? {
+                        MACPIBValue::$name(value) => Ok($name(value)),
+                        _ => unreachable!(),
+                    }
+                }
+
+                fn check_set_rsp(response: srsp::SetReq) -> Result<(), Error> {
+                    check_status(response.status)
+                }
+            }
+        )*
+
+        #[derive(Debug, PartialEq, Clone)]
+        pub enum MACPIBValue {
+            $($name($value),)*
+        }
+
+        impl MACPIBValue {
+            pub fn attribute_id(&self) -> MACPIBAttributeId {
+                match self {
+                    $(MACPIBValue::$name(_) => MACPIBAttributeId::$name,)*
+                }
+            }
+
+            pub fn try_decode(
+                attribute_id: MACPIBAttributeId,
+                cursor: &mut Cursor<&[u8]>,
+            ) -> Result<Self, Error> {
+                match attribute_id {
+                    $(MACPIBAttributeId::$name => {
+                        Ok(MACPIBValue::$name(PIBValue::try_decode(cursor)?))
+                    })*
+                }
+            }
+
+            pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+                match self {
+                    $(MACPIBValue::$name(x) => x.encode_into(buffer),)*
+                }
+            }
+        }
+    };
+}
+
+mac_pib_attributes! {
+    AckWaitDuration(u8),
+    AssociationPermit(bool),
+    AutoRequest(bool),
+    BattLifeExt(bool),
+    BattLeftExtPeriods(u8),
+    BeaconPayload(Vec<u8>),
+    BeaconPayloadLength(u8),
+    BeaconOrder(u8),
+    BeaconTxTime(u32),
+    BSN(u8),
+    CoordExtendedAddress(types::ExtendedAddress),
+    CoordShortAddress(u16),
+    DSN(u8),
+    GTSPermit(bool),
+    MaxCSMABackoffs(u8),
+    MinBE(u8),
+    PANId(u16),
+    PromiscuousMode(bool),
+    RxOnWhenIdle(bool),
+    ShortAddress(u16),
+    SuperframeOrder(u8),
+    TransactionPersistenceTime(u16),
+    AssociatedPANCoord(bool),
+    MaxBE(u8),
+    FrameTotalWaitTime(u16),
+    MaxFrameRetries(u8),
+    ResponseWaitTime(u8),
+    SyncSymbolOffset(u8),
+    TimestampSupported(bool),
+    SecurityEnabled(bool),
+    EBSN(u8),
+    EBeaconOrder(u8),
+    EBeaconOrderNBPAN(u16),
+    OffsetTimeslot(u8),
+    IncludeMPMIE(bool),
+    PhyFSKPreambleLen(u8),
+    PhyMRFSKSFD(u8),
+    PhyTransmitPowerSigned(i8),
+    LogicalChannel(u8),
+    ExtendedAddress(types::ExtendedAddress),
+    AltBE(u8),
+    DeviceBeaconOrder(u8),
+    RF4CEPowerSavings(u8),
+    FrameVersionSupport(u8),
+    ChannelPage(u8),
+    PhyCurrentDescriptorId(u8),
+    FCSType(bool),
+}
+
+impl BeaconPayload {
+    // The GetReq value is zero padded, the payload length is a separate
+    // attribute.
+    pub fn truncate(mut self, length: &BeaconPayloadLength) -> BeaconPayload {
+        self.0.truncate(length.0 as usize);
+        self
+    }
+}
+
+impl MACPIBValue {
+    pub fn into_set_req(self) -> Result<sreq::SetReq, Error> {
+        let mut data = Vec::new();
+        self.encode_into(&mut data);
+        Ok(sreq::SetReq {
+            attribute_id: self.attribute_id(),
+            attribute_value: to_raw_value(data)?,
+        })
+    }
+
+    pub fn from_get_rsp(
+        attribute_id: MACPIBAttributeId,
+        response: &srsp::GetReq,
+    ) -> Result<Self, Error> {
+        check_status(response.status)?;
+        let data = from_raw_value(&response.data);
+        let mut cursor = Cursor::new(&data[..]);
+        MACPIBValue::try_decode(attribute_id, &mut cursor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Attribute, BeaconPayload, BeaconPayloadLength, ExtendedAddress, MACPIBValue, PANId,
+    };
+    use crate::command::Command;
+    use crate::error::Error;
+    use crate::subsystem::mac::srsp;
+    use crate::types::{self, MACPIBAttributeId, Status};
+
+    #[test]
+    fn set_req_encodes_value() {
+        let set_req = PANId(0x1234).set_req().unwrap();
+        assert_eq!(set_req.attribute_id, MACPIBAttributeId::PANId);
+
+        let mut expected = vec![0x50, 0x34, 0x12];
+        expected.extend([0x0; 14]);
+        assert_eq!(set_req.encode(), expected);
+
+        let address = ExtendedAddress(types::ExtendedAddress {
+            address: [1, 2, 3, 4, 5, 6, 7, 8],
+        });
+        let set_req = address.set_req().unwrap();
+        assert_eq!(set_req.encode()[..9], [0xe2, 8, 7, 6, 5, 4, 3, 2, 1]);
+
+        assert!(matches!(
+            BeaconPayload(vec![0; 17]).set_req(),
+            Err(Error::PayloadTooLong(17))
+        ));
+    }
+
+    #[test]
+    fn get_rsp_decodes_value() {
+        let mut buffer = vec![0x0, 0xcd, 0xab];
+        buffer.extend([0x0; 14]);
+        let response = srsp::GetReq::try_decode(&buffer).unwrap();
+
+        assert_eq!(
+            PANId::from_get_rsp(response.clone()).unwrap(),
+            PANId(0xabcd)
+        );
+        assert_eq!(
+            MACPIBValue::from_get_rsp(MACPIBAttributeId::MaxBE, &response).unwrap(),
+            MACPIBValue::MaxBE(0xcd)
+        );
+
+        let payload = BeaconPayload::from_get_rsp(response.clone()).unwrap();
+        assert_eq!(payload.0.len(), 16);
+        assert_eq!(
+            payload.truncate(&BeaconPayloadLength(2)),
+            BeaconPayload(vec![0xcd, 0xab])
+        );
+
+        let response = srsp::GetReq {
+            status: Status::UnsupportedAttribute,
+            ..response
+        };
+        assert!(matches!(
+            PANId::from_get_rsp(response),
+            Err(Error::Status(Status::UnsupportedAttribute))
+        ));
+    }
+}
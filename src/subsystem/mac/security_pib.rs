@@ -0,0 +1,648 @@
+use crate::error::Error;
+use crate::subsystem::mac::pib::{Attribute, PIBValue, check_status};
+use crate::subsystem::mac::{sreq, srsp};
+use crate::types::{
+    ExtendedAddress, KeyIdMode, KeySource, MACFrameType, SecurityLevel, SecurityPIBAttributeId,
+    Status,
+};
+use bytes::{Buf, BufMut};
+use std::io::Cursor;
+
+const KEY_ID_LOOKUP_DATA_SIZE: usize = 9;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct KeyIdLookupEntry {
+    // Either 5 or 9 bytes.
+    pub lookup_data: Vec<u8>,
+}
+
+impl PIBValue for KeyIdLookupEntry {
+    fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let data = <[u8; KEY_ID_LOOKUP_DATA_SIZE]>::try_decode(cursor)?;
+        let lookup_data = match cursor.try_get_u8()? {
+            0x00 => data[..5].to_vec(),
+            0x01 => data.to_vec(),
+            size => return Err(Error::InvalidKeyIdLookupSize(size)),
+        };
+
+        Ok(KeyIdLookupEntry { lookup_data })
+    }
+
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
+        let mut data = self.lookup_data.clone();
+        data.resize(KEY_ID_LOOKUP_DATA_SIZE, 0);
+        buffer.extend(data.iter());
+        buffer.put_u8((self.lookup_data.len() > 5) as u8);
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct KeyIdDeviceEntry {
+    pub device_handle: u16,
+    pub unique_device: bool,
+    pub blacklisted: bool,
+}
+
+impl PIBValue for KeyIdDeviceEntry {
+    fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        Ok(KeyIdDeviceEntry {
+            device_handle: cursor.try_get_u16_le()?,
+            unique_device: bool::try_decode(cursor)?,
+            blacklisted: bool::try_decode(cursor)?,
+        })
+    }
+
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
+        buffer.put_u16_le(self.device_handle);
+        self.unique_device.encode_into(buffer);
+        self.blacklisted.encode_into(buffer);
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct KeyIdUsageEntry {
+    pub frame_type: MACFrameType,
+    pub command_frame_id: u8,
+}
+
+impl PIBValue for KeyIdUsageEntry {
+    fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        Ok(KeyIdUsageEntry {
+            frame_type: MACFrameType::try_decode(cursor)?,
+            command_frame_id: cursor.try_get_u8()?,
+        })
+    }
+
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
+        self.frame_type.encode_into(buffer);
+        buffer.put_u8(self.command_frame_id);
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct KeyEntry {
+    pub key: [u8; 16],
+    pub frame_counter: u32,
+}
+
+impl PIBValue for KeyEntry {
+    fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        Ok(KeyEntry {
+            key: PIBValue::try_decode(cursor)?,
+            frame_counter: cursor.try_get_u32_le()?,
+        })
+    }
+
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
+        self.key.encode_into(buffer);
+        buffer.put_u32_le(self.frame_counter);
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct DeviceFrameCounter {
+    pub frame_counter: u32,
+    pub key_index: u16,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct DeviceEntry {
+    pub pan_id: u16,
+    pub short_address: u16,
+    pub ext_address: ExtendedAddress,
+    pub exempt: bool,
+    pub frame_counters: Vec<DeviceFrameCounter>,
+}
+
+impl PIBValue for DeviceEntry {
+    fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let pan_id = cursor.try_get_u16_le()?;
+        let short_address = cursor.try_get_u16_le()?;
+        let ext_address = ExtendedAddress::try_decode(cursor)?;
+        let exempt = bool::try_decode(cursor)?;
+
+        // One frame counter per key used by the device, up to the end.
+        let mut frame_counters = Vec::new();
+        while cursor.remaining() >= 6 {
+            frame_counters.push(DeviceFrameCounter {
+                frame_counter: cursor.try_get_u32_le()?,
+                key_index: cursor.try_get_u16_le()?,
+            });
+        }
+
+        Ok(DeviceEntry {
+            pan_id,
+            short_address,
+            ext_address,
+            exempt,
+            frame_counters,
+        })
+    }
+
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
+        buffer.put_u16_le(self.pan_id);
+        buffer.put_u16_le(self.short_address);
+        self.ext_address.encode_into(buffer);
+        self.exempt.encode_into(buffer);
+        for x in self.frame_counters.iter() {
+            buffer.put_u32_le(x.frame_counter);
+            buffer.put_u16_le(x.key_index);
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct SecurityLevelEntry {
+    pub frame_type: MACFrameType,
+    pub command_frame_id: u8,
+    pub security_minimum: SecurityLevel,
+    pub security_override: bool,
+}
+
+impl PIBValue for SecurityLevelEntry {
+    fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        Ok(SecurityLevelEntry {
+            frame_type: MACFrameType::try_decode(cursor)?,
+            command_frame_id: cursor.try_get_u8()?,
+            security_minimum: SecurityLevel::try_decode(cursor)?,
+            security_override: bool::try_decode(cursor)?,
+        })
+    }
+
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
+        self.frame_type.encode_into(buffer);
+        buffer.put_u8(self.command_frame_id);
+        self.security_minimum.encode_into(buffer);
+        self.security_override.encode_into(buffer);
+    }
+}
+
+// A record of one of the security tables, addressed by index1 and index2,
+// see the guide for the meaning of the indexes of each entry.
+pub trait TableEntry: Sized {
+    fn get_req(index1: u16, index2: u16) -> sreq::SecurityGetReq;
+
+    fn set_req(&self, index1: u16, index2: u16) -> Result<sreq::SecuritySetReq, Error>;
+
+    fn from_get_rsp(response: srsp::SecurityGetReq) -> Result<Self, Error>;
+
+    fn check_set_rsp(response: srsp::SecuritySetReq) -> Result<(), Error> {
+        check_status(response.status)
+    }
+}
+
+macro_rules! security_pib_attributes {
+    (
+        attributes { $($name:ident($value:ty),)* }
+        entries { $($entry:ident,)* }
+    ) => {
+        $(
+            #[derive(Debug, PartialEq, Clone)]
+            pub struct $name(pub $value);
+
+            impl Attribute for $name {
+                type GetReq = sreq::SecurityGetReq;
+                type SetReq = sreq::SecuritySetReq;
+
+                fn get_req() -> sreq::SecurityGetReq {
+                    sreq::SecurityGetReq {
+                        attribute_id: SecurityPIBAttributeId::$name,
+                        index1: 0,
+                        index2: 0,
+                    }
+                }
+
+                fn set_req(&self) -> Result<sreq::SecuritySetReq, Error> {
+                    SecurityPIBValue::$name(self.0.clone()).into_set_req(0, 0)
+                }
+
+                fn from_get_rsp(response: srsp::SecurityGetReq) -> Result<Self, Error> {
+                    let attribute_id = SecurityPIBAttributeId::$name;
+                    match SecurityPIBValue::from_get_rsp(attribute_id, &response)? {
+                        SecurityPIBValue::$name(value) => Ok($name(value)),
+                        _ => unreachable!(),
+                    }
+                }
+
+                fn check_set_rsp(response: srsp::SecuritySetReq) -> Result<(), Error> {
+                    check_status(response.status)
+                }
+            }
+        )*
+
+        $(
+            impl TableEntry for $entry {
+                fn get_req(index1: u16, index2: u16) -> sreq::SecurityGetReq {
+                    sreq::SecurityGetReq {
+                        attribute_id: SecurityPIBAttributeId::$entry,
+                        index1,
+                        index2,
+                    }
+                }
+
+                fn set_req(
+                    &self,
+                    index1: u16,
+                    index2: u16,
+                ) -> Result<sreq::SecuritySetReq, Error> {
+                    SecurityPIBValue::$entry(self.clone()).into_set_req(index1, index2)
+                }
+
+                fn from_get_rsp(response: srsp::SecurityGetReq) -> Result<Self, Error> {
+                    let attribute_id = SecurityPIBAttributeId::$entry;
+                    match SecurityPIBValue::from_get_rsp(attribute_id, &response)? {
+                        SecurityPIBValue::$entry(value) => Ok(value),
+                        _ => unreachable!(),
+                    }
+                }
+            }
+        )*
+
+        #[derive(Debug, PartialEq, Clone)]
+        pub enum SecurityPIBValue {
+            $($name($value),)*
+            $($entry($entry),)*
+        }
+
+        impl SecurityPIBValue {
+            pub fn attribute_id(&self) -> SecurityPIBAttributeId {
+                match self {
+                    $(SecurityPIBValue::$name(_) => SecurityPIBAttributeId::$name,)*
+                    $(SecurityPIBValue::$entry(_) => SecurityPIBAttributeId::$entry,)*
+                }
+            }
+
+            pub fn try_decode(
+                attribute_id: SecurityPIBAttributeId,
+                cursor: &mut Cursor<&[u8]>,
+            ) -> Result<Self, Error> {
+                let value = match attribute_id {
+                    $(SecurityPIBAttributeId::$name => {
+                        SecurityPIBValue::$name(PIBValue::try_decode(cursor)?)
+                    })*
+                    $(SecurityPIBAttributeId::$entry => {
+                        SecurityPIBValue::$entry(PIBValue::try_decode(cursor)?)
+                    })*
+                };
+
+                match cursor.remaining() {
+                    0 => Ok(value),
+                    count => Err(Error::TrailingBytes(count)),
+                }
+            }
+
+            pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+                match self {
+                    $(SecurityPIBValue::$name(x) => x.encode_into(buffer),)*
+                    $(SecurityPIBValue::$entry(x) => x.encode_into(buffer),)*
+                }
+            }
+        }
+    };
+}
+
+security_pib_attributes! {
+    attributes {
+        KeyTable(()),
+        KeyTableEntries(u8),
+        DeviceTableEntries(u8),
+        SecurityLevelTableEntries(u8),
+        FrameCounter(u32),
+        AutoRequestSecurityLevel(SecurityLevel),
+        AutoRequestKeyIdMode(KeyIdMode),
+        AutoRequestKeySource(KeySource),
+        AutoRequestKeyIndex(u8),
+        DefaultKeySource(KeySource),
+        PANCoordExtendedAddress(ExtendedAddress),
+        PANCoordShortAddress(u16),
+    }
+    entries {
+        KeyIdLookupEntry,
+        KeyIdDeviceEntry,
+        KeyIdUsageEntry,
+        KeyEntry,
+        DeviceEntry,
+        SecurityLevelEntry,
+    }
+}
+
+impl SecurityPIBValue {
+    pub fn into_set_req(self, index1: u16, index2: u16) -> Result<sreq::SecuritySetReq, Error> {
+        if let SecurityPIBValue::KeyIdLookupEntry(ref entry) = self
+            && !matches!(entry.lookup_data.len(), 5 | KEY_ID_LOOKUP_DATA_SIZE)
+        {
+            return Err(Error::InvalidKeyIdLookupSize(entry.lookup_data.len() as u8));
+        }
+
+        let mut attribute_value = Vec::new();
+        self.encode_into(&mut attribute_value);
+        Ok(sreq::SecuritySetReq {
+            attribute_id: self.attribute_id(),
+            index1,
+            index2,
+            attribute_value,
+        })
+    }
+
+    pub fn from_get_rsp(
+        attribute_id: SecurityPIBAttributeId,
+        response: &srsp::SecurityGetReq,
+    ) -> Result<Self, Error> {
+        check_status(response.status)?;
+        let mut cursor = Cursor::new(response.data.as_slice());
+        SecurityPIBValue::try_decode(attribute_id, &mut cursor)
+    }
+}
+
+// A key and the lists hanging off its key descriptor.
+#[derive(Debug, PartialEq, Clone)]
+pub struct KeyDescriptor {
+    pub key: KeyEntry,
+    pub lookup_list: Vec<KeyIdLookupEntry>,
+    pub device_list: Vec<KeyIdDeviceEntry>,
+    pub usage_list: Vec<KeyIdUsageEntry>,
+}
+
+// The security database of the CoP, see SecurityTablesReader.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct SecurityTables {
+    pub keys: Vec<KeyDescriptor>,
+    pub devices: Vec<DeviceEntry>,
+    pub security_levels: Vec<SecurityLevelEntry>,
+}
+
+// The lookup and usage lists of a key descriptor have no entry count
+// attribute, they are read until the CoP refuses the index.
+const MAX_KEY_LIST_LENGTH: u16 = 256;
+
+fn is_end_of_list(error: &Error) -> bool {
+    matches!(
+        error,
+        Error::Status(Status::InvalidIndex) | Error::Status(Status::InvalidParameter)
+    )
+}
+
+// Appends the entry of the response, false at the end of the list.
+fn read_list_entry<E: TableEntry>(
+    response: srsp::SecurityGetReq,
+    list: &mut Vec<E>,
+) -> Result<bool, Error> {
+    match E::from_get_rsp(response) {
+        Ok(entry) => {
+            list.push(entry);
+            Ok(true)
+        }
+        Err(e) if is_end_of_list(&e) => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+enum TableStep {
+    KeyTableEntries,
+    DeviceTableEntries,
+    SecurityLevelTableEntries,
+    Key(u16),
+    KeyIdLookup(u16, u16),
+    KeyIdDevice(u16, u16),
+    KeyIdUsage(u16, u16),
+    Device(u16),
+    SecurityLevel(u16),
+    Done,
+}
+
+// Reads the key, device and security level tables of the CoP one
+// SecurityGetReq at a time, the blocking and async clients send the requests.
+#[derive(Debug, Clone)]
+pub struct SecurityTablesReader {
+    step: TableStep,
+    key_count: u16,
+    device_count: u16,
+    security_level_count: u16,
+    tables: SecurityTables,
+}
+
+impl SecurityTablesReader {
+    pub fn new() -> SecurityTablesReader {
+        SecurityTablesReader {
+            step: TableStep::KeyTableEntries,
+            key_count: 0,
+            device_count: 0,
+            security_level_count: 0,
+            tables: SecurityTables::default(),
+        }
+    }
+
+    // None once the tables are read.
+    pub fn next_request(&self) -> Option<sreq::SecurityGetReq> {
+        match self.step {
+            TableStep::KeyTableEntries => Some(KeyTableEntries::get_req()),
+            TableStep::DeviceTableEntries => Some(DeviceTableEntries::get_req()),
+            TableStep::SecurityLevelTableEntries => Some(SecurityLevelTableEntries::get_req()),
+            TableStep::Key(key) => Some(KeyEntry::get_req(key, 0)),
+            TableStep::KeyIdLookup(key, index) => Some(KeyIdLookupEntry::get_req(key, index)),
+            TableStep::KeyIdDevice(key, index) => Some(KeyIdDeviceEntry::get_req(key, index)),
+            TableStep::KeyIdUsage(key, index) => Some(KeyIdUsageEntry::get_req(key, index)),
+            TableStep::Device(index) => Some(DeviceEntry::get_req(index, 0)),
+            TableStep::SecurityLevel(index) => Some(SecurityLevelEntry::get_req(index, 0)),
+            TableStep::Done => None,
+        }
+    }
+
+    // Takes the response to the last next_request.
+    pub fn handle_response(&mut self, response: srsp::SecurityGetReq) -> Result<(), Error> {
+        self.step = match self.step {
+            TableStep::KeyTableEntries => {
+                self.key_count = KeyTableEntries::from_get_rsp(response)?.0 as u16;
+                TableStep::DeviceTableEntries
+            }
+            TableStep::DeviceTableEntries => {
+                self.device_count = DeviceTableEntries::from_get_rsp(response)?.0 as u16;
+                TableStep::SecurityLevelTableEntries
+            }
+            TableStep::SecurityLevelTableEntries => {
+                self.security_level_count =
+                    SecurityLevelTableEntries::from_get_rsp(response)?.0 as u16;
+                self.next_key(0)
+            }
+            TableStep::Key(key) => {
+                self.tables.keys.push(KeyDescriptor {
+                    key: KeyEntry::from_get_rsp(response)?,
+                    lookup_list: Vec::new(),
+                    device_list: Vec::new(),
+                    usage_list: Vec::new(),
+                });
+                self.next_lookup(key, 0)
+            }
+            TableStep::KeyIdLookup(key, index) => {
+                let list = &mut self.tables.keys[key as usize].lookup_list;
+                match read_list_entry(response, list)? {
+                    true => self.next_lookup(key, index + 1),
+                    false => self.next_key_device(key, 0),
+                }
+            }
+            TableStep::KeyIdDevice(key, index) => {
+                let list = &mut self.tables.keys[key as usize].device_list;
+                match read_list_entry(response, list)? {
+                    true => self.next_key_device(key, index + 1),
+                    false => self.next_usage(key, 0),
+                }
+            }
+            TableStep::KeyIdUsage(key, index) => {
+                let list = &mut self.tables.keys[key as usize].usage_list;
+                match read_list_entry(response, list)? {
+                    true => self.next_usage(key, index + 1),
+                    false => self.next_key(key + 1),
+                }
+            }
+            TableStep::Device(index) => {
+                self.tables
+                    .devices
+                    .push(DeviceEntry::from_get_rsp(response)?);
+                self.next_device(index + 1)
+            }
+            TableStep::SecurityLevel(index) => {
+                let entry = SecurityLevelEntry::from_get_rsp(response)?;
+                self.tables.security_levels.push(entry);
+                self.next_security_level(index + 1)
+            }
+            TableStep::Done => TableStep::Done,
+        };
+        Ok(())
+    }
+
+    pub fn into_tables(self) -> SecurityTables {
+        self.tables
+    }
+
+    fn next_key(&self, key: u16) -> TableStep {
+        match key < self.key_count {
+            true => TableStep::Key(key),
+            false => self.next_device(0),
+        }
+    }
+
+    fn next_lookup(&self, key: u16, index: u16) -> TableStep {
+        match index < MAX_KEY_LIST_LENGTH {
+            true => TableStep::KeyIdLookup(key, index),
+            false => self.next_key_device(key, 0),
+        }
+    }
+
+    fn next_key_device(&self, key: u16, index: u16) -> TableStep {
+        match index < self.device_count {
+            true => TableStep::KeyIdDevice(key, index),
+            false => self.next_usage(key, 0),
+        }
+    }
+
+    fn next_usage(&self, key: u16, index: u16) -> TableStep {
+        match index < MAX_KEY_LIST_LENGTH {
+            true => TableStep::KeyIdUsage(key, index),
+            false => self.next_key(key + 1),
+        }
+    }
+
+    fn next_device(&self, index: u16) -> TableStep {
+        match index < self.device_count {
+            true => TableStep::Device(index),
+            false => self.next_security_level(0),
+        }
+    }
+
+    fn next_security_level(&self, index: u16) -> TableStep {
+        match index < self.security_level_count {
+            true => TableStep::SecurityLevel(index),
+            false => TableStep::Done,
+        }
+    }
+}
+
+impl Default for SecurityTablesReader {
+    fn default() -> Self {
+        SecurityTablesReader::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        DeviceEntry, DeviceFrameCounter, KeyEntry, KeyIdLookupEntry, KeyTableEntries,
+        SecurityPIBValue, TableEntry,
+    };
+    use crate::command::Command;
+    use crate::error::Error;
+    use crate::subsystem::mac::pib::Attribute;
+    use crate::subsystem::mac::srsp;
+    use crate::types::{ExtendedAddress, SecurityPIBAttributeId};
+
+    #[test]
+    fn entries_roundtrip() {
+        let entry = DeviceEntry {
+            pan_id: 0x1234,
+            short_address: 0x0001,
+            ext_address: ExtendedAddress {
+                address: [1, 2, 3, 4, 5, 6, 7, 8],
+            },
+            exempt: false,
+            frame_counters: vec![DeviceFrameCounter {
+                frame_counter: 7,
+                key_index: 1,
+            }],
+        };
+
+        let set_req = entry.set_req(3, 0).unwrap();
+        let buffer = set_req.encode();
+        assert_eq!(buffer[..5], [0xd4, 0x3, 0x0, 0x0, 0x0]);
+        assert_eq!(buffer.len(), 5 + 2 + 2 + 8 + 1 + 6);
+
+        let mut response = vec![0x0, 0x3, 0x0, 0x0, 0x0];
+        response.extend(&buffer[5..]);
+        let response = srsp::SecurityGetReq::try_decode(&response).unwrap();
+        assert_eq!(DeviceEntry::from_get_rsp(response).unwrap(), entry);
+
+        let entry = KeyIdLookupEntry {
+            lookup_data: vec![1, 2, 3, 4, 5],
+        };
+        let set_req = entry.set_req(0, 0).unwrap();
+        assert_eq!(set_req.attribute_value, [1, 2, 3, 4, 5, 0, 0, 0, 0, 0]);
+
+        let mut cursor = std::io::Cursor::new(set_req.attribute_value.as_slice());
+        assert_eq!(
+            SecurityPIBValue::try_decode(SecurityPIBAttributeId::KeyIdLookupEntry, &mut cursor)
+                .unwrap(),
+            SecurityPIBValue::KeyIdLookupEntry(entry)
+        );
+
+        let entry = KeyIdLookupEntry {
+            lookup_data: vec![1, 2, 3],
+        };
+        assert!(matches!(
+            entry.set_req(0, 0),
+            Err(Error::InvalidKeyIdLookupSize(3))
+        ));
+    }
+
+    #[test]
+    fn get_rsp_checks_length() {
+        let response = srsp::SecurityGetReq::try_decode(&[0x0, 0x0, 0x0, 0x0, 0x0, 0x2]).unwrap();
+        assert_eq!(
+            KeyTableEntries::from_get_rsp(response).unwrap(),
+            KeyTableEntries(2)
+        );
+
+        let response = srsp::SecurityGetReq::try_decode(&[0x0, 0x0, 0x0, 0x0, 0x0, 0x1]).unwrap();
+        assert!(matches!(
+            KeyEntry::from_get_rsp(response),
+            Err(Error::NotEnoughBytes)
+        ));
+
+        let mut buffer = vec![0x0, 0x0, 0x0, 0x0, 0x0];
+        buffer.extend([0xaa; 21]);
+        let response = srsp::SecurityGetReq::try_decode(&buffer).unwrap();
+        assert!(matches!(
+            KeyEntry::from_get_rsp(response),
+            Err(Error::TrailingBytes(1))
+        ));
+    }
+}
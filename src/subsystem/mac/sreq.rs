@@ -1,41 +1,32 @@
+use crate::command::{Command, Request};
 use crate::error::Error;
-use crate::frame::{CommandCode, MTFrame, MTHeader};
+use crate::ie::{self, PayloadIE};
+use crate::phy::{BeaconTiming, PhyDescriptor};
+use crate::subsystem::mac::areq::PanDescriptor;
+use crate::subsystem::mac::srsp;
 use crate::types::*;
 use bytes::{Buf, BufMut};
+use num_traits::FromPrimitive;
 use std::io::Cursor;
 use std::io::Read;
 
 #[derive(Debug, Clone)]
 pub struct Init {}
 
-impl Init {
-    pub fn try_decode(_: &[u8]) -> Result<Self, Error> {
+impl Command for Init {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SREQ;
+    const ID: u8 = MACCommandId::Init as u8;
+
+    fn try_decode_from(_: &mut Cursor<&[u8]>) -> Result<Self, Error> {
         Ok(Init {})
     }
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
-    }
+    fn encode_into(&self, _: &mut Vec<u8>) {}
+}
 
-    pub fn encode_into(&self, _: &mut Vec<u8>) {}
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x00,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SREQ,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::Init as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
+impl Request for Init {
+    type Response = srsp::Init;
 }
 
 #[derive(Debug, Clone)]
@@ -51,37 +42,48 @@ pub struct DataReq {
     pub security_level: SecurityLevel,
     pub key_id_mode: KeyIdMode,
     pub key_index: u8,
-    pub include_fh_ies: u32,
-    pub data_length: u16,
-    pub ie_length: u16,
+    pub include_fh_ies: FHIEFlags,
     pub data_payload: Vec<u8>,
     pub ie_payload: Vec<u8>,
 }
 
 impl DataReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let dest_address = Address::try_decode(&mut cursor)?;
-        let dest_pan_id = cursor.get_u16_le();
-        let src_address_mode = AddressMode::try_decode(&mut cursor)?;
-        let handle = cursor.get_u8();
-        let tx_option = TxOption::try_decode(&mut cursor)?;
-        let channel = cursor.get_u8();
-        let power = cursor.get_u8();
-        let key_source = KeySource::try_decode(&mut cursor)?;
-        let security_level = SecurityLevel::try_decode(&mut cursor)?;
-        let key_id_mode = KeyIdMode::try_decode(&mut cursor)?;
-        let key_index = cursor.get_u8();
-        let include_fh_ies = cursor.get_u32_le();
-        let data_length = cursor.get_u16_le();
-        let ie_length = cursor.get_u16_le();
+    pub fn payload_ies(&self) -> Result<Vec<PayloadIE>, Error> {
+        ie::decode_payload_ies(&self.ie_payload)
+    }
+
+    pub fn set_payload_ies(&mut self, ies: &[PayloadIE]) {
+        self.ie_payload = ie::encode_payload_ies(ies);
+    }
+}
+
+impl Command for DataReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SREQ;
+    const ID: u8 = MACCommandId::DataReq as u8;
+
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let dest_address = Address::try_decode(cursor)?;
+        let dest_pan_id = cursor.try_get_u16_le()?;
+        let src_address_mode = AddressMode::try_decode(cursor)?;
+        let handle = cursor.try_get_u8()?;
+        let tx_option = TxOption::try_decode(cursor)?;
+        let channel = cursor.try_get_u8()?;
+        let power = cursor.try_get_u8()?;
+        let key_source = KeySource::try_decode(cursor)?;
+        let security_level = SecurityLevel::try_decode(cursor)?;
+        let key_id_mode = KeyIdMode::try_decode(cursor)?;
+        let key_index = cursor.try_get_u8()?;
+        let include_fh_ies = FHIEFlags::try_decode(cursor)?;
+        let data_length = cursor.try_get_u16_le()?;
+        let ie_length = cursor.try_get_u16_le()?;
 
         let mut data_payload = vec![0x00; data_length as usize];
         cursor
             .read_exact(&mut data_payload)
             .map_err(|_| Error::NotEnoughBytes)?;
 
-        let mut ie_payload = vec![0x00; data_length as usize];
+        let mut ie_payload = vec![0x00; ie_length as usize];
         cursor
             .read_exact(&mut ie_payload)
             .map_err(|_| Error::NotEnoughBytes)?;
@@ -99,20 +101,12 @@ impl DataReq {
             key_id_mode,
             key_index,
             include_fh_ies,
-            data_length,
-            ie_length,
             data_payload,
             ie_payload,
         })
     }
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
-    }
-
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.dest_address.encode_into(buffer);
         buffer.put_u16_le(self.dest_pan_id);
         self.src_address_mode.encode_into(buffer);
@@ -124,28 +118,17 @@ impl DataReq {
         self.security_level.encode_into(buffer);
         self.key_id_mode.encode_into(buffer);
         buffer.put_u8(self.key_index);
-        buffer.put_u32_le(self.include_fh_ies);
-        buffer.put_u16_le(self.data_length);
-        buffer.put_u16_le(self.ie_length);
+        self.include_fh_ies.encode_into(buffer);
+        // Both lengths are derived from the payloads so they can't disagree.
+        buffer.put_u16_le(self.data_payload.len() as u16);
+        buffer.put_u16_le(self.ie_payload.len() as u16);
         buffer.extend(self.data_payload.iter());
         buffer.extend(self.ie_payload.iter());
     }
+}
 
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: (0x23 + self.data_payload.len() + self.ie_payload.len()) as u8,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SREQ,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::DataReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
+impl Request for DataReq {
+    type Response = srsp::DataReq;
 }
 
 #[derive(Debug, Clone)]
@@ -153,38 +136,23 @@ pub struct PurgeReq {
     pub handle: u8,
 }
 
-impl PurgeReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let handle = cursor.get_u8();
-        Ok(PurgeReq { handle })
-    }
+impl Command for PurgeReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SREQ;
+    const ID: u8 = MACCommandId::PurgeReq as u8;
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let handle = cursor.try_get_u8()?;
+        Ok(PurgeReq { handle })
     }
 
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         buffer.put_u8(self.handle);
     }
+}
 
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x01,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SREQ,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::PurgeReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
+impl Request for PurgeReq {
+    type Response = srsp::PurgeReq;
 }
 
 #[derive(Debug, Clone)]
@@ -194,7 +162,7 @@ pub struct AssociateReq {
     pub phy_id: u8,
     pub coord_address: Address,
     pub coord_pan_id: u16,
-    pub capability_info: u8,
+    pub capability_info: CapabilityInformation,
     pub key_source: KeySource,
     pub security_level: SecurityLevel,
     pub key_id_mode: KeyIdMode,
@@ -202,18 +170,49 @@ pub struct AssociateReq {
 }
 
 impl AssociateReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let logical_channel = cursor.get_u8();
-        let channel_page = cursor.get_u8();
-        let phy_id = cursor.get_u8();
-        let coord_address = Address::try_decode(&mut cursor)?;
-        let coord_pan_id = cursor.get_u16_le();
-        let capability_info = cursor.get_u8();
-        let key_source = KeySource::try_decode(&mut cursor)?;
-        let security_level = SecurityLevel::try_decode(&mut cursor)?;
-        let key_id_mode = KeyIdMode::try_decode(&mut cursor)?;
-        let key_index = cursor.get_u8();
+    // Associates with the coordinator of a scan or beacon PAN descriptor,
+    // using the security of its beacons.
+    pub fn from_pan_descriptor(
+        descriptor: &PanDescriptor,
+        phy_id: PhyId,
+        capability_info: CapabilityInformation,
+    ) -> AssociateReq {
+        AssociateReq {
+            logical_channel: descriptor.logical_channel,
+            channel_page: descriptor.channel_page,
+            phy_id: phy_id as u8,
+            coord_address: descriptor.coord_address,
+            coord_pan_id: descriptor.pan_id,
+            capability_info,
+            key_source: descriptor.key_source,
+            security_level: descriptor.security_level,
+            key_id_mode: descriptor.key_id_mode,
+            key_index: descriptor.key_index,
+        }
+    }
+
+    pub fn validate_channel(&self) -> Result<(), Error> {
+        let phy_id = PhyId::from_u8(self.phy_id).ok_or(Error::InvalidPhyId(self.phy_id))?;
+        PhyDescriptor::try_get(phy_id)?.validate_channel(self.logical_channel)
+    }
+}
+
+impl Command for AssociateReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SREQ;
+    const ID: u8 = MACCommandId::AssociateReq as u8;
+
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let logical_channel = cursor.try_get_u8()?;
+        let channel_page = cursor.try_get_u8()?;
+        let phy_id = cursor.try_get_u8()?;
+        let coord_address = Address::try_decode(cursor)?;
+        let coord_pan_id = cursor.try_get_u16_le()?;
+        let capability_info = CapabilityInformation::try_decode(cursor)?;
+        let key_source = KeySource::try_decode(cursor)?;
+        let security_level = SecurityLevel::try_decode(cursor)?;
+        let key_id_mode = KeyIdMode::try_decode(cursor)?;
+        let key_index = cursor.try_get_u8()?;
         Ok(AssociateReq {
             logical_channel,
             channel_page,
@@ -228,40 +227,22 @@ impl AssociateReq {
         })
     }
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
-    }
-
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         buffer.put_u8(self.logical_channel);
         buffer.put_u8(self.channel_page);
         buffer.put_u8(self.phy_id);
         self.coord_address.encode_into(buffer);
         buffer.put_u16_le(self.coord_pan_id);
-        buffer.put_u8(self.capability_info);
+        self.capability_info.encode_into(buffer);
         self.key_source.encode_into(buffer);
         self.security_level.encode_into(buffer);
         self.key_id_mode.encode_into(buffer);
         buffer.put_u8(self.key_index);
     }
+}
 
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x1a,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SREQ,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::AssociateReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
+impl Request for AssociateReq {
+    type Response = srsp::AssociateReq;
 }
 
 #[derive(Debug, Clone)]
@@ -275,16 +256,19 @@ pub struct AssociateRsp {
     pub key_index: u8,
 }
 
-impl AssociateRsp {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let extended_address = ExtendedAddress::try_decode(&mut cursor)?;
-        let assoc_short_address = ShortAddress::try_decode(&mut cursor)?;
-        let assoc_status = AssociationStatus::try_decode(&mut cursor)?;
-        let key_source = KeySource::try_decode(&mut cursor)?;
-        let security_level = SecurityLevel::try_decode(&mut cursor)?;
-        let key_id_mode = KeyIdMode::try_decode(&mut cursor)?;
-        let key_index = cursor.get_u8();
+impl Command for AssociateRsp {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SREQ;
+    const ID: u8 = MACCommandId::AssociateRsp as u8;
+
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let extended_address = ExtendedAddress::try_decode(cursor)?;
+        let assoc_short_address = ShortAddress::try_decode(cursor)?;
+        let assoc_status = AssociationStatus::try_decode(cursor)?;
+        let key_source = KeySource::try_decode(cursor)?;
+        let security_level = SecurityLevel::try_decode(cursor)?;
+        let key_id_mode = KeyIdMode::try_decode(cursor)?;
+        let key_index = cursor.try_get_u8()?;
         Ok(AssociateRsp {
             extended_address,
             assoc_short_address,
@@ -296,13 +280,7 @@ impl AssociateRsp {
         })
     }
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
-    }
-
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.extended_address.encode_into(buffer);
         self.assoc_short_address.encode_into(buffer);
         self.assoc_status.encode_into(buffer);
@@ -311,22 +289,10 @@ impl AssociateRsp {
         self.key_id_mode.encode_into(buffer);
         buffer.put_u8(self.key_index);
     }
+}
 
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x16,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SREQ,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::AssociateRsp as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
+impl Request for AssociateRsp {
+    type Response = srsp::AssociateRsp;
 }
 
 #[derive(Debug, Clone)]
@@ -341,17 +307,20 @@ pub struct DisassociateReq {
     pub key_index: u8,
 }
 
-impl DisassociateReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let device_address = Address::try_decode(&mut cursor)?;
-        let device_pan_id = cursor.get_u16_le();
-        let disassociate_reason = DisassociateReason::try_decode(&mut cursor)?;
-        let tx_indirect = cursor.get_u8() == 0;
-        let key_source = KeySource::try_decode(&mut cursor)?;
-        let security_level = SecurityLevel::try_decode(&mut cursor)?;
-        let key_id_mode = KeyIdMode::try_decode(&mut cursor)?;
-        let key_index = cursor.get_u8();
+impl Command for DisassociateReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SREQ;
+    const ID: u8 = MACCommandId::DisassociateReq as u8;
+
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let device_address = Address::try_decode(cursor)?;
+        let device_pan_id = cursor.try_get_u16_le()?;
+        let disassociate_reason = DisassociateReason::try_decode(cursor)?;
+        let tx_indirect = cursor.try_get_u8()? == 0;
+        let key_source = KeySource::try_decode(cursor)?;
+        let security_level = SecurityLevel::try_decode(cursor)?;
+        let key_id_mode = KeyIdMode::try_decode(cursor)?;
+        let key_index = cursor.try_get_u8()?;
         Ok(DisassociateReq {
             device_address,
             device_pan_id,
@@ -364,13 +333,7 @@ impl DisassociateReq {
         })
     }
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
-    }
-
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.device_address.encode_into(buffer);
         buffer.put_u16_le(self.device_pan_id);
         self.disassociate_reason.encode_into(buffer);
@@ -380,22 +343,10 @@ impl DisassociateReq {
         self.key_id_mode.encode_into(buffer);
         buffer.put_u8(self.key_index);
     }
+}
 
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x18,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SREQ,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::DisassociateReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
+impl Request for DisassociateReq {
+    type Response = srsp::DisassociateReq;
 }
 
 #[derive(Debug, Clone)]
@@ -403,38 +354,23 @@ pub struct GetReq {
     pub attribute_id: MACPIBAttributeId,
 }
 
-impl GetReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let attribute_id = MACPIBAttributeId::try_decode(&mut cursor)?;
-        Ok(GetReq { attribute_id })
-    }
+impl Command for GetReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SREQ;
+    const ID: u8 = MACCommandId::GetReq as u8;
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let attribute_id = MACPIBAttributeId::try_decode(cursor)?;
+        Ok(GetReq { attribute_id })
     }
 
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.attribute_id.encode_into(buffer);
     }
+}
 
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x01,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SREQ,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::GetReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
+impl Request for GetReq {
+    type Response = srsp::GetReq;
 }
 
 #[derive(Debug, Clone)]
@@ -443,10 +379,13 @@ pub struct SetReq {
     pub attribute_value: [u8; 16],
 }
 
-impl SetReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let attribute_id = MACPIBAttributeId::try_decode(&mut cursor)?;
+impl Command for SetReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SREQ;
+    const ID: u8 = MACCommandId::SetReq as u8;
+
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let attribute_id = MACPIBAttributeId::try_decode(cursor)?;
 
         let mut attribute_value: [u8; 16] = Default::default();
         cursor
@@ -460,32 +399,14 @@ impl SetReq {
         })
     }
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
-    }
-
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.attribute_id.encode_into(buffer);
         buffer.extend(self.attribute_value.iter().rev());
     }
+}
 
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x11,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SREQ,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::SetReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
+impl Request for SetReq {
+    type Response = srsp::SetReq;
 }
 
 #[derive(Debug, Clone)]
@@ -495,12 +416,15 @@ pub struct SecurityGetReq {
     pub index2: u16,
 }
 
-impl SecurityGetReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let attribute_id = SecurityPIBAttributeId::try_decode(&mut cursor)?;
-        let index1 = cursor.get_u16_le();
-        let index2 = cursor.get_u16_le();
+impl Command for SecurityGetReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SREQ;
+    const ID: u8 = MACCommandId::SecurityGetReq as u8;
+
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let attribute_id = SecurityPIBAttributeId::try_decode(cursor)?;
+        let index1 = cursor.try_get_u16_le()?;
+        let index2 = cursor.try_get_u16_le()?;
 
         Ok(SecurityGetReq {
             attribute_id,
@@ -509,33 +433,15 @@ impl SecurityGetReq {
         })
     }
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
-    }
-
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.attribute_id.encode_into(buffer);
         buffer.put_u16_le(self.index1);
         buffer.put_u16_le(self.index2);
     }
+}
 
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x05,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SREQ,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::SecurityGetReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
+impl Request for SecurityGetReq {
+    type Response = srsp::SecurityGetReq;
 }
 
 #[derive(Debug, Clone)]
@@ -546,12 +452,15 @@ pub struct SecuritySetReq {
     pub attribute_value: Vec<u8>,
 }
 
-impl SecuritySetReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let attribute_id = SecurityPIBAttributeId::try_decode(&mut cursor)?;
-        let index1 = cursor.get_u16_le();
-        let index2 = cursor.get_u16_le();
+impl Command for SecuritySetReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SREQ;
+    const ID: u8 = MACCommandId::SecuritySetReq as u8;
+
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let attribute_id = SecurityPIBAttributeId::try_decode(cursor)?;
+        let index1 = cursor.try_get_u16_le()?;
+        let index2 = cursor.try_get_u16_le()?;
 
         let mut attribute_value = Vec::new();
         cursor
@@ -566,34 +475,16 @@ impl SecuritySetReq {
         })
     }
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
-    }
-
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.attribute_id.encode_into(buffer);
         buffer.put_u16_le(self.index1);
         buffer.put_u16_le(self.index2);
         buffer.extend(self.attribute_value.iter());
     }
+}
 
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x05 + self.attribute_value.len() as u8,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SREQ,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::SecuritySetReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
+impl Request for SecuritySetReq {
+    type Response = srsp::SecuritySetReq;
 }
 
 #[derive(Debug, Clone)]
@@ -601,38 +492,23 @@ pub struct UpdatePANIdReq {
     pub pan_id: u16,
 }
 
-impl UpdatePANIdReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let pan_id = cursor.get_u16_le();
-        Ok(UpdatePANIdReq { pan_id })
-    }
+impl Command for UpdatePANIdReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SREQ;
+    const ID: u8 = MACCommandId::UpdatePANIdReq as u8;
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let pan_id = cursor.try_get_u16_le()?;
+        Ok(UpdatePANIdReq { pan_id })
     }
 
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         buffer.put_u16_le(self.pan_id);
     }
+}
 
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x02,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SREQ,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::UpdatePANIdReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
+impl Request for UpdatePANIdReq {
+    type Response = srsp::UpdatePANIdReq;
 }
 
 #[derive(Debug, Clone)]
@@ -648,17 +524,20 @@ pub struct AddDeviceReq {
     pub lookup_data: [u8; 9],
 }
 
-impl AddDeviceReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let pan_id = cursor.get_u16_le();
-        let short_addr = ShortAddress::try_decode(&mut cursor)?;
-        let ext_addr = ExtendedAddress::try_decode(&mut cursor)?;
-        let frame_counter = cursor.get_u32_le();
-        let exempt = cursor.get_u8() != 0;
-        let unique = cursor.get_u8() != 0;
-        let duplicate = cursor.get_u8() != 0;
-        let data_size = cursor.get_u8();
+impl Command for AddDeviceReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SREQ;
+    const ID: u8 = MACCommandId::AddDeviceReq as u8;
+
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let pan_id = cursor.try_get_u16_le()?;
+        let short_addr = ShortAddress::try_decode(cursor)?;
+        let ext_addr = ExtendedAddress::try_decode(cursor)?;
+        let frame_counter = cursor.try_get_u32_le()?;
+        let exempt = cursor.try_get_u8()? != 0;
+        let unique = cursor.try_get_u8()? != 0;
+        let duplicate = cursor.try_get_u8()? != 0;
+        let data_size = cursor.try_get_u8()?;
 
         let mut lookup_data: [u8; 9] = Default::default();
         cursor
@@ -678,13 +557,7 @@ impl AddDeviceReq {
         })
     }
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
-    }
-
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         buffer.put_u16_le(self.pan_id);
         self.short_addr.encode_into(buffer);
         self.ext_addr.encode_into(buffer);
@@ -695,22 +568,10 @@ impl AddDeviceReq {
         buffer.put_u8(self.data_size);
         buffer.extend(self.lookup_data.iter());
     }
+}
 
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x1d,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SREQ,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::AddDeviceReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
+impl Request for AddDeviceReq {
+    type Response = srsp::AddDeviceReq;
 }
 
 #[derive(Debug, Clone)]
@@ -718,71 +579,42 @@ pub struct DeleteDeviceReq {
     pub ext_addr: ExtendedAddress,
 }
 
-impl DeleteDeviceReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let ext_addr = ExtendedAddress::try_decode(&mut cursor)?;
-        Ok(DeleteDeviceReq { ext_addr })
-    }
+impl Command for DeleteDeviceReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SREQ;
+    const ID: u8 = MACCommandId::DeleteDeviceReq as u8;
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let ext_addr = ExtendedAddress::try_decode(cursor)?;
+        Ok(DeleteDeviceReq { ext_addr })
     }
 
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.ext_addr.encode_into(buffer);
     }
+}
 
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x08,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SREQ,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::DeleteDeviceReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
+impl Request for DeleteDeviceReq {
+    type Response = srsp::DeleteDeviceReq;
 }
 
 #[derive(Debug, Clone)]
 pub struct DeleteAllDevicesReq {}
 
-impl DeleteAllDevicesReq {
-    pub fn try_decode(_: &[u8]) -> Result<Self, Error> {
+impl Command for DeleteAllDevicesReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SREQ;
+    const ID: u8 = MACCommandId::DeleteAllDevicesReq as u8;
+
+    fn try_decode_from(_: &mut Cursor<&[u8]>) -> Result<Self, Error> {
         Ok(DeleteAllDevicesReq {})
     }
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
-    }
+    fn encode_into(&self, _: &mut Vec<u8>) {}
+}
 
-    pub fn encode_into(&self, _: &mut Vec<u8>) {}
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x00,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SREQ,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::DeleteAllDevicesReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
+impl Request for DeleteAllDevicesReq {
+    type Response = srsp::DeleteAllDevicesReq;
 }
 
 #[derive(Debug, Clone)]
@@ -790,38 +622,23 @@ pub struct DeleteKeyReq {
     pub index: u8,
 }
 
-impl DeleteKeyReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let index = cursor.get_u8();
-        Ok(DeleteKeyReq { index })
-    }
+impl Command for DeleteKeyReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SREQ;
+    const ID: u8 = MACCommandId::DeleteKeyReq as u8;
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let index = cursor.try_get_u8()?;
+        Ok(DeleteKeyReq { index })
     }
 
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         buffer.put_u8(self.index);
     }
+}
 
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x01,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SREQ,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::DeleteKeyReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
+impl Request for DeleteKeyReq {
+    type Response = srsp::DeleteKeyReq;
 }
 
 #[derive(Debug, Clone)]
@@ -829,38 +646,23 @@ pub struct ReadKeyReq {
     pub index: u8,
 }
 
-impl ReadKeyReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let index = cursor.get_u8();
-        Ok(ReadKeyReq { index })
-    }
+impl Command for ReadKeyReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SREQ;
+    const ID: u8 = MACCommandId::ReadKeyReq as u8;
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let index = cursor.try_get_u8()?;
+        Ok(ReadKeyReq { index })
     }
 
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         buffer.put_u8(self.index);
     }
+}
 
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x01,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SREQ,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::ReadKeyReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
+impl Request for ReadKeyReq {
+    type Response = srsp::ReadKeyReq;
 }
 
 #[derive(Debug, Clone)]
@@ -873,19 +675,22 @@ pub struct WriteKeyReq {
     pub lookup_data: [u8; 9],
 }
 
-impl WriteKeyReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let new = cursor.get_u8() != 0;
-        let index = cursor.get_u16_le();
+impl Command for WriteKeyReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SREQ;
+    const ID: u8 = MACCommandId::WriteKeyReq as u8;
+
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let new = cursor.try_get_u8()? != 0;
+        let index = cursor.try_get_u16_le()?;
 
         let mut key: [u8; 16] = Default::default();
         cursor
             .read_exact(&mut key)
             .map_err(|_| Error::NotEnoughBytes)?;
 
-        let frame_counter = cursor.get_u32_le();
-        let data_size = cursor.get_u8();
+        let frame_counter = cursor.try_get_u32_le()?;
+        let data_size = cursor.try_get_u8()?;
 
         let mut lookup_data: [u8; 9] = Default::default();
         cursor
@@ -902,13 +707,7 @@ impl WriteKeyReq {
         })
     }
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
-    }
-
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         buffer.put_u8(if self.new { 1 } else { 0 });
         buffer.put_u16_le(self.index);
         buffer.extend(self.key.iter());
@@ -916,22 +715,10 @@ impl WriteKeyReq {
         buffer.put_u8(self.data_size);
         buffer.extend(self.lookup_data.iter());
     }
+}
 
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x21,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SREQ,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::WriteKeyReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
+impl Request for WriteKeyReq {
+    type Response = srsp::WriteKeyReq;
 }
 
 #[derive(Debug, Clone)]
@@ -945,16 +732,19 @@ pub struct OrphanRsp {
     pub key_index: u8,
 }
 
-impl OrphanRsp {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let extended_address = ExtendedAddress::try_decode(&mut cursor)?;
-        let assoc_short_address = ShortAddress::try_decode(&mut cursor)?;
-        let associated_member = cursor.get_u8() != 0;
-        let key_source = KeySource::try_decode(&mut cursor)?;
-        let security_level = SecurityLevel::try_decode(&mut cursor)?;
-        let key_id_mode = KeyIdMode::try_decode(&mut cursor)?;
-        let key_index = cursor.get_u8();
+impl Command for OrphanRsp {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SREQ;
+    const ID: u8 = MACCommandId::OrphanRsp as u8;
+
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let extended_address = ExtendedAddress::try_decode(cursor)?;
+        let assoc_short_address = ShortAddress::try_decode(cursor)?;
+        let associated_member = cursor.try_get_u8()? != 0;
+        let key_source = KeySource::try_decode(cursor)?;
+        let security_level = SecurityLevel::try_decode(cursor)?;
+        let key_id_mode = KeyIdMode::try_decode(cursor)?;
+        let key_index = cursor.try_get_u8()?;
         Ok(OrphanRsp {
             extended_address,
             assoc_short_address,
@@ -966,13 +756,7 @@ impl OrphanRsp {
         })
     }
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
-    }
-
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.extended_address.encode_into(buffer);
         self.assoc_short_address.encode_into(buffer);
         buffer.put_u8(if self.associated_member { 1 } else { 0 });
@@ -981,22 +765,10 @@ impl OrphanRsp {
         self.key_id_mode.encode_into(buffer);
         buffer.put_u8(self.key_index);
     }
+}
 
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x16,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SREQ,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::OrphanRsp as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
+impl Request for OrphanRsp {
+    type Response = srsp::OrphanRsp;
 }
 
 #[derive(Debug, Clone)]
@@ -1009,15 +781,18 @@ pub struct PollReq {
     pub key_index: u8,
 }
 
-impl PollReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let coord_address = Address::try_decode(&mut cursor)?;
-        let coord_pan_id = cursor.get_u16_le();
-        let key_source = KeySource::try_decode(&mut cursor)?;
-        let security_level = SecurityLevel::try_decode(&mut cursor)?;
-        let key_id_mode = KeyIdMode::try_decode(&mut cursor)?;
-        let key_index = cursor.get_u8();
+impl Command for PollReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SREQ;
+    const ID: u8 = MACCommandId::PollReq as u8;
+
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let coord_address = Address::try_decode(cursor)?;
+        let coord_pan_id = cursor.try_get_u16_le()?;
+        let key_source = KeySource::try_decode(cursor)?;
+        let security_level = SecurityLevel::try_decode(cursor)?;
+        let key_id_mode = KeyIdMode::try_decode(cursor)?;
+        let key_index = cursor.try_get_u8()?;
         Ok(PollReq {
             coord_address,
             coord_pan_id,
@@ -1028,13 +803,7 @@ impl PollReq {
         })
     }
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
-    }
-
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.coord_address.encode_into(buffer);
         buffer.put_u16_le(self.coord_pan_id);
         self.key_source.encode_into(buffer);
@@ -1042,22 +811,10 @@ impl PollReq {
         self.key_id_mode.encode_into(buffer);
         buffer.put_u8(self.key_index);
     }
+}
 
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x16,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SREQ,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::PollReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
+impl Request for PollReq {
+    type Response = srsp::PollReq;
 }
 
 #[derive(Debug, Clone)]
@@ -1065,38 +822,23 @@ pub struct ResetReq {
     pub set_default: bool,
 }
 
-impl ResetReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let set_default = cursor.get_u8() != 0;
-        Ok(ResetReq { set_default })
-    }
+impl Command for ResetReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SREQ;
+    const ID: u8 = MACCommandId::ResetReq as u8;
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let set_default = cursor.try_get_u8()? != 0;
+        Ok(ResetReq { set_default })
     }
 
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         buffer.put_u8(if self.set_default { 1 } else { 0 });
     }
+}
 
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x01,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SREQ,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::ResetReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
+impl Request for ResetReq {
+    type Response = srsp::ResetReq;
 }
 
 #[derive(Debug, Clone)]
@@ -1119,25 +861,28 @@ pub struct ScanReq {
     pub channels: ChannelsBitMap,
 }
 
-impl ScanReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let scan_type = ScanType::try_decode(&mut cursor)?;
-        let scan_duration = cursor.get_u8();
-        let channel_page = cursor.get_u8();
-        let phy_id = PhyId::try_decode(&mut cursor)?;
-        let max_results = cursor.get_u8();
-        let permit_join = PermitJoin::try_decode(&mut cursor)?;
-        let link_quality = cursor.get_u8();
-        let rsp_filter = cursor.get_u8();
-        let mpm_scan = MPMScan::try_decode(&mut cursor)?;
-        let mpm_type = MPMType::try_decode(&mut cursor)?;
-        let mpm_duration = cursor.get_u16_le();
-        let key_source = KeySource::try_decode(&mut cursor)?;
-        let security_level = SecurityLevel::try_decode(&mut cursor)?;
-        let key_id_mode = KeyIdMode::try_decode(&mut cursor)?;
-        let key_index = cursor.get_u8();
-        let channels = ChannelsBitMap::try_decode(&mut cursor)?;
+impl Command for ScanReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SREQ;
+    const ID: u8 = MACCommandId::ScanReq as u8;
+
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let scan_type = ScanType::try_decode(cursor)?;
+        let scan_duration = cursor.try_get_u8()?;
+        let channel_page = cursor.try_get_u8()?;
+        let phy_id = PhyId::try_decode(cursor)?;
+        let max_results = cursor.try_get_u8()?;
+        let permit_join = PermitJoin::try_decode(cursor)?;
+        let link_quality = cursor.try_get_u8()?;
+        let rsp_filter = cursor.try_get_u8()?;
+        let mpm_scan = MPMScan::try_decode(cursor)?;
+        let mpm_type = MPMType::try_decode(cursor)?;
+        let mpm_duration = cursor.try_get_u16_le()?;
+        let key_source = KeySource::try_decode(cursor)?;
+        let security_level = SecurityLevel::try_decode(cursor)?;
+        let key_id_mode = KeyIdMode::try_decode(cursor)?;
+        let key_index = cursor.try_get_u8()?;
+        let channels = ChannelsBitMap::try_decode(cursor)?;
         Ok(ScanReq {
             scan_type,
             scan_duration,
@@ -1158,13 +903,7 @@ impl ScanReq {
         })
     }
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
-    }
-
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.scan_type.encode_into(buffer);
         buffer.put_u8(self.scan_duration);
         buffer.put_u8(self.channel_page);
@@ -1182,22 +921,10 @@ impl ScanReq {
         buffer.put_u8(self.key_index);
         self.channels.encode_into(buffer);
     }
+}
 
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x1b,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SREQ,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::ScanReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
+impl Request for ScanReq {
+    type Response = srsp::ScanReq;
 }
 
 #[derive(Debug, Clone)]
@@ -1224,41 +951,55 @@ pub struct StartReq {
     pub enh_beacon_order: u8,
     pub ofs_time_slot: u8,
     pub non_beacon_order: u16,
-    pub num_ies: u8,
-    pub ie_id_list: Vec<u8>,
+    pub ie_id_list: Vec<IEId>,
 }
 
 impl StartReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let start_time = cursor.get_u32_le();
-        let pan_id = cursor.get_u16_le();
-        let logical_channel = cursor.get_u8();
-        let channel_page = cursor.get_u8();
-        let phy_id = PhyId::try_decode(&mut cursor)?;
-        let beacon_order = cursor.get_u8();
-        let super_frame_order = cursor.get_u8();
-        let pan_coordinator = cursor.get_u8() != 0;
-        let battery_life_ext = cursor.get_u8() != 0;
-        let coord_realignment = cursor.get_u8() != 0;
-        let realign_key_source = KeySource::try_decode(&mut cursor)?;
-        let realign_security_level = SecurityLevel::try_decode(&mut cursor)?;
-        let realign_key_id_mode = KeyIdMode::try_decode(&mut cursor)?;
-        let realign_key_index = cursor.get_u8();
-        let beacon_key_source = KeySource::try_decode(&mut cursor)?;
-        let beacon_security_level = SecurityLevel::try_decode(&mut cursor)?;
-        let beacon_key_id_mode = KeyIdMode::try_decode(&mut cursor)?;
-        let beacon_key_index = cursor.get_u8();
-        let start_fh = cursor.get_u8() != 0;
-        let enh_beacon_order = cursor.get_u8();
-        let ofs_time_slot = cursor.get_u8();
-        let non_beacon_order = cursor.get_u16_le();
-        let num_ies = cursor.get_u8();
-
-        let mut ie_id_list = vec![0x00; num_ies as usize];
-        cursor
-            .read_exact(&mut ie_id_list)
-            .map_err(|_| Error::NotEnoughBytes)?;
+    // Checks logical_channel against the channel plan of phy_id.
+    pub fn validate_channel(&self) -> Result<(), Error> {
+        PhyDescriptor::try_get(self.phy_id)?.validate_channel(self.logical_channel)
+    }
+
+    // Also checks the beacon and superframe order.
+    pub fn beacon_timing(&self) -> Result<Option<BeaconTiming>, Error> {
+        PhyDescriptor::try_get(self.phy_id)?
+            .beacon_timing(self.beacon_order, self.super_frame_order)
+    }
+}
+
+impl Command for StartReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SREQ;
+    const ID: u8 = MACCommandId::StartReq as u8;
+
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let start_time = cursor.try_get_u32_le()?;
+        let pan_id = cursor.try_get_u16_le()?;
+        let logical_channel = cursor.try_get_u8()?;
+        let channel_page = cursor.try_get_u8()?;
+        let phy_id = PhyId::try_decode(cursor)?;
+        let beacon_order = cursor.try_get_u8()?;
+        let super_frame_order = cursor.try_get_u8()?;
+        let pan_coordinator = cursor.try_get_u8()? != 0;
+        let battery_life_ext = cursor.try_get_u8()? != 0;
+        let coord_realignment = cursor.try_get_u8()? != 0;
+        let realign_key_source = KeySource::try_decode(cursor)?;
+        let realign_security_level = SecurityLevel::try_decode(cursor)?;
+        let realign_key_id_mode = KeyIdMode::try_decode(cursor)?;
+        let realign_key_index = cursor.try_get_u8()?;
+        let beacon_key_source = KeySource::try_decode(cursor)?;
+        let beacon_security_level = SecurityLevel::try_decode(cursor)?;
+        let beacon_key_id_mode = KeyIdMode::try_decode(cursor)?;
+        let beacon_key_index = cursor.try_get_u8()?;
+        let start_fh = cursor.try_get_u8()? != 0;
+        let enh_beacon_order = cursor.try_get_u8()?;
+        let ofs_time_slot = cursor.try_get_u8()?;
+        let non_beacon_order = cursor.try_get_u16_le()?;
+        let num_ies = cursor.try_get_u8()?;
+
+        let ie_id_list = (0..num_ies)
+            .map(|_| IEId::try_decode(cursor))
+            .collect::<Result<Vec<_>, _>>()?;
 
         Ok(StartReq {
             start_time,
@@ -1283,18 +1024,11 @@ impl StartReq {
             enh_beacon_order,
             ofs_time_slot,
             non_beacon_order,
-            num_ies,
             ie_id_list,
         })
     }
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
-    }
-
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         buffer.put_u32_le(self.start_time);
         buffer.put_u16_le(self.pan_id);
         buffer.put_u8(self.logical_channel);
@@ -1317,27 +1051,17 @@ impl StartReq {
         buffer.put_u8(self.enh_beacon_order);
         buffer.put_u8(self.ofs_time_slot);
         buffer.put_u16_le(self.non_beacon_order);
-        buffer.put_u8(self.num_ies);
-        buffer.extend(self.ie_id_list.iter());
-    }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x2a + self.ie_id_list.len() as u8,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SREQ,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::StartReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
+        buffer.put_u8(self.ie_id_list.len() as u8);
+        for ie_id in self.ie_id_list.iter() {
+            ie_id.encode_into(buffer);
         }
     }
 }
 
+impl Request for StartReq {
+    type Response = srsp::StartReq;
+}
+
 #[derive(Debug, Clone)]
 pub struct SyncReq {
     pub logical_channel: u8,
@@ -1347,12 +1071,21 @@ pub struct SyncReq {
 }
 
 impl SyncReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let logical_channel = cursor.get_u8();
-        let channel_page = cursor.get_u8();
-        let track_beacon = cursor.get_u8() != 0;
-        let phy_id = PhyId::try_decode(&mut cursor)?;
+    pub fn validate_channel(&self) -> Result<(), Error> {
+        PhyDescriptor::try_get(self.phy_id)?.validate_channel(self.logical_channel)
+    }
+}
+
+impl Command for SyncReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SREQ;
+    const ID: u8 = MACCommandId::SyncReq as u8;
+
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let logical_channel = cursor.try_get_u8()?;
+        let channel_page = cursor.try_get_u8()?;
+        let track_beacon = cursor.try_get_u8()? != 0;
+        let phy_id = PhyId::try_decode(cursor)?;
         Ok(SyncReq {
             logical_channel,
             channel_page,
@@ -1361,34 +1094,16 @@ impl SyncReq {
         })
     }
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
-    }
-
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         buffer.put_u8(self.logical_channel);
         buffer.put_u8(self.channel_page);
         buffer.put_u8(if self.track_beacon { 1 } else { 0 });
         self.phy_id.encode_into(buffer);
     }
+}
 
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x04,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SREQ,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::SyncReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
+impl Request for SyncReq {
+    type Response = srsp::SyncReq;
 }
 
 #[derive(Debug, Clone)]
@@ -1396,38 +1111,23 @@ pub struct SetRxGainReq {
     pub mode: bool,
 }
 
-impl SetRxGainReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let mode = cursor.get_u8() != 0;
-        Ok(SetRxGainReq { mode })
-    }
+impl Command for SetRxGainReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SREQ;
+    const ID: u8 = MACCommandId::SetRxGainReq as u8;
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let mode = cursor.try_get_u8()? != 0;
+        Ok(SetRxGainReq { mode })
     }
 
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         buffer.put_u8(if self.mode { 1 } else { 0 });
     }
+}
 
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x01,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SREQ,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::SetRxGainReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
+impl Request for SetRxGainReq {
+    type Response = srsp::SetRxGainReq;
 }
 
 #[derive(Debug, Clone)]
@@ -1441,16 +1141,19 @@ pub struct WSAsyncReq {
     pub channels: ChannelsBitMap,
 }
 
-impl WSAsyncReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let operation = WiSUNAsyncOperation::try_decode(&mut cursor)?;
-        let frame_type = WiSUNAsyncFrameType::try_decode(&mut cursor)?;
-        let key_source = KeySource::try_decode(&mut cursor)?;
-        let security_level = SecurityLevel::try_decode(&mut cursor)?;
-        let key_id_mode = KeyIdMode::try_decode(&mut cursor)?;
-        let key_index = cursor.get_u8();
-        let channels = ChannelsBitMap::try_decode(&mut cursor)?;
+impl Command for WSAsyncReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SREQ;
+    const ID: u8 = MACCommandId::WSAsyncReq as u8;
+
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let operation = WiSUNAsyncOperation::try_decode(cursor)?;
+        let frame_type = WiSUNAsyncFrameType::try_decode(cursor)?;
+        let key_source = KeySource::try_decode(cursor)?;
+        let security_level = SecurityLevel::try_decode(cursor)?;
+        let key_id_mode = KeyIdMode::try_decode(cursor)?;
+        let key_index = cursor.try_get_u8()?;
+        let channels = ChannelsBitMap::try_decode(cursor)?;
         Ok(WSAsyncReq {
             operation,
             frame_type,
@@ -1462,13 +1165,7 @@ impl WSAsyncReq {
         })
     }
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
-    }
-
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.operation.encode_into(buffer);
         self.frame_type.encode_into(buffer);
         self.key_source.encode_into(buffer);
@@ -1477,88 +1174,48 @@ impl WSAsyncReq {
         buffer.put_u8(self.key_index);
         self.channels.encode_into(buffer);
     }
+}
 
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x26,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SREQ,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::WSAsyncReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
+impl Request for WSAsyncReq {
+    type Response = srsp::WSAsyncReq;
 }
 
 #[derive(Debug, Clone)]
 pub struct FHEnableReq {}
 
-impl FHEnableReq {
-    pub fn try_decode(_: &[u8]) -> Result<Self, Error> {
+impl Command for FHEnableReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SREQ;
+    const ID: u8 = MACCommandId::FHEnableReq as u8;
+
+    fn try_decode_from(_: &mut Cursor<&[u8]>) -> Result<Self, Error> {
         Ok(FHEnableReq {})
     }
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
-    }
+    fn encode_into(&self, _: &mut Vec<u8>) {}
+}
 
-    pub fn encode_into(&self, _: &mut Vec<u8>) {}
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x00,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SREQ,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::FHEnableReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
+impl Request for FHEnableReq {
+    type Response = srsp::FHEnableReq;
 }
 
 #[derive(Debug, Clone)]
 pub struct FHStartReq {}
 
-impl FHStartReq {
-    pub fn try_decode(_: &[u8]) -> Result<Self, Error> {
+impl Command for FHStartReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SREQ;
+    const ID: u8 = MACCommandId::FHStartReq as u8;
+
+    fn try_decode_from(_: &mut Cursor<&[u8]>) -> Result<Self, Error> {
         Ok(FHStartReq {})
     }
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
-    }
+    fn encode_into(&self, _: &mut Vec<u8>) {}
+}
 
-    pub fn encode_into(&self, _: &mut Vec<u8>) {}
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x00,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SREQ,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::FHStartReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
+impl Request for FHStartReq {
+    type Response = srsp::FHStartReq;
 }
 
 #[derive(Debug, Clone)]
@@ -1566,38 +1223,23 @@ pub struct FHGetReq {
     pub attribute_id: FHPIBAttributeId,
 }
 
-impl FHGetReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let attribute_id = FHPIBAttributeId::try_decode(&mut cursor)?;
-        Ok(FHGetReq { attribute_id })
-    }
+impl Command for FHGetReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SREQ;
+    const ID: u8 = MACCommandId::FHGetReq as u8;
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let attribute_id = FHPIBAttributeId::try_decode(cursor)?;
+        Ok(FHGetReq { attribute_id })
     }
 
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.attribute_id.encode_into(buffer);
     }
+}
 
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x02,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SREQ,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::FHGetReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
+impl Request for FHGetReq {
+    type Response = srsp::FHGetReq;
 }
 
 #[derive(Debug, Clone)]
@@ -1606,10 +1248,13 @@ pub struct FHSetReq {
     pub data: Vec<u8>,
 }
 
-impl FHSetReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let attribute_id = FHPIBAttributeId::try_decode(&mut cursor)?;
+impl Command for FHSetReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SREQ;
+    const ID: u8 = MACCommandId::FHSetReq as u8;
+
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let attribute_id = FHPIBAttributeId::try_decode(cursor)?;
 
         let mut data = Vec::new();
         cursor
@@ -1619,30 +1264,12 @@ impl FHSetReq {
         Ok(FHSetReq { attribute_id, data })
     }
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
-    }
-
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.attribute_id.encode_into(buffer);
         buffer.extend(self.data.iter());
     }
+}
 
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x02 + self.data.len() as u8,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SREQ,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::FHSetReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
+impl Request for FHSetReq {
+    type Response = srsp::FHSetReq;
 }
@@ -1,5 +1,5 @@
+use crate::command::Command;
 use crate::error::Error;
-use crate::frame::{CommandCode, MTFrame, MTHeader};
 use crate::types::*;
 use bytes::{Buf, BufMut};
 use std::io::Cursor;
@@ -10,38 +10,19 @@ pub struct Init {
     pub status: Status,
 }
 
-impl Init {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let status = Status::try_decode(&mut cursor)?;
-        Ok(Init { status })
-    }
+impl Command for Init {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SRSP;
+    const ID: u8 = MACCommandId::Init as u8;
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let status = Status::try_decode(cursor)?;
+        Ok(Init { status })
     }
 
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.status.encode_into(buffer);
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x01,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SRSP,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::Init as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -49,38 +30,19 @@ pub struct DataReq {
     pub status: Status,
 }
 
-impl DataReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let status = Status::try_decode(&mut cursor)?;
-        Ok(DataReq { status })
-    }
+impl Command for DataReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SRSP;
+    const ID: u8 = MACCommandId::DataReq as u8;
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let status = Status::try_decode(cursor)?;
+        Ok(DataReq { status })
     }
 
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.status.encode_into(buffer);
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x01,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SRSP,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::DataReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -88,38 +50,19 @@ pub struct PurgeReq {
     pub status: Status,
 }
 
-impl PurgeReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let status = Status::try_decode(&mut cursor)?;
-        Ok(PurgeReq { status })
-    }
+impl Command for PurgeReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SRSP;
+    const ID: u8 = MACCommandId::PurgeReq as u8;
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let status = Status::try_decode(cursor)?;
+        Ok(PurgeReq { status })
     }
 
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.status.encode_into(buffer);
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x01,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SRSP,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::PurgeReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -127,38 +70,19 @@ pub struct AssociateReq {
     pub status: Status,
 }
 
-impl AssociateReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let status = Status::try_decode(&mut cursor)?;
-        Ok(AssociateReq { status })
-    }
+impl Command for AssociateReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SRSP;
+    const ID: u8 = MACCommandId::AssociateReq as u8;
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let status = Status::try_decode(cursor)?;
+        Ok(AssociateReq { status })
     }
 
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.status.encode_into(buffer);
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x01,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SRSP,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::AssociateReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -166,38 +90,19 @@ pub struct AssociateRsp {
     pub status: Status,
 }
 
-impl AssociateRsp {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let status = Status::try_decode(&mut cursor)?;
-        Ok(AssociateRsp { status })
-    }
+impl Command for AssociateRsp {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SRSP;
+    const ID: u8 = MACCommandId::AssociateRsp as u8;
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let status = Status::try_decode(cursor)?;
+        Ok(AssociateRsp { status })
     }
 
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.status.encode_into(buffer);
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x01,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SRSP,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::AssociateRsp as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -205,38 +110,19 @@ pub struct DisassociateReq {
     pub status: Status,
 }
 
-impl DisassociateReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let status = Status::try_decode(&mut cursor)?;
-        Ok(DisassociateReq { status })
-    }
+impl Command for DisassociateReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SRSP;
+    const ID: u8 = MACCommandId::DisassociateReq as u8;
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let status = Status::try_decode(cursor)?;
+        Ok(DisassociateReq { status })
     }
 
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.status.encode_into(buffer);
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x01,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SRSP,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::DisassociateReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -245,10 +131,13 @@ pub struct GetReq {
     pub data: [u8; 16],
 }
 
-impl GetReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let status = Status::try_decode(&mut cursor)?;
+impl Command for GetReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SRSP;
+    const ID: u8 = MACCommandId::GetReq as u8;
+
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let status = Status::try_decode(cursor)?;
 
         let mut data: [u8; 16] = Default::default();
         cursor
@@ -259,32 +148,10 @@ impl GetReq {
         Ok(GetReq { status, data })
     }
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
-    }
-
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.status.encode_into(buffer);
         buffer.extend(self.data.iter().rev());
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x11,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SRSP,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::GetReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -292,38 +159,19 @@ pub struct SetReq {
     pub status: Status,
 }
 
-impl SetReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let status = Status::try_decode(&mut cursor)?;
-        Ok(SetReq { status })
-    }
+impl Command for SetReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SRSP;
+    const ID: u8 = MACCommandId::SetReq as u8;
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let status = Status::try_decode(cursor)?;
+        Ok(SetReq { status })
     }
 
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.status.encode_into(buffer);
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x01,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SRSP,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::SetReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -334,12 +182,15 @@ pub struct SecurityGetReq {
     pub data: Vec<u8>,
 }
 
-impl SecurityGetReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let status = Status::try_decode(&mut cursor)?;
-        let index1 = cursor.get_u16_le();
-        let index2 = cursor.get_u16_le();
+impl Command for SecurityGetReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SRSP;
+    const ID: u8 = MACCommandId::SecurityGetReq as u8;
+
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let status = Status::try_decode(cursor)?;
+        let index1 = cursor.try_get_u16_le()?;
+        let index2 = cursor.try_get_u16_le()?;
 
         let mut data = Vec::new();
         cursor
@@ -354,34 +205,12 @@ impl SecurityGetReq {
         })
     }
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
-    }
-
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.status.encode_into(buffer);
         buffer.put_u16_le(self.index1);
         buffer.put_u16_le(self.index2);
         buffer.extend(self.data.iter());
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x05 + self.data.len() as u8,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SRSP,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::SecurityGetReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -389,38 +218,19 @@ pub struct SecuritySetReq {
     pub status: Status,
 }
 
-impl SecuritySetReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let status = Status::try_decode(&mut cursor)?;
-        Ok(SecuritySetReq { status })
-    }
+impl Command for SecuritySetReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SRSP;
+    const ID: u8 = MACCommandId::SecuritySetReq as u8;
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let status = Status::try_decode(cursor)?;
+        Ok(SecuritySetReq { status })
     }
 
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.status.encode_into(buffer);
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x01,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SRSP,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::SecuritySetReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -428,38 +238,19 @@ pub struct UpdatePANIdReq {
     pub status: Status,
 }
 
-impl UpdatePANIdReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let status = Status::try_decode(&mut cursor)?;
-        Ok(UpdatePANIdReq { status })
-    }
+impl Command for UpdatePANIdReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SRSP;
+    const ID: u8 = MACCommandId::UpdatePANIdReq as u8;
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let status = Status::try_decode(cursor)?;
+        Ok(UpdatePANIdReq { status })
     }
 
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.status.encode_into(buffer);
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x01,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SRSP,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::UpdatePANIdReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -467,38 +258,19 @@ pub struct AddDeviceReq {
     pub status: Status,
 }
 
-impl AddDeviceReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let status = Status::try_decode(&mut cursor)?;
-        Ok(AddDeviceReq { status })
-    }
+impl Command for AddDeviceReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SRSP;
+    const ID: u8 = MACCommandId::AddDeviceReq as u8;
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let status = Status::try_decode(cursor)?;
+        Ok(AddDeviceReq { status })
     }
 
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.status.encode_into(buffer);
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x01,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SRSP,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::AddDeviceReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -506,38 +278,19 @@ pub struct DeleteDeviceReq {
     pub status: Status,
 }
 
-impl DeleteDeviceReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let status = Status::try_decode(&mut cursor)?;
-        Ok(DeleteDeviceReq { status })
-    }
+impl Command for DeleteDeviceReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SRSP;
+    const ID: u8 = MACCommandId::DeleteDeviceReq as u8;
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let status = Status::try_decode(cursor)?;
+        Ok(DeleteDeviceReq { status })
     }
 
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.status.encode_into(buffer);
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x01,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SRSP,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::DeleteDeviceReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -545,38 +298,19 @@ pub struct DeleteAllDevicesReq {
     pub status: Status,
 }
 
-impl DeleteAllDevicesReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let status = Status::try_decode(&mut cursor)?;
-        Ok(DeleteAllDevicesReq { status })
-    }
+impl Command for DeleteAllDevicesReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SRSP;
+    const ID: u8 = MACCommandId::DeleteAllDevicesReq as u8;
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let status = Status::try_decode(cursor)?;
+        Ok(DeleteAllDevicesReq { status })
     }
 
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.status.encode_into(buffer);
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x01,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SRSP,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::DeleteAllDevicesReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -584,38 +318,19 @@ pub struct DeleteKeyReq {
     pub status: Status,
 }
 
-impl DeleteKeyReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let status = Status::try_decode(&mut cursor)?;
-        Ok(DeleteKeyReq { status })
-    }
+impl Command for DeleteKeyReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SRSP;
+    const ID: u8 = MACCommandId::DeleteKeyReq as u8;
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let status = Status::try_decode(cursor)?;
+        Ok(DeleteKeyReq { status })
     }
 
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.status.encode_into(buffer);
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x01,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SRSP,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::DeleteKeyReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -624,43 +339,24 @@ pub struct ReadKeyReq {
     pub frame_counter: u32,
 }
 
-impl ReadKeyReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let status = Status::try_decode(&mut cursor)?;
-        let frame_counter = cursor.get_u32_le();
+impl Command for ReadKeyReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SRSP;
+    const ID: u8 = MACCommandId::ReadKeyReq as u8;
+
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let status = Status::try_decode(cursor)?;
+        let frame_counter = cursor.try_get_u32_le()?;
         Ok(ReadKeyReq {
             status,
             frame_counter,
         })
     }
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
-    }
-
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.status.encode_into(buffer);
         buffer.put_u32_le(self.frame_counter);
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x05,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SRSP,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::ReadKeyReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -668,38 +364,19 @@ pub struct WriteKeyReq {
     pub status: Status,
 }
 
-impl WriteKeyReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let status = Status::try_decode(&mut cursor)?;
-        Ok(WriteKeyReq { status })
-    }
+impl Command for WriteKeyReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SRSP;
+    const ID: u8 = MACCommandId::WriteKeyReq as u8;
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let status = Status::try_decode(cursor)?;
+        Ok(WriteKeyReq { status })
     }
 
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.status.encode_into(buffer);
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x01,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SRSP,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::WriteKeyReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -707,38 +384,19 @@ pub struct OrphanRsp {
     pub status: Status,
 }
 
-impl OrphanRsp {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let status = Status::try_decode(&mut cursor)?;
-        Ok(OrphanRsp { status })
-    }
+impl Command for OrphanRsp {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SRSP;
+    const ID: u8 = MACCommandId::OrphanRsp as u8;
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let status = Status::try_decode(cursor)?;
+        Ok(OrphanRsp { status })
     }
 
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.status.encode_into(buffer);
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x01,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SRSP,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::OrphanRsp as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -746,38 +404,19 @@ pub struct PollReq {
     pub status: Status,
 }
 
-impl PollReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let status = Status::try_decode(&mut cursor)?;
-        Ok(PollReq { status })
-    }
+impl Command for PollReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SRSP;
+    const ID: u8 = MACCommandId::PollReq as u8;
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let status = Status::try_decode(cursor)?;
+        Ok(PollReq { status })
     }
 
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.status.encode_into(buffer);
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x01,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SRSP,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::PollReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -785,38 +424,19 @@ pub struct ResetReq {
     pub status: Status,
 }
 
-impl ResetReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let status = Status::try_decode(&mut cursor)?;
-        Ok(ResetReq { status })
-    }
+impl Command for ResetReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SRSP;
+    const ID: u8 = MACCommandId::ResetReq as u8;
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let status = Status::try_decode(cursor)?;
+        Ok(ResetReq { status })
     }
 
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.status.encode_into(buffer);
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x01,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SRSP,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::ResetReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -824,38 +444,19 @@ pub struct ScanReq {
     pub status: Status,
 }
 
-impl ScanReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let status = Status::try_decode(&mut cursor)?;
-        Ok(ScanReq { status })
-    }
+impl Command for ScanReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SRSP;
+    const ID: u8 = MACCommandId::ScanReq as u8;
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let status = Status::try_decode(cursor)?;
+        Ok(ScanReq { status })
     }
 
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.status.encode_into(buffer);
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x01,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SRSP,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::ScanReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -863,38 +464,19 @@ pub struct StartReq {
     pub status: Status,
 }
 
-impl StartReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let status = Status::try_decode(&mut cursor)?;
-        Ok(StartReq { status })
-    }
+impl Command for StartReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SRSP;
+    const ID: u8 = MACCommandId::StartReq as u8;
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let status = Status::try_decode(cursor)?;
+        Ok(StartReq { status })
     }
 
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.status.encode_into(buffer);
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x01,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SRSP,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::StartReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -902,38 +484,19 @@ pub struct SyncReq {
     pub status: Status,
 }
 
-impl SyncReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let status = Status::try_decode(&mut cursor)?;
-        Ok(SyncReq { status })
-    }
+impl Command for SyncReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SRSP;
+    const ID: u8 = MACCommandId::SyncReq as u8;
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let status = Status::try_decode(cursor)?;
+        Ok(SyncReq { status })
     }
 
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.status.encode_into(buffer);
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x01,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SRSP,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::SyncReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -941,38 +504,19 @@ pub struct SetRxGainReq {
     pub status: Status,
 }
 
-impl SetRxGainReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let status = Status::try_decode(&mut cursor)?;
-        Ok(SetRxGainReq { status })
-    }
+impl Command for SetRxGainReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SRSP;
+    const ID: u8 = MACCommandId::SetRxGainReq as u8;
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let status = Status::try_decode(cursor)?;
+        Ok(SetRxGainReq { status })
     }
 
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.status.encode_into(buffer);
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x01,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SRSP,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::SetRxGainReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -980,38 +524,19 @@ pub struct WSAsyncReq {
     pub status: Status,
 }
 
-impl WSAsyncReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let status = Status::try_decode(&mut cursor)?;
-        Ok(WSAsyncReq { status })
-    }
+impl Command for WSAsyncReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SRSP;
+    const ID: u8 = MACCommandId::WSAsyncReq as u8;
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let status = Status::try_decode(cursor)?;
+        Ok(WSAsyncReq { status })
     }
 
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.status.encode_into(buffer);
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x01,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SRSP,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::WSAsyncReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -1019,38 +544,19 @@ pub struct FHEnableReq {
     pub status: Status,
 }
 
-impl FHEnableReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let status = Status::try_decode(&mut cursor)?;
-        Ok(FHEnableReq { status })
-    }
+impl Command for FHEnableReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SRSP;
+    const ID: u8 = MACCommandId::FHEnableReq as u8;
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let status = Status::try_decode(cursor)?;
+        Ok(FHEnableReq { status })
     }
 
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.status.encode_into(buffer);
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x01,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SRSP,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::FHEnableReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -1058,38 +564,19 @@ pub struct FHStartReq {
     pub status: Status,
 }
 
-impl FHStartReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let status = Status::try_decode(&mut cursor)?;
-        Ok(FHStartReq { status })
-    }
+impl Command for FHStartReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SRSP;
+    const ID: u8 = MACCommandId::FHStartReq as u8;
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let status = Status::try_decode(cursor)?;
+        Ok(FHStartReq { status })
     }
 
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.status.encode_into(buffer);
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x01,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SRSP,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::FHStartReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -1098,10 +585,13 @@ pub struct FHGetReq {
     pub data: Vec<u8>,
 }
 
-impl FHGetReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let status = Status::try_decode(&mut cursor)?;
+impl Command for FHGetReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SRSP;
+    const ID: u8 = MACCommandId::FHGetReq as u8;
+
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let status = Status::try_decode(cursor)?;
 
         let mut data = Vec::new();
         cursor
@@ -1111,32 +601,10 @@ impl FHGetReq {
         Ok(FHGetReq { status, data })
     }
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
-    }
-
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.status.encode_into(buffer);
         buffer.extend(self.data.iter());
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x01 + self.data.len() as u8,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SRSP,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::FHGetReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -1144,36 +612,17 @@ pub struct FHSetReq {
     pub status: Status,
 }
 
-impl FHSetReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let status = Status::try_decode(&mut cursor)?;
-        Ok(FHSetReq { status })
-    }
+impl Command for FHSetReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::MAC;
+    const CMD_TYPE: CommandType = CommandType::SRSP;
+    const ID: u8 = MACCommandId::FHSetReq as u8;
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let status = Status::try_decode(cursor)?;
+        Ok(FHSetReq { status })
     }
 
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.status.encode_into(buffer);
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x01,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SRSP,
-                    subsystem: MTSubsystem::MAC,
-                    id: MACCommandId::FHSetReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
@@ -3,8 +3,11 @@ pub mod rpc;
 pub mod sys;
 pub mod util;
 
+use crate::command::Command;
 use crate::error::Error;
 use crate::types::{CommandType, MTSubsystem};
+use bytes::Buf;
+use std::io::Cursor;
 
 #[derive(Debug)]
 #[allow(non_camel_case_types)]
@@ -135,12 +138,36 @@ impl MTFramePayload {
         cmd_type: &CommandType,
         id: u8,
         buffer: &[u8],
+    ) -> Result<Self, Error> {
+        Self::try_decode_from(subsystem, cmd_type, id, &mut Cursor::new(buffer))
+    }
+
+    // Same as try_decode, but rejects bytes left over after the payload.
+    pub fn try_decode_strict(
+        subsystem: &MTSubsystem,
+        cmd_type: &CommandType,
+        id: u8,
+        buffer: &[u8],
+    ) -> Result<Self, Error> {
+        let mut cursor = Cursor::new(buffer);
+        let payload = Self::try_decode_from(subsystem, cmd_type, id, &mut cursor)?;
+        match cursor.remaining() {
+            0 => Ok(payload),
+            remaining => Err(Error::TrailingBytes(remaining)),
+        }
+    }
+
+    fn try_decode_from(
+        subsystem: &MTSubsystem,
+        cmd_type: &CommandType,
+        id: u8,
+        cursor: &mut Cursor<&[u8]>,
     ) -> Result<Self, Error> {
         match subsystem {
-            MTSubsystem::MAC => mac::try_decode(cmd_type, id, buffer),
-            MTSubsystem::RPC => rpc::try_decode(cmd_type, id, buffer),
-            MTSubsystem::SYS => sys::try_decode(cmd_type, id, buffer),
-            MTSubsystem::UTIL => util::try_decode(cmd_type, id, buffer),
+            MTSubsystem::MAC => mac::try_decode(cmd_type, id, cursor),
+            MTSubsystem::RPC => rpc::try_decode(cmd_type, id, cursor),
+            MTSubsystem::SYS => sys::try_decode(cmd_type, id, cursor),
+            MTSubsystem::UTIL => util::try_decode(cmd_type, id, cursor),
         }
     }
 
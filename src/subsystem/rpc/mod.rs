@@ -1,11 +1,16 @@
+use crate::command::Command;
 use crate::error::Error;
-use crate::frame::{CommandCode, MTFrame, MTHeader};
+use crate::frame::CommandCode;
 use crate::subsystem::MTFramePayload;
 use crate::types::{CommandType, ErrorCode, MTSubsystem, RPCCommandId};
 use num_traits::FromPrimitive;
 use std::io::Cursor;
 
-pub fn try_decode(cmd_type: &CommandType, id: u8, buffer: &[u8]) -> Result<MTFramePayload, Error> {
+pub fn try_decode(
+    cmd_type: &CommandType,
+    id: u8,
+    cursor: &mut Cursor<&[u8]>,
+) -> Result<MTFramePayload, Error> {
     use MTFramePayload::*;
 
     let id = FromPrimitive::from_u8(id).ok_or(Error::InvalidCommandId(id))?;
@@ -16,7 +21,7 @@ pub fn try_decode(cmd_type: &CommandType, id: u8, buffer: &[u8]) -> Result<MTFra
         CommandType::AREQ => Err(Error::NotImplemented),
         CommandType::SRSP => match id {
             RPCCommandId::MTCommandError => {
-                MTCommandError::try_decode(buffer).map(|x| RPC_MTCommandError(x))
+                MTCommandError::try_decode_from(cursor).map(|x| RPC_MTCommandError(x))
             }
         },
     }
@@ -28,41 +33,22 @@ pub struct MTCommandError {
     pub command: CommandCode,
 }
 
-impl MTCommandError {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let error_code = ErrorCode::try_decode(&mut cursor)?;
-        let command = CommandCode::try_decode(&mut cursor)?;
+impl Command for MTCommandError {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::RPC;
+    const CMD_TYPE: CommandType = CommandType::SRSP;
+    const ID: u8 = RPCCommandId::MTCommandError as u8;
+
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let error_code = ErrorCode::try_decode(cursor)?;
+        let command = CommandCode::try_decode(cursor)?;
         Ok(MTCommandError {
             error_code,
             command,
         })
     }
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
-    }
-
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.error_code.encode_into(buffer);
         self.command.encode_into(buffer);
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x03,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SRSP,
-                    subsystem: MTSubsystem::RPC,
-                    id: RPCCommandId::MTCommandError as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
@@ -1,5 +1,5 @@
+use crate::command::Command;
 use crate::error::Error;
-use crate::frame::{CommandCode, MTFrame, MTHeader};
 use crate::types::*;
 use bytes::{Buf, BufMut};
 use std::io::Cursor;
@@ -9,38 +9,19 @@ pub struct ResetReq {
     pub reset_type: ResetType,
 }
 
-impl ResetReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let reset_type = ResetType::try_decode(&mut cursor)?;
-        Ok(ResetReq { reset_type })
-    }
+impl Command for ResetReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::SYS;
+    const CMD_TYPE: CommandType = CommandType::AREQ;
+    const ID: u8 = SYSCommandId::ResetReq as u8;
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let reset_type = ResetType::try_decode(cursor)?;
+        Ok(ResetReq { reset_type })
     }
 
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.reset_type.encode_into(buffer);
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x01,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::AREQ,
-                    subsystem: MTSubsystem::SYS,
-                    id: SYSCommandId::ResetReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -53,15 +34,18 @@ pub struct ResetInd {
     pub maint: u8,
 }
 
-impl ResetInd {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let reason = ResetReason::try_decode(&mut cursor)?;
-        let transport = TransportProtocolRevision::try_decode(&mut cursor)?;
-        let product = ProductIdCode::try_decode(&mut cursor)?;
-        let major = cursor.get_u8();
-        let minor = cursor.get_u8();
-        let maint = cursor.get_u8();
+impl Command for ResetInd {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::SYS;
+    const CMD_TYPE: CommandType = CommandType::AREQ;
+    const ID: u8 = SYSCommandId::ResetInd as u8;
+
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let reason = ResetReason::try_decode(cursor)?;
+        let transport = TransportProtocolRevision::try_decode(cursor)?;
+        let product = ProductIdCode::try_decode(cursor)?;
+        let major = cursor.try_get_u8()?;
+        let minor = cursor.try_get_u8()?;
+        let maint = cursor.try_get_u8()?;
         Ok(ResetInd {
             reason,
             transport,
@@ -72,13 +56,7 @@ impl ResetInd {
         })
     }
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
-    }
-
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.reason.encode_into(buffer);
         self.transport.encode_into(buffer);
         self.product.encode_into(buffer);
@@ -86,20 +64,4 @@ impl ResetInd {
         buffer.put_u8(self.minor);
         buffer.put_u8(self.maint);
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x06,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::AREQ,
-                    subsystem: MTSubsystem::SYS,
-                    id: SYSCommandId::ResetInd as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
@@ -1,5 +1,6 @@
+use crate::command::{Command, Request};
 use crate::error::Error;
-use crate::frame::{CommandCode, MTFrame, MTHeader};
+use crate::subsystem::sys::srsp;
 use crate::types::{CommandType, MTSubsystem, SYSCommandId};
 use bytes::{Buf, BufMut};
 use std::io::Cursor;
@@ -8,67 +9,39 @@ use std::io::Read;
 #[derive(Debug, Clone)]
 pub struct PingReq {}
 
-impl PingReq {
-    pub fn try_decode(_: &[u8]) -> Result<Self, Error> {
+impl Command for PingReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::SYS;
+    const CMD_TYPE: CommandType = CommandType::SREQ;
+    const ID: u8 = SYSCommandId::PingReq as u8;
+
+    fn try_decode_from(_: &mut Cursor<&[u8]>) -> Result<Self, Error> {
         Ok(PingReq {})
     }
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
-    }
+    fn encode_into(&self, _: &mut Vec<u8>) {}
+}
 
-    pub fn encode_into(&self, _: &mut Vec<u8>) {}
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x00,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SREQ,
-                    subsystem: MTSubsystem::SYS,
-                    id: SYSCommandId::PingReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
+impl Request for PingReq {
+    type Response = srsp::PingReq;
 }
 
 #[derive(Debug, Clone)]
 pub struct VersionReq {}
 
-impl VersionReq {
-    pub fn try_decode(_: &[u8]) -> Result<Self, Error> {
+impl Command for VersionReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::SYS;
+    const CMD_TYPE: CommandType = CommandType::SREQ;
+    const ID: u8 = SYSCommandId::VersionReq as u8;
+
+    fn try_decode_from(_: &mut Cursor<&[u8]>) -> Result<Self, Error> {
         Ok(VersionReq {})
     }
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
-    }
+    fn encode_into(&self, _: &mut Vec<u8>) {}
+}
 
-    pub fn encode_into(&self, _: &mut Vec<u8>) {}
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x00,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SREQ,
-                    subsystem: MTSubsystem::SYS,
-                    id: SYSCommandId::VersionReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
+impl Request for VersionReq {
+    type Response = srsp::VersionReq;
 }
 
 #[derive(Debug, Clone)]
@@ -79,13 +52,16 @@ pub struct NVCreateReq {
     pub length: u32,
 }
 
-impl NVCreateReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let sys_id = cursor.get_u8();
-        let item_id = cursor.get_u16_le();
-        let sub_id = cursor.get_u16_le();
-        let length = cursor.get_u32_le();
+impl Command for NVCreateReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::SYS;
+    const CMD_TYPE: CommandType = CommandType::SREQ;
+    const ID: u8 = SYSCommandId::NVCreateReq as u8;
+
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let sys_id = cursor.try_get_u8()?;
+        let item_id = cursor.try_get_u16_le()?;
+        let sub_id = cursor.try_get_u16_le()?;
+        let length = cursor.try_get_u32_le()?;
         Ok(NVCreateReq {
             sys_id,
             item_id,
@@ -94,34 +70,16 @@ impl NVCreateReq {
         })
     }
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
-    }
-
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         buffer.put_u8(self.sys_id);
         buffer.put_u16_le(self.item_id);
         buffer.put_u16_le(self.sub_id);
         buffer.put_u32_le(self.length);
     }
+}
 
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x09,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SREQ,
-                    subsystem: MTSubsystem::SYS,
-                    id: SYSCommandId::NVCreateReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
+impl Request for NVCreateReq {
+    type Response = srsp::NVCreateReq;
 }
 
 #[derive(Debug, Clone)]
@@ -131,12 +89,15 @@ pub struct NVDeleteReq {
     pub sub_id: u16,
 }
 
-impl NVDeleteReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let sys_id = cursor.get_u8();
-        let item_id = cursor.get_u16_le();
-        let sub_id = cursor.get_u16_le();
+impl Command for NVDeleteReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::SYS;
+    const CMD_TYPE: CommandType = CommandType::SREQ;
+    const ID: u8 = SYSCommandId::NVDeleteReq as u8;
+
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let sys_id = cursor.try_get_u8()?;
+        let item_id = cursor.try_get_u16_le()?;
+        let sub_id = cursor.try_get_u16_le()?;
         Ok(NVDeleteReq {
             sys_id,
             item_id,
@@ -144,33 +105,15 @@ impl NVDeleteReq {
         })
     }
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
-    }
-
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         buffer.put_u8(self.sys_id);
         buffer.put_u16_le(self.item_id);
         buffer.put_u16_le(self.sub_id);
     }
+}
 
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x05,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SREQ,
-                    subsystem: MTSubsystem::SYS,
-                    id: SYSCommandId::NVDeleteReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
+impl Request for NVDeleteReq {
+    type Response = srsp::NVDeleteReq;
 }
 
 #[derive(Debug, Clone)]
@@ -180,12 +123,15 @@ pub struct NVLengthReq {
     pub sub_id: u16,
 }
 
-impl NVLengthReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let sys_id = cursor.get_u8();
-        let item_id = cursor.get_u16_le();
-        let sub_id = cursor.get_u16_le();
+impl Command for NVLengthReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::SYS;
+    const CMD_TYPE: CommandType = CommandType::SREQ;
+    const ID: u8 = SYSCommandId::NVLengthReq as u8;
+
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let sys_id = cursor.try_get_u8()?;
+        let item_id = cursor.try_get_u16_le()?;
+        let sub_id = cursor.try_get_u16_le()?;
         Ok(NVLengthReq {
             sys_id,
             item_id,
@@ -193,33 +139,15 @@ impl NVLengthReq {
         })
     }
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
-    }
-
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         buffer.put_u8(self.sys_id);
         buffer.put_u16_le(self.item_id);
         buffer.put_u16_le(self.sub_id);
     }
+}
 
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x05,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SREQ,
-                    subsystem: MTSubsystem::SYS,
-                    id: SYSCommandId::NVLengthReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
+impl Request for NVLengthReq {
+    type Response = srsp::NVLengthReq;
 }
 
 #[derive(Debug, Clone)]
@@ -231,14 +159,17 @@ pub struct NVReadReq {
     pub length: u8,
 }
 
-impl NVReadReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let sys_id = cursor.get_u8();
-        let item_id = cursor.get_u16_le();
-        let sub_id = cursor.get_u16_le();
-        let offset = cursor.get_u16_le();
-        let length = cursor.get_u8();
+impl Command for NVReadReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::SYS;
+    const CMD_TYPE: CommandType = CommandType::SREQ;
+    const ID: u8 = SYSCommandId::NVReadReq as u8;
+
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let sys_id = cursor.try_get_u8()?;
+        let item_id = cursor.try_get_u16_le()?;
+        let sub_id = cursor.try_get_u16_le()?;
+        let offset = cursor.try_get_u16_le()?;
+        let length = cursor.try_get_u8()?;
         Ok(NVReadReq {
             sys_id,
             item_id,
@@ -248,35 +179,17 @@ impl NVReadReq {
         })
     }
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
-    }
-
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         buffer.put_u8(self.sys_id);
         buffer.put_u16_le(self.item_id);
         buffer.put_u16_le(self.sub_id);
         buffer.put_u16_le(self.offset);
         buffer.put_u8(self.length);
     }
+}
 
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x08,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SREQ,
-                    subsystem: MTSubsystem::SYS,
-                    id: SYSCommandId::NVReadReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
+impl Request for NVReadReq {
+    type Response = srsp::NVReadReq;
 }
 
 #[derive(Debug, Clone)]
@@ -289,14 +202,17 @@ pub struct NVWriteReq {
     pub data: Vec<u8>,
 }
 
-impl NVWriteReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let sys_id = cursor.get_u8();
-        let item_id = cursor.get_u16_le();
-        let sub_id = cursor.get_u16_le();
-        let offset = cursor.get_u16_le();
-        let length = cursor.get_u8();
+impl Command for NVWriteReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::SYS;
+    const CMD_TYPE: CommandType = CommandType::SREQ;
+    const ID: u8 = SYSCommandId::NVWriteReq as u8;
+
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let sys_id = cursor.try_get_u8()?;
+        let item_id = cursor.try_get_u16_le()?;
+        let sub_id = cursor.try_get_u16_le()?;
+        let offset = cursor.try_get_u16_le()?;
+        let length = cursor.try_get_u8()?;
 
         let mut data = Vec::new();
         cursor
@@ -313,13 +229,7 @@ impl NVWriteReq {
         })
     }
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
-    }
-
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         buffer.put_u8(self.sys_id);
         buffer.put_u16_le(self.item_id);
         buffer.put_u16_le(self.sub_id);
@@ -327,22 +237,10 @@ impl NVWriteReq {
         buffer.put_u8(self.length);
         buffer.extend(self.data.iter());
     }
+}
 
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x08 + self.data.len() as u8,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SREQ,
-                    subsystem: MTSubsystem::SYS,
-                    id: SYSCommandId::NVWriteReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
+impl Request for NVWriteReq {
+    type Response = srsp::NVWriteReq;
 }
 
 #[derive(Debug, Clone)]
@@ -354,13 +252,16 @@ pub struct NVUpdateReq {
     pub data: Vec<u8>,
 }
 
-impl NVUpdateReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let sys_id = cursor.get_u8();
-        let item_id = cursor.get_u16_le();
-        let sub_id = cursor.get_u16_le();
-        let length = cursor.get_u8();
+impl Command for NVUpdateReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::SYS;
+    const CMD_TYPE: CommandType = CommandType::SREQ;
+    const ID: u8 = SYSCommandId::NVUpdateReq as u8;
+
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let sys_id = cursor.try_get_u8()?;
+        let item_id = cursor.try_get_u16_le()?;
+        let sub_id = cursor.try_get_u16_le()?;
+        let length = cursor.try_get_u8()?;
 
         let mut data = Vec::new();
         cursor
@@ -376,35 +277,17 @@ impl NVUpdateReq {
         })
     }
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
-    }
-
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         buffer.put_u8(self.sys_id);
         buffer.put_u16_le(self.item_id);
         buffer.put_u16_le(self.sub_id);
         buffer.put_u8(self.length);
         buffer.extend(self.data.iter());
     }
+}
 
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x06 + self.data.len() as u8,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SREQ,
-                    subsystem: MTSubsystem::SYS,
-                    id: SYSCommandId::NVUpdateReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
+impl Request for NVUpdateReq {
+    type Response = srsp::NVUpdateReq;
 }
 
 #[derive(Debug, Clone)]
@@ -412,36 +295,21 @@ pub struct NVCompactReq {
     pub threshold: u16,
 }
 
-impl NVCompactReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let threshold = cursor.get_u16_le();
-        Ok(NVCompactReq { threshold })
-    }
+impl Command for NVCompactReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::SYS;
+    const CMD_TYPE: CommandType = CommandType::SREQ;
+    const ID: u8 = SYSCommandId::NVCompactReq as u8;
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let threshold = cursor.try_get_u16_le()?;
+        Ok(NVCompactReq { threshold })
     }
 
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         buffer.put_u16_le(self.threshold);
     }
+}
 
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x02,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SREQ,
-                    subsystem: MTSubsystem::SYS,
-                    id: SYSCommandId::NVCompactReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
+impl Request for NVCompactReq {
+    type Response = srsp::NVCompactReq;
 }
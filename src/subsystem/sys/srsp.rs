@@ -1,5 +1,5 @@
+use crate::command::Command;
 use crate::error::Error;
-use crate::frame::{CommandCode, MTFrame, MTHeader};
 use crate::types::*;
 use bytes::{Buf, BufMut};
 use std::io::Cursor;
@@ -10,38 +10,19 @@ pub struct PingReq {
     pub capabilities: u16,
 }
 
-impl PingReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let capabilities = cursor.get_u16_le();
-        Ok(PingReq { capabilities })
-    }
+impl Command for PingReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::SYS;
+    const CMD_TYPE: CommandType = CommandType::SRSP;
+    const ID: u8 = SYSCommandId::PingReq as u8;
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let capabilities = cursor.try_get_u16_le()?;
+        Ok(PingReq { capabilities })
     }
 
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         buffer.put_u16_le(self.capabilities);
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x01,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SRSP,
-                    subsystem: MTSubsystem::SYS,
-                    id: SYSCommandId::PingReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -53,14 +34,17 @@ pub struct VersionReq {
     pub maint: u8,
 }
 
-impl VersionReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let transport = TransportProtocolRevision::try_decode(&mut cursor)?;
-        let product = ProductIdCode::try_decode(&mut cursor)?;
-        let major = cursor.get_u8();
-        let minor = cursor.get_u8();
-        let maint = cursor.get_u8();
+impl Command for VersionReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::SYS;
+    const CMD_TYPE: CommandType = CommandType::SRSP;
+    const ID: u8 = SYSCommandId::VersionReq as u8;
+
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let transport = TransportProtocolRevision::try_decode(cursor)?;
+        let product = ProductIdCode::try_decode(cursor)?;
+        let major = cursor.try_get_u8()?;
+        let minor = cursor.try_get_u8()?;
+        let maint = cursor.try_get_u8()?;
         Ok(VersionReq {
             transport,
             product,
@@ -70,35 +54,13 @@ impl VersionReq {
         })
     }
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
-    }
-
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.transport.encode_into(buffer);
         self.product.encode_into(buffer);
         buffer.put_u8(self.major);
         buffer.put_u8(self.minor);
         buffer.put_u8(self.maint);
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x05,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SRSP,
-                    subsystem: MTSubsystem::SYS,
-                    id: SYSCommandId::VersionReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -106,38 +68,19 @@ pub struct NVCreateReq {
     pub status: Status,
 }
 
-impl NVCreateReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let status = Status::try_decode(&mut cursor)?;
-        Ok(NVCreateReq { status })
-    }
+impl Command for NVCreateReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::SYS;
+    const CMD_TYPE: CommandType = CommandType::SRSP;
+    const ID: u8 = SYSCommandId::NVCreateReq as u8;
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let status = Status::try_decode(cursor)?;
+        Ok(NVCreateReq { status })
     }
 
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.status.encode_into(buffer);
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x01,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SRSP,
-                    subsystem: MTSubsystem::SYS,
-                    id: SYSCommandId::NVCreateReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -145,38 +88,19 @@ pub struct NVDeleteReq {
     pub status: Status,
 }
 
-impl NVDeleteReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let status = Status::try_decode(&mut cursor)?;
-        Ok(NVDeleteReq { status })
-    }
+impl Command for NVDeleteReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::SYS;
+    const CMD_TYPE: CommandType = CommandType::SRSP;
+    const ID: u8 = SYSCommandId::NVDeleteReq as u8;
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let status = Status::try_decode(cursor)?;
+        Ok(NVDeleteReq { status })
     }
 
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.status.encode_into(buffer);
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x01,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SRSP,
-                    subsystem: MTSubsystem::SYS,
-                    id: SYSCommandId::NVDeleteReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -184,38 +108,19 @@ pub struct NVLengthReq {
     pub length: u32,
 }
 
-impl NVLengthReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let length = cursor.get_u32_le();
-        Ok(NVLengthReq { length })
-    }
+impl Command for NVLengthReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::SYS;
+    const CMD_TYPE: CommandType = CommandType::SRSP;
+    const ID: u8 = SYSCommandId::NVLengthReq as u8;
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let length = cursor.try_get_u32_le()?;
+        Ok(NVLengthReq { length })
     }
 
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         buffer.put_u32_le(self.length);
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x04,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SRSP,
-                    subsystem: MTSubsystem::SYS,
-                    id: SYSCommandId::NVLengthReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -225,11 +130,14 @@ pub struct NVReadReq {
     pub data: Vec<u8>,
 }
 
-impl NVReadReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let status = Status::try_decode(&mut cursor)?;
-        let length = cursor.get_u8();
+impl Command for NVReadReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::SYS;
+    const CMD_TYPE: CommandType = CommandType::SRSP;
+    const ID: u8 = SYSCommandId::NVReadReq as u8;
+
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let status = Status::try_decode(cursor)?;
+        let length = cursor.try_get_u8()?;
 
         let mut data = Vec::new();
         cursor
@@ -243,33 +151,11 @@ impl NVReadReq {
         })
     }
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
-    }
-
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.status.encode_into(buffer);
         buffer.put_u8(self.length);
         buffer.extend(self.data.iter());
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x02 + self.data.len() as u8,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SRSP,
-                    subsystem: MTSubsystem::SYS,
-                    id: SYSCommandId::NVReadReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -277,38 +163,19 @@ pub struct NVWriteReq {
     pub status: Status,
 }
 
-impl NVWriteReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let status = Status::try_decode(&mut cursor)?;
-        Ok(NVWriteReq { status })
-    }
+impl Command for NVWriteReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::SYS;
+    const CMD_TYPE: CommandType = CommandType::SRSP;
+    const ID: u8 = SYSCommandId::NVWriteReq as u8;
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let status = Status::try_decode(cursor)?;
+        Ok(NVWriteReq { status })
     }
 
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.status.encode_into(buffer);
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x01,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SRSP,
-                    subsystem: MTSubsystem::SYS,
-                    id: SYSCommandId::NVWriteReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -316,38 +183,19 @@ pub struct NVUpdateReq {
     pub status: Status,
 }
 
-impl NVUpdateReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let status = Status::try_decode(&mut cursor)?;
-        Ok(NVUpdateReq { status })
-    }
+impl Command for NVUpdateReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::SYS;
+    const CMD_TYPE: CommandType = CommandType::SRSP;
+    const ID: u8 = SYSCommandId::NVUpdateReq as u8;
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let status = Status::try_decode(cursor)?;
+        Ok(NVUpdateReq { status })
     }
 
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.status.encode_into(buffer);
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x01,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SRSP,
-                    subsystem: MTSubsystem::SYS,
-                    id: SYSCommandId::NVUpdateReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -355,36 +203,17 @@ pub struct NVCompactReq {
     pub status: Status,
 }
 
-impl NVCompactReq {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let status = Status::try_decode(&mut cursor)?;
-        Ok(NVCompactReq { status })
-    }
+impl Command for NVCompactReq {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::SYS;
+    const CMD_TYPE: CommandType = CommandType::SRSP;
+    const ID: u8 = SYSCommandId::NVCompactReq as u8;
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let status = Status::try_decode(cursor)?;
+        Ok(NVCompactReq { status })
     }
 
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.status.encode_into(buffer);
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x01,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SRSP,
-                    subsystem: MTSubsystem::SYS,
-                    id: SYSCommandId::NVCompactReq as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
@@ -1,5 +1,5 @@
+use crate::command::Command;
 use crate::error::Error;
-use crate::frame::{CommandCode, MTFrame, MTHeader};
 use crate::types::{CommandType, MTSubsystem, UTILCommandId};
 use bytes::{Buf, BufMut};
 use std::io::Cursor;
@@ -12,11 +12,14 @@ pub struct Loopback {
     pub data: Vec<u8>,
 }
 
-impl Loopback {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let repeats = cursor.get_u8();
-        let interval = cursor.get_u32_le();
+impl Command for Loopback {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::UTIL;
+    const CMD_TYPE: CommandType = CommandType::AREQ;
+    const ID: u8 = UTILCommandId::Loopback as u8;
+
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let repeats = cursor.try_get_u8()?;
+        let interval = cursor.try_get_u32_le()?;
 
         let mut data = Vec::new();
         cursor
@@ -30,31 +33,9 @@ impl Loopback {
         })
     }
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
-    }
-
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         buffer.put_u8(self.repeats);
         buffer.put_u32_le(self.interval);
         buffer.extend(self.data.iter());
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x05 + self.data.len() as u8,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::AREQ,
-                    subsystem: MTSubsystem::UTIL,
-                    id: UTILCommandId::Loopback as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
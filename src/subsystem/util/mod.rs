@@ -2,12 +2,18 @@ pub mod areq;
 pub mod sreq;
 pub mod srsp;
 
+use crate::command::Command;
 use crate::error::Error;
 use crate::subsystem::MTFramePayload;
 use crate::types::CommandType;
 use num_traits::FromPrimitive;
+use std::io::Cursor;
 
-pub fn try_decode(cmd_type: &CommandType, id: u8, buffer: &[u8]) -> Result<MTFramePayload, Error> {
+pub fn try_decode(
+    cmd_type: &CommandType,
+    id: u8,
+    cursor: &mut Cursor<&[u8]>,
+) -> Result<MTFramePayload, Error> {
     use crate::types::UTILCommandId::*;
     use MTFramePayload::*;
 
@@ -17,23 +23,27 @@ pub fn try_decode(cmd_type: &CommandType, id: u8, buffer: &[u8]) -> Result<MTFra
         CommandType::POLL => Err(Error::NotImplemented),
         CommandType::SREQ => match id {
             CallbackSubCmd => {
-                sreq::CallbackSubCmd::try_decode(buffer).map(|x| UTIL_CallbackSubCmd_SREQ(x))
+                sreq::CallbackSubCmd::try_decode_from(cursor).map(|x| UTIL_CallbackSubCmd_SREQ(x))
             }
-            GetExtAddr => sreq::GetExtAddr::try_decode(buffer).map(|x| UTIL_GetExtAddr_SREQ(x)),
-            Loopback => sreq::Loopback::try_decode(buffer).map(|x| UTIL_Loopback_SREQ(x)),
-            Random => sreq::Random::try_decode(buffer).map(|x| UTIL_Random_SREQ(x)),
+            GetExtAddr => {
+                sreq::GetExtAddr::try_decode_from(cursor).map(|x| UTIL_GetExtAddr_SREQ(x))
+            }
+            Loopback => sreq::Loopback::try_decode_from(cursor).map(|x| UTIL_Loopback_SREQ(x)),
+            Random => sreq::Random::try_decode_from(cursor).map(|x| UTIL_Random_SREQ(x)),
         },
         CommandType::AREQ => match id {
-            Loopback => areq::Loopback::try_decode(buffer).map(|x| UTIL_Loopback_AREQ(x)),
+            Loopback => areq::Loopback::try_decode_from(cursor).map(|x| UTIL_Loopback_AREQ(x)),
             _ => Err(Error::NotImplemented),
         },
         CommandType::SRSP => match id {
             CallbackSubCmd => {
-                srsp::CallbackSubCmd::try_decode(buffer).map(|x| UTIL_CallbackSubCmd_SRSP(x))
+                srsp::CallbackSubCmd::try_decode_from(cursor).map(|x| UTIL_CallbackSubCmd_SRSP(x))
+            }
+            GetExtAddr => {
+                srsp::GetExtAddr::try_decode_from(cursor).map(|x| UTIL_GetExtAddr_SRSP(x))
             }
-            GetExtAddr => srsp::GetExtAddr::try_decode(buffer).map(|x| UTIL_GetExtAddr_SRSP(x)),
-            Loopback => srsp::Loopback::try_decode(buffer).map(|x| UTIL_Loopback_SRSP(x)),
-            Random => srsp::Random::try_decode(buffer).map(|x| UTIL_Random_SRSP(x)),
+            Loopback => srsp::Loopback::try_decode_from(cursor).map(|x| UTIL_Loopback_SRSP(x)),
+            Random => srsp::Random::try_decode_from(cursor).map(|x| UTIL_Random_SRSP(x)),
         },
     }
 }
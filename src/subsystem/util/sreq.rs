@@ -1,5 +1,6 @@
+use crate::command::{Command, Request};
 use crate::error::Error;
-use crate::frame::{CommandCode, MTFrame, MTHeader};
+use crate::subsystem::util::srsp;
 use crate::types::*;
 use bytes::{Buf, BufMut};
 use std::io::Cursor;
@@ -11,43 +12,28 @@ pub struct CallbackSubCmd {
     pub enables: u32,
 }
 
-impl CallbackSubCmd {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let subsystem_id = SubsystemId::try_decode(&mut cursor)?;
-        let enables = cursor.get_u32_le();
+impl Command for CallbackSubCmd {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::UTIL;
+    const CMD_TYPE: CommandType = CommandType::SREQ;
+    const ID: u8 = UTILCommandId::CallbackSubCmd as u8;
+
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let subsystem_id = SubsystemId::try_decode(cursor)?;
+        let enables = cursor.try_get_u32_le()?;
         Ok(CallbackSubCmd {
             subsystem_id,
             enables,
         })
     }
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
-    }
-
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.subsystem_id.encode_into(buffer);
         buffer.put_u32_le(self.enables);
     }
+}
 
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x05,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SREQ,
-                    subsystem: MTSubsystem::UTIL,
-                    id: UTILCommandId::CallbackSubCmd as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
+impl Request for CallbackSubCmd {
+    type Response = srsp::CallbackSubCmd;
 }
 
 #[derive(Debug, Clone)]
@@ -55,38 +41,23 @@ pub struct GetExtAddr {
     pub address_type: ExtendedAddressType,
 }
 
-impl GetExtAddr {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let address_type = ExtendedAddressType::try_decode(&mut cursor)?;
-        Ok(GetExtAddr { address_type })
-    }
+impl Command for GetExtAddr {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::UTIL;
+    const CMD_TYPE: CommandType = CommandType::SREQ;
+    const ID: u8 = UTILCommandId::GetExtAddr as u8;
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let address_type = ExtendedAddressType::try_decode(cursor)?;
+        Ok(GetExtAddr { address_type })
     }
 
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.address_type.encode_into(buffer);
     }
+}
 
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x01,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SREQ,
-                    subsystem: MTSubsystem::UTIL,
-                    id: UTILCommandId::GetExtAddr as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
+impl Request for GetExtAddr {
+    type Response = srsp::GetExtAddr;
 }
 
 #[derive(Debug, Clone)]
@@ -96,11 +67,14 @@ pub struct Loopback {
     pub data: Vec<u8>,
 }
 
-impl Loopback {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let repeats = cursor.get_u8();
-        let interval = cursor.get_u32_le();
+impl Command for Loopback {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::UTIL;
+    const CMD_TYPE: CommandType = CommandType::SREQ;
+    const ID: u8 = UTILCommandId::Loopback as u8;
+
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let repeats = cursor.try_get_u8()?;
+        let interval = cursor.try_get_u32_le()?;
 
         let mut data = Vec::new();
         cursor
@@ -114,64 +88,32 @@ impl Loopback {
         })
     }
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
-    }
-
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         buffer.put_u8(self.repeats);
         buffer.put_u32_le(self.interval);
         buffer.extend(self.data.iter());
     }
+}
 
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x05 + self.data.len() as u8,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SREQ,
-                    subsystem: MTSubsystem::UTIL,
-                    id: UTILCommandId::Loopback as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
+impl Request for Loopback {
+    type Response = srsp::Loopback;
 }
 
 #[derive(Debug, Clone)]
 pub struct Random {}
 
-impl Random {
-    pub fn try_decode(_: &[u8]) -> Result<Self, Error> {
+impl Command for Random {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::UTIL;
+    const CMD_TYPE: CommandType = CommandType::SREQ;
+    const ID: u8 = UTILCommandId::Random as u8;
+
+    fn try_decode_from(_: &mut Cursor<&[u8]>) -> Result<Self, Error> {
         Ok(Random {})
     }
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
-    }
+    fn encode_into(&self, _: &mut Vec<u8>) {}
+}
 
-    pub fn encode_into(&self, _: &mut Vec<u8>) {}
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x00,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SREQ,
-                    subsystem: MTSubsystem::UTIL,
-                    id: UTILCommandId::Random as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
+impl Request for Random {
+    type Response = srsp::Random;
 }
@@ -1,5 +1,5 @@
+use crate::command::Command;
 use crate::error::Error;
-use crate::frame::{CommandCode, MTFrame, MTHeader};
 use crate::types::*;
 use bytes::{Buf, BufMut};
 use std::io::Cursor;
@@ -11,40 +11,21 @@ pub struct CallbackSubCmd {
     pub enables: u32,
 }
 
-impl CallbackSubCmd {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let status = Status::try_decode(&mut cursor)?;
-        let enables = cursor.get_u32_le();
-        Ok(CallbackSubCmd { status, enables })
-    }
+impl Command for CallbackSubCmd {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::UTIL;
+    const CMD_TYPE: CommandType = CommandType::SRSP;
+    const ID: u8 = UTILCommandId::CallbackSubCmd as u8;
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let status = Status::try_decode(cursor)?;
+        let enables = cursor.try_get_u32_le()?;
+        Ok(CallbackSubCmd { status, enables })
     }
 
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.status.encode_into(buffer);
         buffer.put_u32_le(self.enables);
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x05,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SRSP,
-                    subsystem: MTSubsystem::UTIL,
-                    id: UTILCommandId::CallbackSubCmd as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -53,43 +34,24 @@ pub struct GetExtAddr {
     pub ext_address: ExtendedAddress,
 }
 
-impl GetExtAddr {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let address_type = ExtendedAddressType::try_decode(&mut cursor)?;
-        let ext_address = ExtendedAddress::try_decode(&mut cursor)?;
+impl Command for GetExtAddr {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::UTIL;
+    const CMD_TYPE: CommandType = CommandType::SRSP;
+    const ID: u8 = UTILCommandId::GetExtAddr as u8;
+
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let address_type = ExtendedAddressType::try_decode(cursor)?;
+        let ext_address = ExtendedAddress::try_decode(cursor)?;
         Ok(GetExtAddr {
             address_type,
             ext_address,
         })
     }
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
-    }
-
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         self.address_type.encode_into(buffer);
         self.ext_address.encode_into(buffer);
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x09,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SRSP,
-                    subsystem: MTSubsystem::UTIL,
-                    id: UTILCommandId::GetExtAddr as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -99,11 +61,14 @@ pub struct Loopback {
     pub data: Vec<u8>,
 }
 
-impl Loopback {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let repeats = cursor.get_u8();
-        let interval = cursor.get_u32_le();
+impl Command for Loopback {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::UTIL;
+    const CMD_TYPE: CommandType = CommandType::SRSP;
+    const ID: u8 = UTILCommandId::Loopback as u8;
+
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let repeats = cursor.try_get_u8()?;
+        let interval = cursor.try_get_u32_le()?;
 
         let mut data = Vec::new();
         cursor
@@ -117,33 +82,11 @@ impl Loopback {
         })
     }
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
-    }
-
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         buffer.put_u8(self.repeats);
         buffer.put_u32_le(self.interval);
         buffer.extend(self.data.iter());
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x05 + self.data.len() as u8,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SRSP,
-                    subsystem: MTSubsystem::UTIL,
-                    id: UTILCommandId::Loopback as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -151,36 +94,17 @@ pub struct Random {
     pub number: u16,
 }
 
-impl Random {
-    pub fn try_decode(buffer: &[u8]) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(buffer);
-        let number = cursor.get_u16_le();
-        Ok(Random { number })
-    }
+impl Command for Random {
+    const SUBSYSTEM: MTSubsystem = MTSubsystem::UTIL;
+    const CMD_TYPE: CommandType = CommandType::SRSP;
+    const ID: u8 = UTILCommandId::Random as u8;
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        self.encode_into(&mut buffer);
-        buffer
+    fn try_decode_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let number = cursor.try_get_u16_le()?;
+        Ok(Random { number })
     }
 
-    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
         buffer.put_u16_le(self.number);
     }
-
-    pub fn into_mt_frame(self) -> MTFrame {
-        MTFrame {
-            header: MTHeader {
-                length: 0x02,
-                command: CommandCode {
-                    is_extended: false,
-                    cmd_type: CommandType::SRSP,
-                    subsystem: MTSubsystem::UTIL,
-                    id: UTILCommandId::Random as u8,
-                },
-            },
-            extended_header: None,
-            payload: self.encode(),
-        }
-    }
 }
@@ -1,4 +1,5 @@
 use crate::error::Error;
+use crate::phy::{BeaconTiming, PhyDescriptor};
 use bytes::{Buf, BufMut};
 use num_derive::FromPrimitive;
 use num_traits::FromPrimitive;
@@ -110,7 +111,7 @@ pub enum MTExtendedHeaderStatus {
 
 impl MTExtendedHeaderStatus {
     pub fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
-        let value = cursor.get_u8();
+        let value = cursor.try_get_u8()?;
         FromPrimitive::from_u8(value).ok_or(Error::InvalidExtendedHeaderStatus(value))
     }
 
@@ -190,7 +191,7 @@ pub enum Status {
 
 impl Status {
     pub fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
-        let value = cursor.get_u8();
+        let value = cursor.try_get_u8()?;
         FromPrimitive::from_u8(value).ok_or(Error::InvalidStatus(value))
     }
 
@@ -215,7 +216,7 @@ pub enum AddressMode {
 
 impl AddressMode {
     pub fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
-        let value = cursor.get_u8();
+        let value = cursor.try_get_u8()?;
         FromPrimitive::from_u8(value).ok_or(Error::InvalidAddressMode(value))
     }
 
@@ -224,7 +225,7 @@ impl AddressMode {
     }
 }
 
-#[derive(Debug, PartialEq, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
 pub struct ShortAddress {
     pub address: u16,
 }
@@ -232,7 +233,7 @@ pub struct ShortAddress {
 impl ShortAddress {
     pub fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
         Ok(ShortAddress {
-            address: cursor.get_u16_le(),
+            address: cursor.try_get_u16_le()?,
         })
     }
 
@@ -241,7 +242,7 @@ impl ShortAddress {
     }
 }
 
-#[derive(Debug, PartialEq, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
 pub struct ExtendedAddress {
     pub address: [u8; 8],
 }
@@ -261,7 +262,7 @@ impl ExtendedAddress {
     }
 }
 
-#[derive(Debug, PartialEq, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
 pub enum Address {
     Addr16Bit(ShortAddress),
     Addr64Bit(ExtendedAddress),
@@ -335,7 +336,7 @@ bitflags! {
 
 impl TxOption {
     pub fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
-        let value = cursor.get_u8();
+        let value = cursor.try_get_u8()?;
         TxOption::from_bits(value).ok_or(Error::InvalidTxOption(value))
     }
 
@@ -344,6 +345,130 @@ impl TxOption {
     }
 }
 
+bitflags! {
+    #[derive(Debug, PartialEq, Copy, Clone)]
+    pub struct CapabilityInformation: u8 {
+        // The device is capable of becoming a PAN coordinator.
+        const PAN_COORD = 0x01;
+
+        // The device is a full function device.
+        const FFD = 0x02;
+
+        // The device is mains powered rather than battery powered.
+        const MAINS_POWER = 0x04;
+
+        // The receiver is on when the device is idle, otherwise the device is
+        // sleepy and its data has to be sent indirectly.
+        const RX_ON_IDLE = 0x08;
+
+        // The device is capable of sending and receiving secured frames.
+        const SECURITY = 0x40;
+
+        // Request allocation of a short address in the associate procedure.
+        const ALLOC_ADDR = 0x80;
+    }
+}
+
+impl CapabilityInformation {
+    pub fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        // Reserved bits are kept so that the field encodes back unchanged.
+        Ok(CapabilityInformation::from_bits_retain(
+            cursor.try_get_u8()?,
+        ))
+    }
+
+    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+        buffer.put_u8(self.bits());
+    }
+}
+
+bitflags! {
+    #[derive(Debug, PartialEq, Copy, Clone)]
+    pub struct FHIEFlags: u32 {
+        // Unicast timing and frame type header IE.
+        const UTT = 0x00000002;
+
+        // Received signal level header IE.
+        const RSL = 0x00000004;
+
+        // Broadcast timing header IE.
+        const BT = 0x00000008;
+
+        // Unicast schedule payload IE.
+        const US = 0x00010000;
+
+        // Broadcast schedule payload IE.
+        const BS = 0x00020000;
+
+        // PAN information payload IE.
+        const PAN = 0x00040000;
+
+        // Network name payload IE.
+        const NETNAME = 0x00080000;
+
+        // PAN version payload IE.
+        const PANVER = 0x00100000;
+
+        // GTK hash payload IE.
+        const GTKHASH = 0x00200000;
+    }
+}
+
+impl FHIEFlags {
+    pub fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        // Unknown bits are kept so that the field encodes back unchanged.
+        Ok(FHIEFlags::from_bits_retain(cursor.try_get_u32_le()?))
+    }
+
+    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+        buffer.put_u32_le(self.bits());
+    }
+
+    pub fn ie_ids(&self) -> Vec<IEId> {
+        self.iter()
+            .filter_map(|x| IEId::from_u32(x.bits().trailing_zeros()))
+            .collect()
+    }
+}
+
+impl From<IEId> for FHIEFlags {
+    fn from(ie_id: IEId) -> Self {
+        FHIEFlags::from_bits_retain(1 << ie_id as u32)
+    }
+}
+
+impl FromIterator<IEId> for FHIEFlags {
+    fn from_iter<I: IntoIterator<Item = IEId>>(iter: I) -> Self {
+        iter.into_iter().map(FHIEFlags::from).collect()
+    }
+}
+
+// IE identifiers of the enhanced beacon IE list, the value is the bit of the
+// IE in FHIEFlags.
+#[derive(Debug, FromPrimitive, PartialEq, Copy, Clone)]
+pub enum IEId {
+    UTT = 1,
+    RSL = 2,
+    BT = 3,
+    US = 16,
+    BS = 17,
+    PAN = 18,
+    NETNAME = 19,
+    PANVER = 20,
+    GTKHASH = 21,
+}
+
+impl IEId {
+    pub fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let value = cursor.try_get_u8()?;
+        FromPrimitive::from_u8(value).ok_or(Error::InvalidIEId(value))
+    }
+
+    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+        buffer.put_u8(*self as u8);
+    }
+}
+
 #[derive(Debug, FromPrimitive, PartialEq, Copy, Clone)]
 pub enum SecurityLevel {
     NoSecurity = 0x00,
@@ -358,7 +483,7 @@ pub enum SecurityLevel {
 
 impl SecurityLevel {
     pub fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
-        let value = cursor.get_u8();
+        let value = cursor.try_get_u8()?;
         FromPrimitive::from_u8(value).ok_or(Error::InvalidSecurityLevel(value))
     }
 
@@ -377,7 +502,7 @@ pub enum KeyIdMode {
 
 impl KeyIdMode {
     pub fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
-        let value = cursor.get_u8();
+        let value = cursor.try_get_u8()?;
         FromPrimitive::from_u8(value).ok_or(Error::InvalidKeyIdMode(value))
     }
 
@@ -405,6 +530,25 @@ impl KeySource {
     }
 }
 
+#[derive(Debug, FromPrimitive, PartialEq, Copy, Clone)]
+pub enum MACFrameType {
+    Beacon = 0x00,
+    Data = 0x01,
+    Ack = 0x02,
+    Command = 0x03,
+}
+
+impl MACFrameType {
+    pub fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let value = cursor.try_get_u8()?;
+        FromPrimitive::from_u8(value).ok_or(Error::InvalidMACFrameType(value))
+    }
+
+    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+        buffer.put_u8(*self as u8);
+    }
+}
+
 #[derive(Debug, FromPrimitive, PartialEq, Copy, Clone)]
 pub enum WiSUNAsyncFrameType {
     PANAdvert = 0x00,
@@ -419,7 +563,7 @@ pub enum WiSUNAsyncFrameType {
 
 impl WiSUNAsyncFrameType {
     pub fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
-        let value = cursor.get_u8();
+        let value = cursor.try_get_u8()?;
         FromPrimitive::from_u8(value).ok_or(Error::InvalidFrameType(value))
     }
 
@@ -437,7 +581,7 @@ pub enum AssociationStatus {
 
 impl AssociationStatus {
     pub fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
-        let value = cursor.get_u8();
+        let value = cursor.try_get_u8()?;
         FromPrimitive::from_u8(value).ok_or(Error::InvalidAssociationStatus(value))
     }
 
@@ -446,6 +590,33 @@ impl AssociationStatus {
     }
 }
 
+// The status of an AssociateCnf is either the association status sent by the
+// coordinator or a MAC status, e.g. NoAck, when the association failed locally.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum AssociateCnfStatus {
+    Status(Status),
+    Refused(AssociationStatus),
+}
+
+impl AssociateCnfStatus {
+    pub fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let value = cursor.try_get_u8()?;
+        match FromPrimitive::from_u8(value) {
+            Some(AssociationStatus::Successful) | None => FromPrimitive::from_u8(value)
+                .map(AssociateCnfStatus::Status)
+                .ok_or(Error::InvalidStatus(value)),
+            Some(status) => Ok(AssociateCnfStatus::Refused(status)),
+        }
+    }
+
+    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+        match self {
+            AssociateCnfStatus::Status(status) => status.encode_into(buffer),
+            AssociateCnfStatus::Refused(status) => status.encode_into(buffer),
+        }
+    }
+}
+
 #[derive(Debug, FromPrimitive, PartialEq, Copy, Clone)]
 pub enum DisassociateReason {
     Reserved = 0x00,
@@ -455,7 +626,7 @@ pub enum DisassociateReason {
 
 impl DisassociateReason {
     pub fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
-        let value = cursor.get_u8();
+        let value = cursor.try_get_u8()?;
         FromPrimitive::from_u8(value).ok_or(Error::InvalidDisassociationReason(value))
     }
 
@@ -517,7 +688,7 @@ pub enum MACPIBAttributeId {
 
 impl MACPIBAttributeId {
     pub fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
-        let value = cursor.get_u8();
+        let value = cursor.try_get_u8()?;
         FromPrimitive::from_u8(value).ok_or(Error::InvalidMACPIBAttributeId(value))
     }
 
@@ -558,7 +729,7 @@ pub enum FHPIBAttributeId {
 
 impl FHPIBAttributeId {
     pub fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
-        let value = cursor.get_u16_le();
+        let value = cursor.try_get_u16_le()?;
         FromPrimitive::from_u16(value).ok_or(Error::InvalidFHPIBAttributeId(value))
     }
 
@@ -567,6 +738,69 @@ impl FHPIBAttributeId {
     }
 }
 
+#[derive(Debug, FromPrimitive, PartialEq, Copy, Clone)]
+pub enum ChannelFunction {
+    FixedChannel = 0x00,
+    TR51CF = 0x01,
+    DH1CF = 0x02,
+    VendorDefined = 0x03,
+}
+
+impl ChannelFunction {
+    pub fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let value = cursor.try_get_u8()?;
+        FromPrimitive::from_u8(value).ok_or(Error::InvalidChannelFunction(value))
+    }
+
+    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+        buffer.put_u8(*self as u8);
+    }
+}
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct SuperframeSpec {
+    pub beacon_order: u8,
+    pub superframe_order: u8,
+    pub final_cap_slot: u8,
+    pub battery_life_extension: bool,
+    pub pan_coordinator: bool,
+    pub association_permit: bool,
+}
+
+impl SuperframeSpec {
+    pub fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        let value = cursor.try_get_u16_le()?;
+        Ok(SuperframeSpec {
+            beacon_order: (value & 0xf) as u8,
+            superframe_order: ((value >> 4) & 0xf) as u8,
+            final_cap_slot: ((value >> 8) & 0xf) as u8,
+            battery_life_extension: value & 0x1000 != 0,
+            pan_coordinator: value & 0x4000 != 0,
+            association_permit: value & 0x8000 != 0,
+        })
+    }
+
+    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+        let mut value = (self.beacon_order & 0xf) as u16
+            | ((self.superframe_order & 0xf) as u16) << 4
+            | ((self.final_cap_slot & 0xf) as u16) << 8;
+        if self.battery_life_extension {
+            value |= 0x1000;
+        }
+        if self.pan_coordinator {
+            value |= 0x4000;
+        }
+        if self.association_permit {
+            value |= 0x8000;
+        }
+        buffer.put_u16_le(value);
+    }
+
+    pub fn beacon_timing(&self, phy: &PhyDescriptor) -> Result<Option<BeaconTiming>, Error> {
+        phy.beacon_timing(self.beacon_order, self.superframe_order)
+    }
+}
+
 #[derive(Debug, FromPrimitive, PartialEq, Copy, Clone)]
 pub enum SecurityPIBAttributeId {
     KeyTable = 0x71,
@@ -591,7 +825,7 @@ pub enum SecurityPIBAttributeId {
 
 impl SecurityPIBAttributeId {
     pub fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
-        let value = cursor.get_u8();
+        let value = cursor.try_get_u8()?;
         FromPrimitive::from_u8(value).ok_or(Error::InvalidSecurityPIBAttributeId(value))
     }
 
@@ -611,7 +845,7 @@ pub enum ScanType {
 
 impl ScanType {
     pub fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
-        let value = cursor.get_u8();
+        let value = cursor.try_get_u8()?;
         FromPrimitive::from_u8(value).ok_or(Error::InvalidScanType(value))
     }
 
@@ -638,10 +872,15 @@ pub enum PhyId {
 
 impl PhyId {
     pub fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
-        let value = cursor.get_u8();
+        let value = cursor.try_get_u8()?;
         FromPrimitive::from_u8(value).ok_or(Error::InvalidPhyId(value))
     }
 
+    // None for the ids which don't name a PHY.
+    pub fn channel_count(&self) -> Option<u16> {
+        PhyDescriptor::get(*self).map(|x| x.channel_count)
+    }
+
     pub fn encode_into(&self, buffer: &mut Vec<u8>) {
         buffer.put_u8(*self as u8);
     }
@@ -655,7 +894,7 @@ pub enum PermitJoin {
 
 impl PermitJoin {
     pub fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
-        let value = cursor.get_u8();
+        let value = cursor.try_get_u8()?;
         FromPrimitive::from_u8(value).ok_or(Error::InvalidPhyId(value))
     }
 
@@ -672,7 +911,7 @@ pub enum MPMScan {
 
 impl MPMScan {
     pub fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
-        let value = cursor.get_u8();
+        let value = cursor.try_get_u8()?;
         FromPrimitive::from_u8(value).ok_or(Error::InvalidPhyId(value))
     }
 
@@ -689,7 +928,7 @@ pub enum MPMType {
 
 impl MPMType {
     pub fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
-        let value = cursor.get_u8();
+        let value = cursor.try_get_u8()?;
         FromPrimitive::from_u8(value).ok_or(Error::InvalidPhyId(value))
     }
 
@@ -706,7 +945,7 @@ pub enum WiSUNAsyncOperation {
 
 impl WiSUNAsyncOperation {
     pub fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
-        let value = cursor.get_u8();
+        let value = cursor.try_get_u8()?;
         FromPrimitive::from_u8(value).ok_or(Error::InvalidPhyId(value))
     }
 
@@ -724,7 +963,7 @@ pub enum CommEventReason {
 
 impl CommEventReason {
     pub fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
-        let value = cursor.get_u8();
+        let value = cursor.try_get_u8()?;
         FromPrimitive::from_u8(value).ok_or(Error::InvalidPhyId(value))
     }
 
@@ -741,7 +980,7 @@ pub enum ResetType {
 
 impl ResetType {
     pub fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
-        let value = cursor.get_u8();
+        let value = cursor.try_get_u8()?;
         FromPrimitive::from_u8(value).ok_or(Error::InvalidPhyId(value))
     }
 
@@ -758,7 +997,7 @@ pub enum TransportProtocolRevision {
 
 impl TransportProtocolRevision {
     pub fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
-        let value = cursor.get_u8();
+        let value = cursor.try_get_u8()?;
         FromPrimitive::from_u8(value).ok_or(Error::InvalidPhyId(value))
     }
 
@@ -775,7 +1014,7 @@ pub enum ProductIdCode {
 
 impl ProductIdCode {
     pub fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
-        let value = cursor.get_u8();
+        let value = cursor.try_get_u8()?;
         FromPrimitive::from_u8(value).ok_or(Error::InvalidPhyId(value))
     }
 
@@ -795,7 +1034,7 @@ pub enum ResetReason {
 
 impl ResetReason {
     pub fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
-        let value = cursor.get_u8();
+        let value = cursor.try_get_u8()?;
         FromPrimitive::from_u8(value).ok_or(Error::InvalidPhyId(value))
     }
 
@@ -814,7 +1053,7 @@ pub enum SubsystemId {
 
 impl SubsystemId {
     pub fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
-        let value = cursor.get_u8();
+        let value = cursor.try_get_u8()?;
         FromPrimitive::from_u8(value).ok_or(Error::InvalidPhyId(value))
     }
 
@@ -834,7 +1073,7 @@ pub enum ExtendedAddressType {
 
 impl ExtendedAddressType {
     pub fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
-        let value = cursor.get_u8();
+        let value = cursor.try_get_u8()?;
         FromPrimitive::from_u8(value).ok_or(Error::InvalidPhyId(value))
     }
 
@@ -843,12 +1082,26 @@ impl ExtendedAddressType {
     }
 }
 
-#[derive(Debug, PartialEq, Copy, Clone)]
+#[derive(PartialEq, Eq, Copy, Clone, Default)]
 pub struct ChannelsBitMap {
     pub channels: [u8; 17],
 }
 
 impl ChannelsBitMap {
+    pub const MAX_CHANNEL: u8 = 135;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Every channel of the PHY, e.g. for a full ScanReq.
+    pub fn all(phy_id: PhyId) -> Self {
+        match phy_id.channel_count() {
+            Some(count) => (0..count).map(|x| x as u8).collect(),
+            None => Self::new(),
+        }
+    }
+
     pub fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
         let mut channels: [u8; 17] = Default::default();
         cursor
@@ -861,6 +1114,170 @@ impl ChannelsBitMap {
     pub fn encode_into(&self, buffer: &mut Vec<u8>) {
         buffer.extend(self.channels.iter().rev());
     }
+
+    // Channels are numbered from the least significant bit of the last byte.
+    fn position(channel: u8) -> (usize, u8) {
+        (16 - (channel / 8) as usize, 1 << (channel % 8))
+    }
+
+    pub fn contains(&self, channel: u8) -> bool {
+        if channel > ChannelsBitMap::MAX_CHANNEL {
+            return false;
+        }
+
+        let (index, mask) = Self::position(channel);
+        self.channels[index] & mask != 0
+    }
+
+    // Returns whether the channel wasn't in the set yet, like HashSet::insert.
+    pub fn insert(&mut self, channel: u8) -> Result<bool, Error> {
+        if channel > ChannelsBitMap::MAX_CHANNEL {
+            return Err(Error::InvalidChannel(channel));
+        }
+
+        let inserted = !self.contains(channel);
+        let (index, mask) = Self::position(channel);
+        self.channels[index] |= mask;
+        Ok(inserted)
+    }
+
+    // Same as insert, for channels which have to exist on the PHY.
+    pub fn insert_for(&mut self, phy_id: PhyId, channel: u8) -> Result<bool, Error> {
+        match phy_id.channel_count() {
+            Some(count) if (channel as u16) < count => self.insert(channel),
+            _ => Err(Error::InvalidChannel(channel)),
+        }
+    }
+
+    pub fn remove(&mut self, channel: u8) -> bool {
+        let removed = self.contains(channel);
+        if removed {
+            let (index, mask) = Self::position(channel);
+            self.channels[index] &= !mask;
+        }
+        removed
+    }
+
+    pub fn clear(&mut self) {
+        self.channels = Default::default();
+    }
+
+    pub fn len(&self) -> usize {
+        self.channels.iter().map(|x| x.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.channels.iter().all(|x| *x == 0)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = u8> + '_ {
+        (0..=ChannelsBitMap::MAX_CHANNEL).filter(|x| self.contains(*x))
+    }
+
+    pub fn union(&self, other: &ChannelsBitMap) -> ChannelsBitMap {
+        let mut channels = self.channels;
+        channels
+            .iter_mut()
+            .zip(other.channels.iter())
+            .for_each(|(x, y)| *x |= y);
+        ChannelsBitMap { channels }
+    }
+
+    pub fn intersection(&self, other: &ChannelsBitMap) -> ChannelsBitMap {
+        let mut channels = self.channels;
+        channels
+            .iter_mut()
+            .zip(other.channels.iter())
+            .for_each(|(x, y)| *x &= y);
+        ChannelsBitMap { channels }
+    }
+
+    pub fn difference(&self, other: &ChannelsBitMap) -> ChannelsBitMap {
+        self.intersection(&other.complement())
+    }
+
+    pub fn complement(&self) -> ChannelsBitMap {
+        ChannelsBitMap {
+            channels: self.channels.map(|x| !x),
+        }
+    }
+
+    // Channels which don't exist on the PHY, e.g. to validate a ScanReq.
+    pub fn invalid_channels(&self, phy_id: PhyId) -> ChannelsBitMap {
+        self.difference(&ChannelsBitMap::all(phy_id))
+    }
+
+    pub fn validate(&self, phy_id: PhyId) -> Result<(), Error> {
+        PhyDescriptor::try_get(phy_id)?.validate_channels(self)
+    }
+}
+
+impl FromIterator<u8> for ChannelsBitMap {
+    // Channels above MAX_CHANNEL are ignored, use insert to catch them.
+    fn from_iter<I: IntoIterator<Item = u8>>(iter: I) -> Self {
+        let mut channels = ChannelsBitMap::new();
+        for channel in iter {
+            let _ = channels.insert(channel);
+        }
+        channels
+    }
+}
+
+impl Extend<u8> for ChannelsBitMap {
+    fn extend<I: IntoIterator<Item = u8>>(&mut self, iter: I) {
+        for channel in iter {
+            let _ = self.insert(channel);
+        }
+    }
+}
+
+impl std::ops::BitOr for ChannelsBitMap {
+    type Output = ChannelsBitMap;
+
+    fn bitor(self, other: ChannelsBitMap) -> ChannelsBitMap {
+        self.union(&other)
+    }
+}
+
+impl std::ops::BitAnd for ChannelsBitMap {
+    type Output = ChannelsBitMap;
+
+    fn bitand(self, other: ChannelsBitMap) -> ChannelsBitMap {
+        self.intersection(&other)
+    }
+}
+
+impl std::ops::Not for ChannelsBitMap {
+    type Output = ChannelsBitMap;
+
+    fn not(self) -> ChannelsBitMap {
+        self.complement()
+    }
+}
+
+// Printed as channel ranges, e.g. ChannelsBitMap {0-3, 7, 11-26}.
+impl std::fmt::Debug for ChannelsBitMap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut ranges: Vec<(u8, u8)> = Vec::new();
+        for channel in self.iter() {
+            match ranges.last_mut() {
+                Some((_, last)) if *last + 1 == channel => *last = channel,
+                _ => ranges.push((channel, channel)),
+            }
+        }
+
+        write!(f, "ChannelsBitMap {{")?;
+        for (i, (first, last)) in ranges.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            match first == last {
+                true => write!(f, "{}", first)?,
+                false => write!(f, "{}-{}", first, last)?,
+            }
+        }
+        write!(f, "}}")
+    }
 }
 
 #[derive(Debug, FromPrimitive, Copy, Clone)]
@@ -875,7 +1292,7 @@ pub enum ErrorCode {
 
 impl ErrorCode {
     pub fn try_decode(cursor: &mut Cursor<&[u8]>) -> Result<Self, Error> {
-        let value = cursor.get_u8();
+        let value = cursor.try_get_u8()?;
         FromPrimitive::from_u8(value).ok_or(Error::InvalidErrorCode(value))
     }
 